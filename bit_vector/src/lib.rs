@@ -1,14 +1,28 @@
 //! This module will allow to work with bit vectors
 
 extern crate zero_one;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_bytes;
 
+pub mod packed;
+#[cfg(feature = "serde")]
+mod serialize;
+
+pub use packed::BitVector;
+
+use std::iter::Sum;
 use std::ops::{Add, AddAssign};
+use std::ops::{BitAnd, Mul};
 use std::ops::{BitXor, BitXorAssign};
 use std::ops::{Sub, SubAssign};
 
 use zero_one::{One, Zero};
 
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
 pub struct Bit<T> {
     value: T,
 }
@@ -37,6 +51,7 @@ impl<T> Bit<T> {
 impl<T: BitXor<Output = T>> Add for Bit<T> {
     type Output = Bit<T>;
     #[inline]
+    #[allow(clippy::suspicious_arithmetic_impl)]
     fn add(self, other: Bit<T>) -> Bit<T> {
         self ^ other
     }
@@ -51,6 +66,7 @@ impl<T: BitXorAssign<T>> BitXorAssign for Bit<T> {
 
 impl<T: BitXorAssign<T>> AddAssign for Bit<T> {
     #[inline]
+    #[allow(clippy::suspicious_op_assign_impl)]
     fn add_assign(&mut self, other: Bit<T>) {
         *self ^= other
     }
@@ -58,6 +74,7 @@ impl<T: BitXorAssign<T>> AddAssign for Bit<T> {
 
 impl<T: BitXorAssign<T>> SubAssign for Bit<T> {
     #[inline]
+    #[allow(clippy::suspicious_op_assign_impl)]
     fn sub_assign(&mut self, other: Bit<T>) {
         *self ^= other
     }
@@ -76,11 +93,45 @@ impl<T: BitXor<Output = T>> BitXor for Bit<T> {
 impl<T: BitXor<Output = T>> Sub for Bit<T> {
     type Output = Bit<T>;
     #[inline]
+    #[allow(clippy::suspicious_arithmetic_impl)]
     fn sub(self, other: Bit<T>) -> Bit<T> {
         self ^ other
     }
 }
 
+impl<T: BitAnd<Output = T>> Mul for Bit<T> {
+    type Output = Bit<T>;
+    #[inline]
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, other: Bit<T>) -> Bit<T> {
+        Bit {
+            value: self.value & other.value,
+        }
+    }
+}
+
+macro_rules! reference_operator {
+    ($type:ident, $funcname:ident, $bound:ident) => {
+        impl<'a, T: Copy + $bound<Output = T>> $type<&'a Bit<T>> for &'a Bit<T> {
+            type Output = Bit<T>;
+            #[inline]
+            fn $funcname(self, other: &'a Bit<T>) -> Bit<T> {
+                (*self).$funcname(*other)
+            }
+        }
+    };
+}
+
+reference_operator!(Add, add, BitXor);
+reference_operator!(Sub, sub, BitXor);
+reference_operator!(Mul, mul, BitAnd);
+
+impl<T: Zero + BitXor<Output = T>> Sum for Bit<T> {
+    fn sum<I: Iterator<Item = Bit<T>>>(iter: I) -> Bit<T> {
+        iter.fold(Bit::zero(), |acc, bit| acc + bit)
+    }
+}
+
 macro_rules! tests_for_type {
     ($type: ty, $name: ident, $zero: expr, $one: expr) => {
 
@@ -147,6 +198,32 @@ macro_rules! tests_for_type {
                 assert_eq!(one + zero, one);
             }
 
+            #[test]
+            fn multiplication() {
+                let zero: Bit<$type> = Bit::zero();
+                let one: Bit<$type> = Bit::one();
+                assert_eq!(zero * zero, zero);
+                assert_eq!(zero * one, zero);
+                assert_eq!(one * zero, zero);
+                assert_eq!(one * one, one);
+            }
+
+            #[test]
+            fn by_reference() {
+                let zero: Bit<$type> = Bit::zero();
+                let one: Bit<$type> = Bit::one();
+                assert_eq!(&one + &one, zero);
+                assert_eq!(&one - &zero, one);
+                assert_eq!(&one * &one, one);
+            }
+
+            #[test]
+            fn sum() {
+                let one: Bit<$type> = Bit::one();
+                let bits = vec![one, one, one];
+                assert_eq!(bits.into_iter().sum::<Bit<$type>>(), one);
+            }
+
             #[test]
             fn one_plus_one_eq_zero() {
                 let zero: Bit<$type> = Bit::zero();
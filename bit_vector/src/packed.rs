@@ -0,0 +1,368 @@
+//! Packed vectors of bits, stored 64 bits to a word.
+
+use std::ops::{BitAnd, BitXor, BitXorAssign};
+
+const WORD_BITS: usize = 64;
+
+/// A vector over GF(2) that stores its bits packed into `u64` words.
+///
+/// Bits past `len` in the last word are always zero, so words can be
+/// compared and counted directly.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct BitVector {
+    len: usize,
+    words: Vec<u64>,
+}
+
+#[inline]
+fn words_for(len: usize) -> usize {
+    len.div_ceil(WORD_BITS)
+}
+
+impl BitVector {
+    /// The all-zero vector of length `len`
+    pub fn zero(len: usize) -> BitVector {
+        BitVector {
+            len,
+            words: vec![0; words_for(len)],
+        }
+    }
+
+    pub fn from_bools(bits: &[bool]) -> BitVector {
+        let mut vector = BitVector::zero(bits.len());
+        for (i, &bit) in bits.iter().enumerate() {
+            if bit {
+                vector.set(i, true);
+            }
+        }
+        vector
+    }
+
+    /// Construct the vector of length `len` that is one exactly on `support`.
+    ///
+    /// ```
+    /// use bit_vector::BitVector;
+    /// let v = BitVector::from_support(5, &[0, 3]);
+    /// assert_eq!(v.weight(), 2);
+    /// assert!(v.get(3));
+    /// ```
+    pub fn from_support(len: usize, support: &[usize]) -> BitVector {
+        let mut vector = BitVector::zero(len);
+        for &i in support {
+            vector.set(i, true);
+        }
+        vector
+    }
+
+    /// Read `len` bits from bytes, least significant bit first.
+    ///
+    /// Returns `None` if `bytes` has the wrong length or has bits set past
+    /// `len`.
+    pub fn from_bytes(len: usize, bytes: &[u8]) -> Option<BitVector> {
+        if bytes.len() != len.div_ceil(8) {
+            return None;
+        }
+        let mut vector = BitVector::zero(len);
+        for (i, &byte) in bytes.iter().enumerate() {
+            vector.words[i / 8] |= u64::from(byte) << (8 * (i % 8));
+        }
+        let used = len % WORD_BITS;
+        if used != 0 && vector.words.last().is_some_and(|&w| w >> used != 0) {
+            return None;
+        }
+        Some(vector)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The bits packed into `ceil(len / 8)` bytes, least significant bit first
+    pub fn to_bytes(&self) -> Vec<u8> {
+        (0..self.len.div_ceil(8))
+            .map(|i| (self.words[i / 8] >> (8 * (i % 8))) as u8)
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The underlying words, least significant bit first
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    #[inline]
+    pub fn get(&self, idx: usize) -> bool {
+        assert!(idx < self.len, "Index out of bounds");
+        (self.words[idx / WORD_BITS] >> (idx % WORD_BITS)) & 1 == 1
+    }
+
+    #[inline]
+    pub fn set(&mut self, idx: usize, value: bool) {
+        assert!(idx < self.len, "Index out of bounds");
+        let mask = 1u64 << (idx % WORD_BITS);
+        if value {
+            self.words[idx / WORD_BITS] |= mask;
+        } else {
+            self.words[idx / WORD_BITS] &= !mask;
+        }
+    }
+
+    #[inline]
+    pub fn flip(&mut self, idx: usize) {
+        assert!(idx < self.len, "Index out of bounds");
+        self.words[idx / WORD_BITS] ^= 1u64 << (idx % WORD_BITS);
+    }
+
+    /// The Hamming weight: the number of ones in this vector
+    pub fn weight(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// The inner product over GF(2)
+    pub fn dot(&self, other: &BitVector) -> bool {
+        assert_eq!(self.len, other.len, "Vectors should be of equal length");
+        let ones: u32 = self
+            .words
+            .iter()
+            .zip(other.words.iter())
+            .map(|(a, b)| (a & b).count_ones())
+            .sum();
+        ones & 1 == 1
+    }
+
+    /// Iterate over all bits
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            vector: self,
+            idx: 0,
+        }
+    }
+
+    /// Iterate over the indices of the bits that are set
+    pub fn support(&self) -> Support<'_> {
+        Support {
+            words: &self.words,
+            word_idx: 0,
+            current: self.words.first().cloned().unwrap_or(0),
+        }
+    }
+
+    /// Take `len` bits starting at `start`
+    pub fn slice(&self, start: usize, len: usize) -> BitVector {
+        assert!(start + len <= self.len, "Index out of bounds");
+        let mut result = BitVector::zero(len);
+        for i in self
+            .support()
+            .skip_while(|&i| i < start)
+            .take_while(|&i| i < start + len)
+        {
+            result.set(i - start, true);
+        }
+        result
+    }
+
+    /// Add `other & mask` to this vector, word by word.
+    ///
+    /// With `mask` either all zeros or all ones this is a conditional
+    /// addition that does not branch on the condition.
+    pub fn masked_xor_assign(&mut self, other: &BitVector, mask: u64) {
+        assert_eq!(self.len, other.len, "Vectors should be of equal length");
+        for (a, b) in self.words.iter_mut().zip(other.words.iter()) {
+            *a ^= *b & mask;
+        }
+    }
+
+    /// Append the bits of `other` to this vector
+    pub fn extend_from(&mut self, other: &BitVector) {
+        let offset = self.len;
+        self.len += other.len;
+        self.words.resize(words_for(self.len), 0);
+        for i in other.support() {
+            self.set(offset + i, true);
+        }
+    }
+}
+
+pub struct Iter<'a> {
+    vector: &'a BitVector,
+    idx: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        if self.idx < self.vector.len {
+            self.idx += 1;
+            Some(self.vector.get(self.idx - 1))
+        } else {
+            None
+        }
+    }
+}
+
+pub struct Support<'a> {
+    words: &'a [u64],
+    word_idx: usize,
+    current: u64,
+}
+
+impl<'a> Iterator for Support<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.current == 0 {
+            self.word_idx += 1;
+            if self.word_idx >= self.words.len() {
+                return None;
+            }
+            self.current = self.words[self.word_idx];
+        }
+        let bit = self.current.trailing_zeros() as usize;
+        self.current &= self.current - 1;
+        Some(self.word_idx * WORD_BITS + bit)
+    }
+}
+
+impl<'a> BitXorAssign<&'a BitVector> for BitVector {
+    fn bitxor_assign(&mut self, other: &'a BitVector) {
+        assert_eq!(self.len, other.len, "Vectors should be of equal length");
+        for (a, b) in self.words.iter_mut().zip(other.words.iter()) {
+            *a ^= *b;
+        }
+    }
+}
+
+impl<'a> BitXor<&'a BitVector> for &'a BitVector {
+    type Output = BitVector;
+
+    fn bitxor(self, other: &'a BitVector) -> BitVector {
+        let mut result = self.clone();
+        result ^= other;
+        result
+    }
+}
+
+impl<'a> BitAnd<&'a BitVector> for &'a BitVector {
+    type Output = BitVector;
+
+    fn bitand(self, other: &'a BitVector) -> BitVector {
+        assert_eq!(self.len, other.len, "Vectors should be of equal length");
+        BitVector {
+            len: self.len,
+            words: self
+                .words
+                .iter()
+                .zip(other.words.iter())
+                .map(|(a, b)| a & b)
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero() {
+        let v = BitVector::zero(130);
+        assert_eq!(v.len(), 130);
+        assert_eq!(v.words().len(), 3);
+        assert!(v.is_zero());
+        assert_eq!(v.weight(), 0);
+    }
+
+    #[test]
+    fn set_get_flip() {
+        let mut v = BitVector::zero(100);
+        v.set(3, true);
+        v.set(64, true);
+        v.flip(99);
+        assert!(v.get(3));
+        assert!(v.get(64));
+        assert!(v.get(99));
+        assert!(!v.get(4));
+        assert_eq!(v.weight(), 3);
+        v.flip(3);
+        v.set(64, false);
+        assert_eq!(v.weight(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_out_of_bounds() {
+        BitVector::zero(10).get(10);
+    }
+
+    #[test]
+    fn support() {
+        let support = vec![0, 5, 63, 64, 127, 128, 199];
+        let v = BitVector::from_support(200, &support);
+        assert_eq!(v.support().collect::<Vec<_>>(), support);
+        assert_eq!(BitVector::zero(0).support().count(), 0);
+    }
+
+    #[test]
+    fn from_bools() {
+        let v = BitVector::from_bools(&[true, false, true]);
+        assert_eq!(v.iter().collect::<Vec<_>>(), vec![true, false, true]);
+    }
+
+    #[test]
+    fn xor_and_dot() {
+        let a = BitVector::from_support(70, &[1, 2, 65]);
+        let b = BitVector::from_support(70, &[2, 3, 65, 69]);
+        assert_eq!(&a ^ &b, BitVector::from_support(70, &[1, 3, 69]));
+        assert_eq!(&a & &b, BitVector::from_support(70, &[2, 65]));
+        assert!(!a.dot(&b));
+        assert!(a.dot(&BitVector::from_support(70, &[65])));
+    }
+
+    #[test]
+    #[should_panic]
+    fn xor_diff_sized() {
+        let mut a = BitVector::zero(3);
+        a ^= &BitVector::zero(4);
+    }
+
+    #[test]
+    fn masked_xor() {
+        let mut a = BitVector::from_support(70, &[1, 65]);
+        let b = BitVector::from_support(70, &[1, 2]);
+        a.masked_xor_assign(&b, 0);
+        assert_eq!(a, BitVector::from_support(70, &[1, 65]));
+        a.masked_xor_assign(&b, !0);
+        assert_eq!(a, BitVector::from_support(70, &[2, 65]));
+    }
+
+    #[test]
+    fn bytes_roundtrip() {
+        let v = BitVector::from_support(70, &[0, 9, 64, 69]);
+        let bytes = v.to_bytes();
+        assert_eq!(bytes.len(), 9);
+        assert_eq!(&bytes[..2], &[0x01, 0x02]);
+        assert_eq!(BitVector::from_bytes(70, &bytes), Some(v));
+        assert_eq!(BitVector::from_bytes(73, &bytes), None);
+        assert_eq!(BitVector::from_bytes(68, &bytes), None);
+        assert_eq!(BitVector::from_bytes(0, &[]), Some(BitVector::zero(0)));
+    }
+
+    #[test]
+    fn slice_and_extend() {
+        let v = BitVector::from_support(100, &[10, 70, 90]);
+        let s = v.slice(60, 40);
+        assert_eq!(s.support().collect::<Vec<_>>(), vec![10, 30]);
+
+        let mut front = v.slice(0, 50);
+        front.extend_from(&v.slice(50, 50));
+        assert_eq!(front, v);
+    }
+}
@@ -0,0 +1,67 @@
+//! `Serialize` and `Deserialize` for the `serde` feature.
+//!
+//! A `BitVector` is stored as its length and its packed bytes, least
+//! significant bit first, which is a byte string in binary formats.
+
+use packed::BitVector;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Serialize)]
+#[serde(rename = "BitVector")]
+struct PackedRef<'a> {
+    len: usize,
+    #[serde(with = "serde_bytes")]
+    bits: &'a [u8],
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "BitVector")]
+struct Packed {
+    len: usize,
+    #[serde(with = "serde_bytes")]
+    bits: Vec<u8>,
+}
+
+impl Serialize for BitVector {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        PackedRef {
+            len: self.len(),
+            bits: &self.to_bytes(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BitVector {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<BitVector, D::Error> {
+        let packed = Packed::deserialize(deserializer)?;
+        BitVector::from_bytes(packed.len, &packed.bits)
+            .ok_or_else(|| D::Error::custom("the bits do not match the length of the vector"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+    use packed::BitVector;
+    use Bit;
+
+    #[test]
+    fn bit_vector_json() {
+        let v = BitVector::from_support(10, &[0, 9]);
+        let json = serde_json::to_string(&v).unwrap();
+        assert_eq!(json, r#"{"len":10,"bits":[1,2]}"#);
+        assert_eq!(serde_json::from_str::<BitVector>(&json).unwrap(), v);
+        assert!(serde_json::from_str::<BitVector>(r#"{"len":9,"bits":[1,2]}"#).is_err());
+        assert!(serde_json::from_str::<BitVector>(r#"{"len":20,"bits":[1,2]}"#).is_err());
+    }
+
+    #[test]
+    fn bit_json() {
+        let bits = vec![Bit::from(1u8), Bit::from(0u8)];
+        let json = serde_json::to_string(&bits).unwrap();
+        assert_eq!(json, "[1,0]");
+        assert_eq!(serde_json::from_str::<Vec<Bit<u8>>>(&json).unwrap(), bits);
+    }
+}
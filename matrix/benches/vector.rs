@@ -1,4 +1,5 @@
-#![feature(test)]
+#![cfg(nightly)]
+#![cfg_attr(nightly, feature(test))]
 
 extern crate matrix;
 extern crate test;
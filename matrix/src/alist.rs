@@ -0,0 +1,321 @@
+use sparse::SparseBinaryMatrix;
+use std::io::{self, BufRead, Write};
+use text::ReadError;
+
+/// The lines of a reader, with their (one-based) line numbers
+struct Lines<R> {
+    lines: io::Lines<R>,
+    number: usize,
+}
+
+impl<R: BufRead> Lines<R> {
+    /// Read the next line as a list of numbers, skipping blank lines if
+    /// `skip_blank`
+    fn numbers(&mut self, what: &str, skip_blank: bool) -> Result<(usize, Vec<usize>), ReadError> {
+        loop {
+            let line = match self.lines.next() {
+                Some(line) => line?,
+                None => {
+                    return Err(ReadError::parse(
+                        self.number,
+                        format!("unexpected end of input, expected {}", what),
+                    ))
+                }
+            };
+            self.number += 1;
+            if skip_blank && line.trim().is_empty() {
+                continue;
+            }
+            let number = self.number;
+            let values = line
+                .split_whitespace()
+                .map(|token| {
+                    token.parse().map_err(|_| {
+                        ReadError::parse(number, format!("invalid number {:?} in {}", token, what))
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+            return Ok((number, values));
+        }
+    }
+
+    /// Read the next non-blank line, of exactly `count` numbers, or nothing
+    /// if `count` is zero
+    fn exactly(&mut self, count: usize, what: &str) -> Result<Vec<usize>, ReadError> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let (number, values) = self.numbers(what, true)?;
+        if values.len() != count {
+            return Err(ReadError::parse(
+                number,
+                format!(
+                    "expected {} numbers in {}, found {}",
+                    count,
+                    what,
+                    values.len()
+                ),
+            ));
+        }
+        Ok(values)
+    }
+
+    /// Read the one-based positions of the ones of a row or column, padded
+    /// with zeros, and return them zero-based. A blank line is an empty list.
+    fn positions(
+        &mut self,
+        weight: usize,
+        bound: usize,
+        what: &str,
+    ) -> Result<(usize, Vec<usize>), ReadError> {
+        let (number, values) = self.numbers(what, false)?;
+        let mut positions: Vec<usize> = values.into_iter().filter(|&v| v != 0).collect();
+        if positions.len() != weight {
+            return Err(ReadError::parse(
+                number,
+                format!(
+                    "expected {} positions in {}, found {}",
+                    weight,
+                    what,
+                    positions.len()
+                ),
+            ));
+        }
+        if let Some(&p) = positions.iter().find(|&&p| p > bound) {
+            return Err(ReadError::parse(
+                number,
+                format!("position {} in {} exceeds {}", p, what, bound),
+            ));
+        }
+        positions.sort();
+        if positions.windows(2).any(|w| w[0] == w[1]) {
+            return Err(ReadError::parse(
+                number,
+                format!("repeated position in {}", what),
+            ));
+        }
+        Ok((number, positions.into_iter().map(|p| p - 1).collect()))
+    }
+}
+
+/// Check that none of the weights exceeds the declared maximum
+fn check_weights(
+    number: usize,
+    weights: &[usize],
+    maximum: usize,
+    what: &str,
+) -> Result<(), ReadError> {
+    match weights.iter().find(|&&w| w > maximum) {
+        Some(w) => Err(ReadError::parse(
+            number,
+            format!("{} weight {} exceeds the maximum {}", what, w, maximum),
+        )),
+        None => Ok(()),
+    }
+}
+
+impl SparseBinaryMatrix {
+    /// Read a matrix in MacKay's alist format.
+    ///
+    /// The format lists the number of columns and rows, the largest column
+    /// and row weights, the weight of every column and every row, and then
+    /// the one-based positions of the ones in every column and in every row,
+    /// one per line and padded with zeros. The row and column lists should
+    /// describe the same matrix. Blank lines may separate the lines before
+    /// the lists, but within them a blank line is a row or column of weight
+    /// zero.
+    pub fn read_alist<R: BufRead>(reader: R) -> Result<SparseBinaryMatrix, ReadError> {
+        let mut lines = Lines {
+            lines: reader.lines(),
+            number: 0,
+        };
+        let dimensions = lines.exactly(2, "the dimensions")?;
+        let (ncols, nrows) = (dimensions[0], dimensions[1]);
+        let maxima = lines.exactly(2, "the largest weights")?;
+        let column_weights = lines.exactly(ncols, "the column weights")?;
+        check_weights(lines.number, &column_weights, maxima[0], "column")?;
+        let row_weights = lines.exactly(nrows, "the row weights")?;
+        check_weights(lines.number, &row_weights, maxima[1], "row")?;
+
+        let mut rows = vec![Vec::new(); nrows];
+        for (col, &weight) in column_weights.iter().enumerate() {
+            let what = format!("column {}", col + 1);
+            for row in lines.positions(weight, nrows, &what)?.1 {
+                rows[row].push(col);
+            }
+        }
+        for (row, &weight) in row_weights.iter().enumerate() {
+            let what = format!("row {}", row + 1);
+            let (number, positions) = lines.positions(weight, ncols, &what)?;
+            if positions != rows[row] {
+                return Err(ReadError::parse(
+                    number,
+                    format!("{} does not match the column lists", what),
+                ));
+            }
+        }
+        for line in lines.lines {
+            lines.number += 1;
+            if !line?.trim().is_empty() {
+                return Err(ReadError::parse(
+                    lines.number,
+                    "unexpected input after the row lists",
+                ));
+            }
+        }
+        Ok(SparseBinaryMatrix::from_rows(ncols, rows))
+    }
+
+    pub fn from_alist(text: &str) -> Result<SparseBinaryMatrix, ReadError> {
+        SparseBinaryMatrix::read_alist(text.as_bytes())
+    }
+
+    /// Write the matrix in alist format, padding the lists with zeros and
+    /// writing a single zero for an empty list when all of them are
+    pub fn write_alist<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let columns = self.transpose();
+        let column_weights = self.column_weights();
+        let row_weights: Vec<usize> = (0..self.nrows()).map(|i| self.row(i).len()).collect();
+        let max_column = column_weights.iter().cloned().max().unwrap_or(0);
+        let max_row = row_weights.iter().cloned().max().unwrap_or(0);
+        let join = |values: &mut dyn Iterator<Item = usize>| {
+            values.map(|v| v.to_string()).collect::<Vec<_>>().join(" ")
+        };
+        writeln!(writer, "{} {}", self.ncols(), self.nrows())?;
+        writeln!(writer, "{} {}", max_column, max_row)?;
+        for weights in &[&column_weights, &row_weights] {
+            if !weights.is_empty() {
+                writeln!(writer, "{}", join(&mut weights.iter().cloned()))?;
+            }
+        }
+        let padded = |positions: &[usize], width: usize| {
+            join(
+                &mut positions
+                    .iter()
+                    .map(|&p| p + 1)
+                    .chain(std::iter::repeat(0))
+                    .take(width.max(1)),
+            )
+        };
+        for col in 0..columns.nrows() {
+            writeln!(writer, "{}", padded(columns.row(col), max_column))?;
+        }
+        for row in 0..self.nrows() {
+            writeln!(writer, "{}", padded(self.row(row), max_row))?;
+        }
+        Ok(())
+    }
+
+    pub fn to_alist(&self) -> String {
+        let mut text = Vec::new();
+        self.write_alist(&mut text).unwrap();
+        String::from_utf8(text).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The parity-check matrix of the [7, 4] Hamming code
+    const HAMMING: &str = "7 3
+3 4
+1 1 2 1 2 2 3
+4 4 4
+1 0 0
+2 0 0
+1 2 0
+3 0 0
+1 3 0
+2 3 0
+1 2 3
+1 3 5 7
+2 3 6 7
+4 5 6 7
+";
+
+    fn parse_error<T: ::std::fmt::Debug>(result: Result<T, ReadError>) -> (usize, String) {
+        match result {
+            Err(ReadError::Parse { line, message }) => (line, message),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let h = SparseBinaryMatrix::from_alist(HAMMING).unwrap();
+        assert_eq!(h.nrows(), 3);
+        assert_eq!(h.ncols(), 7);
+        assert_eq!(h.row(0), &[0, 2, 4, 6]);
+        assert_eq!(h.row(2), &[3, 4, 5, 6]);
+        assert_eq!(h.to_alist(), HAMMING);
+    }
+
+    #[test]
+    fn unpadded() {
+        let text = HAMMING.replace(" 0", "").replace("\n3 4\n", "\n\n3 4\n\n");
+        assert_eq!(
+            SparseBinaryMatrix::from_alist(&text).unwrap(),
+            SparseBinaryMatrix::from_alist(HAMMING).unwrap()
+        );
+    }
+
+    #[test]
+    fn zero_weights() {
+        // a zero column and a zero row
+        let m = SparseBinaryMatrix::from_rows(3, vec![vec![0, 2], vec![], vec![2]]);
+        let text = m.to_alist();
+        assert_eq!(
+            text,
+            "3 3\n2 2\n1 0 2\n2 0 1\n1 0\n0 0\n1 3\n1 3\n0 0\n3 0\n"
+        );
+        assert_eq!(SparseBinaryMatrix::from_alist(&text).unwrap(), m);
+        let unpadded = "3 3\n2 2\n1 0 2\n2 0 1\n1\n\n1 3\n1 3\n\n3\n";
+        assert_eq!(SparseBinaryMatrix::from_alist(unpadded).unwrap(), m);
+
+        for &(nrows, ncols) in &[(2, 3), (0, 4), (4, 0), (0, 0)] {
+            let zero = SparseBinaryMatrix::from_rows(ncols, vec![Vec::new(); nrows]);
+            let text = zero.to_alist();
+            assert!(text.lines().all(|line| !line.is_empty()), "{}", text);
+            assert_eq!(
+                SparseBinaryMatrix::from_alist(&text).unwrap(),
+                zero,
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let (line, message) = parse_error(SparseBinaryMatrix::from_alist("7 x\n"));
+        assert_eq!(line, 1);
+        assert_eq!(message, "invalid number \"x\" in the dimensions");
+        let truncated: Vec<&str> = HAMMING.lines().take(11).collect();
+        let (line, message) = parse_error(SparseBinaryMatrix::from_alist(&truncated.join("\n")));
+        assert_eq!(line, 11);
+        assert_eq!(message, "unexpected end of input, expected row 1");
+        let (line, message) = parse_error(SparseBinaryMatrix::from_alist(
+            &HAMMING.replace("1 3 5 7", "1 3 5 6"),
+        ));
+        assert_eq!(line, 12);
+        assert_eq!(message, "row 1 does not match the column lists");
+        let (line, message) = parse_error(SparseBinaryMatrix::from_alist(
+            &HAMMING.replace("1 2 3\n", "1 2 4\n"),
+        ));
+        assert_eq!(line, 11);
+        assert_eq!(message, "position 4 in column 7 exceeds 3");
+        let (line, message) = parse_error(SparseBinaryMatrix::from_alist(
+            &HAMMING.replace("2 3 0", "2 0 0"),
+        ));
+        assert_eq!(line, 10);
+        assert_eq!(message, "expected 2 positions in column 6, found 1");
+        let (line, message) = parse_error(SparseBinaryMatrix::from_alist(
+            &HAMMING.replace("4 4 4", "4 5 4"),
+        ));
+        assert_eq!(line, 4);
+        assert_eq!(message, "row weight 5 exceeds the maximum 4");
+        let (line, _) = parse_error(SparseBinaryMatrix::from_alist(&format!("{}\n1\n", HAMMING)));
+        assert_eq!(line, 16);
+    }
+}
@@ -0,0 +1,413 @@
+extern crate bit_vector;
+use self::bit_vector::BitVector;
+use bit_matrix::BitMatrix;
+use std::error;
+use std::fmt;
+use std::io;
+
+/// The first bytes of every file in the binary format
+pub const MAGIC: [u8; 4] = *b"DCDR";
+
+/// The version of the binary format written by this crate
+pub const VERSION: u16 = 1;
+
+/// Magic, version, kind, payload length and checksum
+const HEADER_LEN: usize = 4 + 2 + 2 + 8 + 4;
+
+/// An error while reading the binary format
+#[derive(Debug)]
+pub enum BinaryError {
+    Io(io::Error),
+    /// The data does not start with `MAGIC`
+    NotBinary,
+    UnsupportedVersion(u16),
+    /// The data holds another type than the one requested
+    WrongKind {
+        expected: u16,
+        found: u16,
+    },
+    /// The data ends before the payload does
+    Truncated,
+    /// The payload was damaged: its checksum does not match the header
+    Checksum {
+        expected: u32,
+        found: u32,
+    },
+    /// The payload is intact but does not describe a valid value
+    Invalid(String),
+}
+
+impl fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BinaryError::Io(ref e) => write!(f, "{}", e),
+            BinaryError::NotBinary => write!(f, "not in the binary format"),
+            BinaryError::UnsupportedVersion(v) => write!(f, "unsupported format version {}", v),
+            BinaryError::WrongKind { expected, found } => {
+                write!(
+                    f,
+                    "expected an object of kind {}, found {}",
+                    expected, found
+                )
+            }
+            BinaryError::Truncated => write!(f, "unexpected end of data"),
+            BinaryError::Checksum { expected, found } => write!(
+                f,
+                "checksum mismatch: expected {:08x}, found {:08x}",
+                expected, found
+            ),
+            BinaryError::Invalid(ref message) => write!(f, "invalid data: {}", message),
+        }
+    }
+}
+
+impl error::Error for BinaryError {}
+
+impl From<io::Error> for BinaryError {
+    fn from(e: io::Error) -> BinaryError {
+        BinaryError::Io(e)
+    }
+}
+
+/// CRC-32 as used by zlib and PNG
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (i, entry) in table.iter_mut().enumerate() {
+        *entry = (0..8).fold(i as u32, |c, _| {
+            if c & 1 == 1 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            }
+        });
+    }
+    !bytes.iter().fold(!0u32, |c, &b| {
+        table[((c ^ u32::from(b)) & 0xff) as usize] ^ (c >> 8)
+    })
+}
+
+/// Whether the data starts like the binary format
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+/// Builds a payload of little-endian integers and packed bits
+#[derive(Clone, Debug, Default)]
+pub struct BinaryWriter {
+    bytes: Vec<u8>,
+}
+
+impl BinaryWriter {
+    pub fn new() -> BinaryWriter {
+        BinaryWriter { bytes: Vec::new() }
+    }
+
+    pub fn put_u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn put_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn put_u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn put_usize(&mut self, value: usize) {
+        self.put_u64(value as u64);
+    }
+
+    pub fn put_bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    /// The length followed by the packed bits
+    pub fn put_bit_vector(&mut self, v: &BitVector) {
+        self.put_usize(v.len());
+        self.put_bytes(&v.to_bytes());
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Reads back what a `BinaryWriter` wrote
+#[derive(Clone, Debug)]
+pub struct BinaryReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> BinaryReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> BinaryReader<'a> {
+        BinaryReader { bytes }
+    }
+
+    pub fn get_bytes(&mut self, len: usize) -> Result<&'a [u8], BinaryError> {
+        if self.bytes.len() < len {
+            return Err(BinaryError::Truncated);
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    pub fn get_u16(&mut self) -> Result<u16, BinaryError> {
+        let mut buf = [0; 2];
+        buf.copy_from_slice(self.get_bytes(2)?);
+        Ok(u16::from_le_bytes(buf))
+    }
+
+    pub fn get_u32(&mut self) -> Result<u32, BinaryError> {
+        let mut buf = [0; 4];
+        buf.copy_from_slice(self.get_bytes(4)?);
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    pub fn get_u64(&mut self) -> Result<u64, BinaryError> {
+        let mut buf = [0; 8];
+        buf.copy_from_slice(self.get_bytes(8)?);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    /// A length or count, which can not exceed the remaining data times
+    /// `per_byte`, so corrupt sizes fail early instead of allocating
+    pub fn get_len(&mut self, per_byte: usize) -> Result<usize, BinaryError> {
+        let len = self.get_u64()?;
+        if len > (self.bytes.len() as u64).saturating_mul(per_byte as u64) {
+            return Err(BinaryError::Truncated);
+        }
+        Ok(len as usize)
+    }
+
+    pub fn get_bit_vector(&mut self) -> Result<BitVector, BinaryError> {
+        let len = self.get_len(8)?;
+        let bytes = self.get_bytes(len.div_ceil(8))?;
+        BitVector::from_bytes(len, bytes)
+            .ok_or_else(|| BinaryError::Invalid("bits set past the end of a vector".to_string()))
+    }
+
+    /// Check that all data was read
+    pub fn finish(self) -> Result<(), BinaryError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(BinaryError::Invalid(format!(
+                "{} trailing bytes",
+                self.bytes.len()
+            )))
+        }
+    }
+}
+
+/// Types with a representation in the binary format.
+///
+/// A file holds a header with `MAGIC`, the format version, the `KIND` of
+/// the object, the payload length and the CRC-32 of the payload, followed
+/// by the payload itself.
+pub trait BinaryFormat: Sized {
+    /// Identifies the type of object in the header
+    const KIND: u16;
+
+    fn write_payload(&self, writer: &mut BinaryWriter);
+
+    fn read_payload(reader: &mut BinaryReader) -> Result<Self, BinaryError>;
+
+    fn to_binary(&self) -> Vec<u8> {
+        let mut payload = BinaryWriter::new();
+        self.write_payload(&mut payload);
+        let payload = payload.into_bytes();
+        let mut writer = BinaryWriter::new();
+        writer.put_bytes(&MAGIC);
+        writer.put_u16(VERSION);
+        writer.put_u16(Self::KIND);
+        writer.put_usize(payload.len());
+        writer.put_u32(crc32(&payload));
+        writer.put_bytes(&payload);
+        writer.into_bytes()
+    }
+
+    fn from_binary(bytes: &[u8]) -> Result<Self, BinaryError> {
+        if bytes.len() < HEADER_LEN || !is_binary(bytes) {
+            return Err(BinaryError::NotBinary);
+        }
+        let mut reader = BinaryReader::new(&bytes[MAGIC.len()..]);
+        let version = reader.get_u16()?;
+        if version != VERSION {
+            return Err(BinaryError::UnsupportedVersion(version));
+        }
+        let kind = reader.get_u16()?;
+        if kind != Self::KIND {
+            return Err(BinaryError::WrongKind {
+                expected: Self::KIND,
+                found: kind,
+            });
+        }
+        let len = reader.get_len(1)?;
+        let expected = reader.get_u32()?;
+        let payload = reader.get_bytes(len)?;
+        reader.finish()?;
+        let found = crc32(payload);
+        if found != expected {
+            return Err(BinaryError::Checksum { expected, found });
+        }
+        let mut reader = BinaryReader::new(payload);
+        let value = Self::read_payload(&mut reader)?;
+        reader.finish()?;
+        Ok(value)
+    }
+}
+
+/// The most rows a binary matrix without columns is read with
+const MAX_EMPTY_ROWS: u64 = 1 << 20;
+
+/// The dimensions and field (always 2) followed by the packed rows
+impl BinaryFormat for BitMatrix {
+    const KIND: u16 = 1;
+
+    fn write_payload(&self, writer: &mut BinaryWriter) {
+        writer.put_usize(self.nrows());
+        writer.put_usize(self.ncols());
+        writer.put_u64(2);
+        for row in self.rows() {
+            writer.put_bytes(&row.to_bytes());
+        }
+    }
+
+    fn read_payload(reader: &mut BinaryReader) -> Result<BitMatrix, BinaryError> {
+        let nrows = reader.get_u64()?;
+        let ncols = reader.get_u64()?;
+        let field = reader.get_u64()?;
+        if field != 2 {
+            return Err(BinaryError::Invalid(format!(
+                "a binary matrix can not be over GF({})",
+                field
+            )));
+        }
+        let row_len = (ncols as usize).div_ceil(8);
+        if row_len == 0 {
+            // the rows of a matrix without columns take no data, so their
+            // number can not be checked against its length
+            if nrows > MAX_EMPTY_ROWS {
+                return Err(BinaryError::Invalid(format!(
+                    "a matrix without columns can have at most {} rows",
+                    MAX_EMPTY_ROWS
+                )));
+            }
+        } else if nrows > (reader.bytes.len() / row_len) as u64 {
+            return Err(BinaryError::Truncated);
+        }
+        let ncols = ncols as usize;
+        let mut rows = Vec::with_capacity(nrows.min(MAX_EMPTY_ROWS) as usize);
+        for _ in 0..nrows {
+            let row = BitVector::from_bytes(ncols, reader.get_bytes(row_len)?)
+                .ok_or_else(|| BinaryError::Invalid("bits set past the last column".to_string()))?;
+            rows.push(row);
+        }
+        Ok(BitMatrix::from_rows(ncols, rows))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> BitMatrix {
+        BitMatrix::from_text("matrix 3 10\n1000000001\n0110000000\n1111111111\n").unwrap()
+    }
+
+    #[test]
+    fn checksum() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn matrix_roundtrip() {
+        let m = example();
+        let bytes = m.to_binary();
+        assert!(is_binary(&bytes));
+        // header, dimensions and field, and two bytes per row
+        assert_eq!(bytes.len(), 20 + 24 + 3 * 2);
+        assert_eq!(BitMatrix::from_binary(&bytes).unwrap(), m);
+        // through the text format and back
+        let text = BitMatrix::from_binary(&bytes).unwrap().to_text();
+        assert_eq!(BitMatrix::from_text(&text).unwrap().to_binary(), bytes);
+        let empty = BitMatrix::zero(0, 7);
+        assert_eq!(BitMatrix::from_binary(&empty.to_binary()).unwrap(), empty);
+    }
+
+    #[test]
+    fn values() {
+        let mut writer = BinaryWriter::new();
+        writer.put_u16(0xbeef);
+        writer.put_u32(7);
+        writer.put_bit_vector(&BitVector::from_support(12, &[0, 11]));
+        let bytes = writer.into_bytes();
+        assert_eq!(bytes.len(), 2 + 4 + 8 + 2);
+        let mut reader = BinaryReader::new(&bytes);
+        assert_eq!(reader.get_u16().unwrap(), 0xbeef);
+        assert_eq!(reader.get_u32().unwrap(), 7);
+        assert_eq!(
+            reader.get_bit_vector().unwrap(),
+            BitVector::from_support(12, &[0, 11])
+        );
+        reader.finish().unwrap();
+    }
+
+    #[test]
+    fn damaged_data() {
+        let bytes = example().to_binary();
+        match BitMatrix::from_binary(&bytes[..bytes.len() - 1]) {
+            Err(BinaryError::Truncated) => (),
+            other => panic!("{:?}", other),
+        }
+        let mut flipped = bytes.clone();
+        flipped[30] ^= 4;
+        match BitMatrix::from_binary(&flipped) {
+            Err(BinaryError::Checksum { .. }) => (),
+            other => panic!("{:?}", other),
+        }
+        let mut version = bytes.clone();
+        version[4] = 9;
+        match BitMatrix::from_binary(&version) {
+            Err(BinaryError::UnsupportedVersion(9)) => (),
+            other => panic!("{:?}", other),
+        }
+        let mut kind = bytes.clone();
+        kind[6] = 5;
+        match BitMatrix::from_binary(&kind) {
+            Err(BinaryError::WrongKind {
+                expected: 1,
+                found: 5,
+            }) => (),
+            other => panic!("{:?}", other),
+        }
+        match BitMatrix::from_binary(b"101\n011\n") {
+            Err(BinaryError::NotBinary) => (),
+            other => panic!("{:?}", other),
+        }
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(BitMatrix::from_binary(&trailing).is_err());
+    }
+
+    #[test]
+    fn empty_rows() {
+        let header = |nrows: u64| {
+            let mut writer = BinaryWriter::new();
+            writer.put_u64(nrows);
+            writer.put_u64(0);
+            writer.put_u64(2);
+            writer.into_bytes()
+        };
+        match BitMatrix::read_payload(&mut BinaryReader::new(&header(u64::MAX))) {
+            Err(BinaryError::Invalid(_)) => (),
+            other => panic!("{:?}", other),
+        }
+        let empty = BitMatrix::read_payload(&mut BinaryReader::new(&header(5))).unwrap();
+        assert_eq!((empty.nrows(), empty.ncols()), (5, 0));
+        assert_eq!(BitMatrix::from_binary(&empty.to_binary()).unwrap(), empty);
+    }
+}
@@ -0,0 +1,573 @@
+extern crate bit_vector;
+extern crate zero_one;
+use self::bit_vector::BitVector;
+use self::zero_one::{One, Zero};
+use error::{unwrap, MatrixError};
+use matrix::Matrix;
+use shape::Shape;
+use sparse::SparseBinaryMatrix;
+use std::hint::black_box;
+use vector::Vector;
+
+/// A dense matrix over GF(2), stored as packed rows.
+///
+/// Row operations work on whole words, which makes this the representation
+/// of choice for Gaussian elimination on large binary matrices.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitMatrix {
+    ncols: usize,
+    rows: Vec<BitVector>,
+}
+
+impl BitMatrix {
+    pub fn zero(nrows: usize, ncols: usize) -> BitMatrix {
+        BitMatrix {
+            ncols,
+            rows: vec![BitVector::zero(ncols); nrows],
+        }
+    }
+
+    pub fn identity(size: usize) -> BitMatrix {
+        let mut matrix = BitMatrix::zero(size, size);
+        for i in 0..size {
+            matrix.rows[i].set(i, true);
+        }
+        matrix
+    }
+
+    pub fn from_rows(ncols: usize, rows: Vec<BitVector>) -> BitMatrix {
+        unwrap(BitMatrix::try_from_rows(ncols, rows))
+    }
+
+    /// Build a matrix from rows that should all have `ncols` bits
+    pub fn try_from_rows(ncols: usize, rows: Vec<BitVector>) -> Result<BitMatrix, MatrixError> {
+        if let Some(index) = rows.iter().position(|row| row.len() != ncols) {
+            return Err(MatrixError::Ragged {
+                index,
+                expected: ncols,
+                found: rows[index].len(),
+            });
+        }
+        Ok(BitMatrix { ncols, rows })
+    }
+
+    pub fn nrows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn ncols(&self) -> usize {
+        self.ncols
+    }
+
+    pub fn shape(&self) -> Shape {
+        Shape::new(self.nrows(), self.ncols)
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        self.rows[row].get(col)
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: bool) {
+        self.rows[row].set(col, value)
+    }
+
+    pub fn row(&self, idx: usize) -> &BitVector {
+        &self.rows[idx]
+    }
+
+    pub fn rows(&self) -> &[BitVector] {
+        &self.rows
+    }
+
+    pub(crate) fn into_rows(self) -> Vec<BitVector> {
+        self.rows
+    }
+
+    pub fn swap_rows(&mut self, a: usize, b: usize) {
+        self.rows.swap(a, b);
+    }
+
+    /// Add row `source` to row `target`
+    pub fn add_row(&mut self, target: usize, source: usize) {
+        assert_ne!(target, source, "Adding a row to itself clears it");
+        if target < source {
+            let (head, tail) = self.rows.split_at_mut(source);
+            head[target] ^= &tail[0];
+        } else {
+            let (head, tail) = self.rows.split_at_mut(target);
+            tail[0] ^= &head[source];
+        }
+    }
+
+    /// Take the columns `start..start + len`
+    pub fn columns(&self, start: usize, len: usize) -> BitMatrix {
+        BitMatrix {
+            ncols: len,
+            rows: self.rows.iter().map(|row| row.slice(start, len)).collect(),
+        }
+    }
+
+    pub fn select_columns(&self, indices: &[usize]) -> BitMatrix {
+        unwrap(self.try_select_columns(indices))
+    }
+
+    /// Take the columns at `indices`, in that order, or give an error if one
+    /// is out of range
+    pub fn try_select_columns(&self, indices: &[usize]) -> Result<BitMatrix, MatrixError> {
+        if let Some(&index) = indices.iter().find(|&&index| index >= self.ncols) {
+            return Err(MatrixError::Selection {
+                index,
+                len: self.ncols,
+            });
+        }
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let mut selected = BitVector::zero(indices.len());
+                for (i, &index) in indices.iter().enumerate() {
+                    selected.set(i, row.get(index));
+                }
+                selected
+            })
+            .collect();
+        Ok(BitMatrix {
+            ncols: indices.len(),
+            rows,
+        })
+    }
+
+    /// Glue the other matrix to the right of this matrix
+    pub fn augment(&mut self, other: BitMatrix) {
+        unwrap(self.try_augment(other))
+    }
+
+    /// Glue the other matrix to the right of this matrix, if they have the
+    /// same number of rows
+    pub fn try_augment(&mut self, other: BitMatrix) -> Result<(), MatrixError> {
+        self.shape().check_augment(other.shape())?;
+        for (row, other) in self.rows.iter_mut().zip(&other.rows) {
+            row.extend_from(other);
+        }
+        self.ncols += other.ncols;
+        Ok(())
+    }
+
+    /// Put the other matrix below this matrix
+    pub fn stack(&mut self, other: BitMatrix) {
+        unwrap(self.try_stack(other))
+    }
+
+    /// Put the other matrix below this matrix, if they have the same number
+    /// of columns
+    pub fn try_stack(&mut self, other: BitMatrix) -> Result<(), MatrixError> {
+        self.shape().check_stack(other.shape())?;
+        self.rows.extend(other.rows);
+        Ok(())
+    }
+
+    /// Multiply the matrix by a column vector: `M v`
+    pub fn mul_vector(&self, v: &BitVector) -> BitVector {
+        unwrap(self.try_mul_vector(v))
+    }
+
+    /// `M v`, or an error if the length of `v` is not the number of columns
+    pub fn try_mul_vector(&self, v: &BitVector) -> Result<BitVector, MatrixError> {
+        self.shape().check_product(Shape::column(v.len()))?;
+        let mut result = BitVector::zero(self.nrows());
+        for (i, row) in self.rows.iter().enumerate() {
+            if row.dot(v) {
+                result.set(i, true);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Multiply a row vector by the matrix: `v M`
+    pub fn vector_mul(&self, v: &BitVector) -> BitVector {
+        unwrap(self.try_vector_mul(v))
+    }
+
+    /// `v M`, or an error if the length of `v` is not the number of rows
+    pub fn try_vector_mul(&self, v: &BitVector) -> Result<BitVector, MatrixError> {
+        Shape::row(v.len()).check_product(self.shape())?;
+        let mut result = BitVector::zero(self.ncols);
+        for i in v.support() {
+            result ^= &self.rows[i];
+        }
+        Ok(result)
+    }
+
+    pub fn transpose(&self) -> BitMatrix {
+        let mut result = BitMatrix::zero(self.ncols, self.nrows());
+        for (i, row) in self.rows.iter().enumerate() {
+            for j in row.support() {
+                result.rows[j].set(i, true);
+            }
+        }
+        result
+    }
+
+    /// Use Gauss-Jordan elimination to turn the leftmost `nrows x nrows`
+    /// block into the identity.
+    ///
+    /// Returns `false`, leaving the matrix partially reduced, if that block
+    /// is singular.
+    pub fn reduce_systematic(&mut self) -> bool {
+        let nrows = self.nrows();
+        assert!(nrows <= self.ncols, "The matrix should not be tall");
+        for col in 0..nrows {
+            let pivot = match (col..nrows).find(|&row| self.rows[row].get(col)) {
+                Some(pivot) => pivot,
+                None => return false,
+            };
+            self.swap_rows(col, pivot);
+            for row in 0..nrows {
+                if row != col && self.rows[row].get(col) {
+                    self.add_row(row, col);
+                }
+            }
+        }
+        true
+    }
+
+    /// Bring the matrix in reduced row echelon form, with the zero rows at
+    /// the bottom.
+    ///
+    /// Returns the pivot columns, one for every non-zero row; their number
+    /// is the rank.
+    pub fn reduce_row_echelon(&mut self) -> Vec<usize> {
+        let nrows = self.nrows();
+        let mut pivots = Vec::new();
+        for col in 0..self.ncols {
+            let rank = pivots.len();
+            if rank == nrows {
+                break;
+            }
+            let pivot = match (rank..nrows).find(|&row| self.rows[row].get(col)) {
+                Some(pivot) => pivot,
+                None => continue,
+            };
+            self.swap_rows(rank, pivot);
+            for row in 0..nrows {
+                if row != rank && self.rows[row].get(col) {
+                    self.add_row(row, rank);
+                }
+            }
+            pivots.push(col);
+        }
+        pivots
+    }
+
+    pub fn rank(&self) -> usize {
+        self.clone().reduce_row_echelon().len()
+    }
+
+    /// A basis of the vectors `v` with `M v = 0`, as the rows of a matrix.
+    ///
+    /// For a parity-check matrix this is a generator matrix of the code, and
+    /// vice versa.
+    pub fn null_space(&self) -> BitMatrix {
+        let mut reduced = self.clone();
+        let pivots = reduced.reduce_row_echelon();
+        let mut is_pivot = vec![false; self.ncols];
+        for &col in &pivots {
+            is_pivot[col] = true;
+        }
+        // every free column gives a basis vector, with the pivot variables
+        // fixed by the rows that contain that column
+        let rows = (0..self.ncols)
+            .filter(|&col| !is_pivot[col])
+            .map(|free| {
+                let mut v = BitVector::from_support(self.ncols, &[free]);
+                for (row, &col) in pivots.iter().enumerate() {
+                    if reduced.rows[row].get(free) {
+                        v.set(col, true);
+                    }
+                }
+                v
+            })
+            .collect();
+        BitMatrix::from_rows(self.ncols, rows)
+    }
+
+    /// Like `reduce_systematic`, but without branches or memory accesses
+    /// that depend on the entries of the matrix.
+    ///
+    /// Every row is added to the pivot row under a mask, instead of
+    /// searching for and swapping in a pivot. Only the result reveals
+    /// whether the block was singular.
+    pub fn reduce_systematic_constant_time(&mut self) -> bool {
+        let nrows = self.nrows();
+        assert!(nrows <= self.ncols, "The matrix should not be tall");
+        let mut singular = 0u64;
+        for col in 0..nrows {
+            for row in col + 1..nrows {
+                let missing = self.bit_mask(col, col) ^ !0;
+                let mask = missing & self.bit_mask(row, col);
+                let (head, tail) = self.rows.split_at_mut(row);
+                head[col].masked_xor_assign(&tail[0], mask);
+            }
+            singular |= self.bit_mask(col, col) ^ !0;
+            for row in 0..nrows {
+                if row == col {
+                    continue;
+                }
+                let mask = self.bit_mask(row, col);
+                if row < col {
+                    let (head, tail) = self.rows.split_at_mut(col);
+                    head[row].masked_xor_assign(&tail[0], mask);
+                } else {
+                    let (head, tail) = self.rows.split_at_mut(row);
+                    tail[0].masked_xor_assign(&head[col], mask);
+                }
+            }
+        }
+        singular == 0
+    }
+
+    /// All ones if the entry is set, all zeros otherwise.
+    ///
+    /// The barrier keeps the compiler from branching on the entry instead.
+    #[inline]
+    fn bit_mask(&self, row: usize, col: usize) -> u64 {
+        let word = self.rows[row].words()[col / 64];
+        black_box(0u64.wrapping_sub((word >> (col % 64)) & 1))
+    }
+
+    pub fn from_matrix<T: Zero + PartialEq>(matrix: &Matrix<T>) -> BitMatrix {
+        let zero = T::zero();
+        let mut result = BitMatrix::zero(matrix.nrows(), matrix.ncols());
+        for col in 0..matrix.ncols() {
+            for row in 0..matrix.nrows() {
+                if matrix[col][row] != zero {
+                    result.rows[row].set(col, true);
+                }
+            }
+        }
+        result
+    }
+
+    pub fn to_matrix<T: Zero + One>(&self) -> Matrix<T> {
+        Matrix::from_vec(
+            (0..self.ncols)
+                .map(|col| {
+                    Vector::from_vec(
+                        self.rows
+                            .iter()
+                            .map(|row| if row.get(col) { T::one() } else { T::zero() })
+                            .collect(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    pub fn from_sparse(matrix: &SparseBinaryMatrix) -> BitMatrix {
+        BitMatrix {
+            ncols: matrix.ncols(),
+            rows: (0..matrix.nrows())
+                .map(|i| BitVector::from_support(matrix.ncols(), matrix.row(i)))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> BitMatrix {
+        BitMatrix::from_rows(
+            5,
+            vec![
+                BitVector::from_support(5, &[0, 1, 4]),
+                BitVector::from_support(5, &[1, 2]),
+                BitVector::from_support(5, &[0, 3]),
+            ],
+        )
+    }
+
+    #[test]
+    fn dimensions_and_entries() {
+        let m = example();
+        assert_eq!(m.nrows(), 3);
+        assert_eq!(m.ncols(), 5);
+        assert!(m.get(0, 4));
+        assert!(!m.get(1, 0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_unequal_rows() {
+        BitMatrix::from_rows(3, vec![BitVector::zero(3), BitVector::zero(4)]);
+    }
+
+    #[test]
+    fn row_operations() {
+        let mut m = example();
+        m.add_row(2, 0);
+        assert_eq!(m.row(2), &BitVector::from_support(5, &[1, 3, 4]));
+        m.add_row(0, 1);
+        assert_eq!(m.row(0), &BitVector::from_support(5, &[0, 2, 4]));
+        m.swap_rows(0, 1);
+        assert_eq!(m.row(0), &BitVector::from_support(5, &[1, 2]));
+    }
+
+    #[test]
+    fn matrix_roundtrip() {
+        let m = example();
+        let dense: Matrix<i32> = m.to_matrix();
+        assert_eq!(dense[4][0], 1);
+        assert_eq!(BitMatrix::from_matrix(&dense), m);
+        assert_eq!(m.transpose().to_matrix::<i32>(), dense.transpose());
+    }
+
+    #[test]
+    fn sparse_conversion() {
+        let sparse = SparseBinaryMatrix::from_rows(3, vec![vec![0, 2], vec![1]]);
+        let m = BitMatrix::from_sparse(&sparse);
+        assert_eq!(m.to_matrix::<i32>(), sparse.to_matrix());
+    }
+
+    #[test]
+    fn products() {
+        let m = example();
+        let v = BitVector::from_support(5, &[0, 2]);
+        assert_eq!(m.mul_vector(&v), BitVector::from_support(3, &[0, 1, 2]));
+        let u = BitVector::from_support(3, &[0, 2]);
+        assert_eq!(m.vector_mul(&u), BitVector::from_support(5, &[1, 3, 4]));
+        assert!(m.try_mul_vector(&u).is_err());
+        assert!(m.try_vector_mul(&v).is_err());
+        assert_eq!(
+            BitMatrix::try_from_rows(5, vec![v, u]),
+            Err(MatrixError::Ragged {
+                index: 1,
+                expected: 5,
+                found: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn columns() {
+        let m = example().columns(1, 3);
+        assert_eq!(m.ncols(), 3);
+        assert_eq!(m.row(2), &BitVector::from_support(3, &[2]));
+    }
+
+    #[test]
+    fn select_columns() {
+        let m = example();
+        let selected = m.select_columns(&[4, 0, 4]);
+        assert_eq!(selected.ncols(), 3);
+        for row in 0..3 {
+            assert_eq!(selected.get(row, 0), m.get(row, 4));
+            assert_eq!(selected.get(row, 1), m.get(row, 0));
+            assert_eq!(selected.get(row, 2), m.get(row, 4));
+        }
+        assert_eq!(m.select_columns(&[1, 2, 3]), m.columns(1, 3));
+        assert_eq!(
+            m.try_select_columns(&[5]),
+            Err(MatrixError::Selection { index: 5, len: 5 })
+        );
+    }
+
+    #[test]
+    fn augment_and_stack() {
+        let mut m = example();
+        m.augment(BitMatrix::identity(3));
+        assert_eq!(m.shape(), Shape::new(3, 8));
+        assert_eq!(m.columns(0, 5), example());
+        assert_eq!(m.columns(5, 3), BitMatrix::identity(3));
+        assert!(m.try_augment(BitMatrix::zero(2, 1)).is_err());
+
+        m.stack(BitMatrix::zero(2, 8));
+        assert_eq!(m.nrows(), 5);
+        assert!(m.row(4).is_zero());
+        assert!(m.try_stack(BitMatrix::zero(1, 5)).is_err());
+    }
+
+    #[test]
+    fn reduce_systematic() {
+        let original = example();
+        let mut m = original.clone();
+        assert!(m.reduce_systematic());
+        assert_eq!(m.columns(0, 3), BitMatrix::identity(3));
+        // the row space is unchanged: every original row is a combination
+        for row in original.rows() {
+            let combination = m.vector_mul(&row.slice(0, 3));
+            assert_eq!(&combination, row);
+        }
+    }
+
+    #[test]
+    fn reduce_row_echelon() {
+        let mut m = BitMatrix::from_rows(
+            4,
+            vec![
+                BitVector::from_support(4, &[1, 2]),
+                BitVector::from_support(4, &[1, 3]),
+                BitVector::from_support(4, &[2, 3]),
+            ],
+        );
+        assert_eq!(m.rank(), 2);
+        assert_eq!(m.reduce_row_echelon(), vec![1, 2]);
+        assert_eq!(m.row(0), &BitVector::from_support(4, &[1, 3]));
+        assert_eq!(m.row(1), &BitVector::from_support(4, &[2, 3]));
+        assert!(m.row(2).is_zero());
+    }
+
+    #[test]
+    fn null_space() {
+        let m = example();
+        let kernel = m.null_space();
+        assert_eq!(kernel.nrows(), 2);
+        assert_eq!(kernel.rank(), 2);
+        for v in kernel.rows() {
+            assert!(m.mul_vector(v).is_zero());
+        }
+        // the kernel of the kernel is the row space again
+        let back = kernel.null_space();
+        assert_eq!(back.rank(), 3);
+        for row in m.rows() {
+            assert!(kernel.mul_vector(row).is_zero());
+        }
+        assert_eq!(BitMatrix::identity(3).null_space().nrows(), 0);
+        assert_eq!(BitMatrix::zero(1, 3).null_space().rank(), 3);
+    }
+
+    #[test]
+    fn reduce_systematic_constant_time() {
+        let mut m = example();
+        let mut expected = example();
+        assert!(m.reduce_systematic_constant_time());
+        assert!(expected.reduce_systematic());
+        // the reduced row echelon form is unique
+        assert_eq!(m, expected);
+    }
+
+    #[test]
+    fn reduce_systematic_constant_time_singular() {
+        let mut m = BitMatrix::from_rows(
+            3,
+            vec![
+                BitVector::from_support(3, &[0, 1]),
+                BitVector::from_support(3, &[0, 1, 2]),
+            ],
+        );
+        assert!(!m.reduce_systematic_constant_time());
+    }
+
+    #[test]
+    fn reduce_systematic_singular() {
+        let mut m = BitMatrix::from_rows(
+            3,
+            vec![
+                BitVector::from_support(3, &[0, 1]),
+                BitVector::from_support(3, &[0, 1, 2]),
+            ],
+        );
+        assert!(!m.reduce_systematic());
+    }
+}
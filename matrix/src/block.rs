@@ -0,0 +1,321 @@
+extern crate bit_vector;
+extern crate zero_one;
+use self::bit_vector::BitVector;
+use self::zero_one::{One, Zero};
+use bit_matrix::BitMatrix;
+use error::{unwrap, MatrixError};
+use matrix::Matrix;
+use shape::Shape;
+use std::ops;
+use vector::Vector;
+
+/// Check that a grid of blocks, given row by row, has as many blocks in
+/// every row, and that the blocks in a row have the same number of rows and
+/// those in a column the same number of columns
+fn check_grid(shapes: &[Vec<Shape>]) -> Result<(), MatrixError> {
+    let first = match shapes.first() {
+        Some(first) => first,
+        None => return Ok(()),
+    };
+    for (index, row) in shapes.iter().enumerate() {
+        if row.len() != first.len() {
+            return Err(MatrixError::Blocks {
+                row: index,
+                expected: first.len(),
+                found: row.len(),
+            });
+        }
+        for (shape, above) in row.iter().zip(first) {
+            Shape::new(row[0].rows, above.cols).check_same(*shape, "align")?;
+        }
+    }
+    Ok(())
+}
+
+impl<T> Matrix<T> {
+    /// The Kronecker product `A ⊗ B`: the block matrix with `a_ij B` as
+    /// block `(i, j)`
+    pub fn kronecker(&self, other: &Matrix<T>) -> Matrix<T>
+    where
+        for<'a> &'a T: ops::Mul<Output = T>,
+    {
+        let mut columns = Vec::with_capacity(self.ncols() * other.ncols());
+        for a in 0..self.ncols() {
+            for b in 0..other.ncols() {
+                let column: Vector<T> = self[a]
+                    .iter()
+                    .flat_map(|x| other[b].iter().map(move |y| x * y))
+                    .collect();
+                columns.push(column);
+            }
+        }
+        Matrix::from_vec(columns)
+    }
+
+    /// The Kronecker product of `n` copies of this matrix, which is the
+    /// `1 x 1` identity for `n = 0`
+    pub fn kronecker_power(&self, n: usize) -> Matrix<T>
+    where
+        T: Zero + One,
+        for<'a> &'a T: ops::Mul<Output = T>,
+    {
+        (0..n).fold(Matrix::identity(1), |power, _| power.kronecker(self))
+    }
+
+    pub fn from_blocks(grid: Vec<Vec<Matrix<T>>>) -> Matrix<T> {
+        unwrap(Matrix::try_from_blocks(grid))
+    }
+
+    /// Assemble a matrix from a grid of blocks given row by row, or give an
+    /// error if the blocks do not line up
+    pub fn try_from_blocks(grid: Vec<Vec<Matrix<T>>>) -> Result<Matrix<T>, MatrixError> {
+        let shapes: Vec<Vec<Shape>> = grid
+            .iter()
+            .map(|row| row.iter().map(|block| block.shape()).collect())
+            .collect();
+        check_grid(&shapes)?;
+        let mut result = Matrix::from_vec(vec![]);
+        for row in grid {
+            let mut blocks = Matrix::from_vec(vec![]);
+            for block in row {
+                blocks.try_augment(block)?;
+            }
+            result.try_stack(blocks)?;
+        }
+        Ok(result)
+    }
+}
+
+impl<T: Zero> Matrix<T> {
+    /// The matrix with the blocks along its diagonal and zeros elsewhere
+    pub fn block_diagonal(blocks: Vec<Matrix<T>>) -> Matrix<T> {
+        let nrows: usize = blocks.iter().map(|block| block.nrows()).sum();
+        let mut columns = Vec::new();
+        let mut above = 0;
+        for block in blocks {
+            let below = nrows - above - block.nrows();
+            let height = block.nrows();
+            for column in block.into_columns() {
+                let column: Vector<T> = (0..above)
+                    .map(|_| T::zero())
+                    .chain(column)
+                    .chain((0..below).map(|_| T::zero()))
+                    .collect();
+                columns.push(column);
+            }
+            above += height;
+        }
+        Matrix::from_vec(columns)
+    }
+}
+
+impl BitMatrix {
+    /// The Kronecker product `A ⊗ B`: the block matrix with `a_ij B` as
+    /// block `(i, j)`
+    pub fn kronecker(&self, other: &BitMatrix) -> BitMatrix {
+        let ncols = self.ncols() * other.ncols();
+        let mut rows = Vec::with_capacity(self.nrows() * other.nrows());
+        for a in self.rows() {
+            for b in other.rows() {
+                let mut row = BitVector::zero(ncols);
+                for i in a.support() {
+                    for j in b.support() {
+                        row.set(i * other.ncols() + j, true);
+                    }
+                }
+                rows.push(row);
+            }
+        }
+        BitMatrix::from_rows(ncols, rows)
+    }
+
+    /// The Kronecker product of `n` copies of this matrix, which is the
+    /// `1 x 1` identity for `n = 0`
+    pub fn kronecker_power(&self, n: usize) -> BitMatrix {
+        (0..n).fold(BitMatrix::identity(1), |power, _| power.kronecker(self))
+    }
+
+    pub fn from_blocks(grid: Vec<Vec<BitMatrix>>) -> BitMatrix {
+        unwrap(BitMatrix::try_from_blocks(grid))
+    }
+
+    /// Assemble a matrix from a grid of blocks given row by row, or give an
+    /// error if the blocks do not line up
+    pub fn try_from_blocks(grid: Vec<Vec<BitMatrix>>) -> Result<BitMatrix, MatrixError> {
+        let shapes: Vec<Vec<Shape>> = grid
+            .iter()
+            .map(|row| row.iter().map(|block| block.shape()).collect())
+            .collect();
+        check_grid(&shapes)?;
+        let ncols = shapes
+            .first()
+            .map_or(0, |row| row.iter().map(|shape| shape.cols).sum());
+        let mut result = BitMatrix::zero(0, ncols);
+        for (row, shapes) in grid.into_iter().zip(shapes) {
+            let mut blocks = BitMatrix::zero(shapes.first().map_or(0, |shape| shape.rows), 0);
+            for block in row {
+                blocks.try_augment(block)?;
+            }
+            result.try_stack(blocks)?;
+        }
+        Ok(result)
+    }
+
+    /// The matrix with the blocks along its diagonal and zeros elsewhere
+    pub fn block_diagonal(blocks: Vec<BitMatrix>) -> BitMatrix {
+        let ncols: usize = blocks.iter().map(|block| block.ncols()).sum();
+        let mut rows = Vec::new();
+        let mut before = 0;
+        for block in blocks {
+            let after = ncols - before - block.ncols();
+            for row in block.rows() {
+                let mut padded = BitVector::zero(before);
+                padded.extend_from(row);
+                padded.extend_from(&BitVector::zero(after));
+                rows.push(padded);
+            }
+            before += block.ncols();
+        }
+        BitMatrix::from_rows(ncols, rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate rand;
+    use self::rand::{Rng, SeedableRng, XorShiftRng};
+    use super::*;
+
+    fn small(rows: usize, cols: usize, rng: &mut XorShiftRng) -> Matrix<i32> {
+        Matrix::random_with(rows, cols, rng, |rng| rng.gen_range(-3, 4))
+    }
+
+    #[test]
+    fn kronecker() {
+        // [1 2]     [0 5]
+        // [3 4] (x) [6 7]
+        let a = Matrix::from_vec(vec![
+            Vector::from_vec(vec![1, 3]),
+            Vector::from_vec(vec![2, 4]),
+        ]);
+        let b = Matrix::from_vec(vec![
+            Vector::from_vec(vec![0, 6]),
+            Vector::from_vec(vec![5, 7]),
+        ]);
+        let k = a.kronecker(&b);
+        assert_eq!(k.shape(), Shape::new(4, 4));
+        assert_eq!(k[0].as_slice(), [0, 6, 0, 18]);
+        assert_eq!(k[3].as_slice(), [10, 14, 20, 28]);
+
+        // the mixed-product property (A ⊗ B)(C ⊗ D) = AC ⊗ BD
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        let (a, b) = (small(2, 3, &mut rng), small(3, 2, &mut rng));
+        let (c, d) = (small(3, 2, &mut rng), small(2, 4, &mut rng));
+        assert_eq!(
+            &a.kronecker(&b) * &c.kronecker(&d),
+            (&a * &c).kronecker(&(&b * &d))
+        );
+        let odd = |m: &Matrix<i32>| {
+            let columns = (0..m.ncols())
+                .map(|col| m[col].iter().map(|x| x & 1).collect())
+                .collect();
+            Matrix::from_vec(columns)
+        };
+        let (a, b) = (odd(&a), odd(&b));
+        let bits = BitMatrix::from_matrix(&a).kronecker(&BitMatrix::from_matrix(&b));
+        assert_eq!(bits.shape(), Shape::new(6, 6));
+        assert_eq!(bits, BitMatrix::from_matrix(&a.kronecker(&b)));
+    }
+
+    #[test]
+    fn kronecker_power() {
+        // the kernel of polar codes; row i of its m-th power has weight
+        // 2^(weight of i), and its rows of weight at least 2^(m - r) span
+        // the Reed-Muller code RM(r, m)
+        let f = BitMatrix::from_rows(
+            2,
+            vec![
+                BitVector::from_support(2, &[0]),
+                BitVector::from_support(2, &[0, 1]),
+            ],
+        );
+        let g = f.kronecker_power(3);
+        assert_eq!(g.shape(), Shape::new(8, 8));
+        for (i, row) in g.rows().iter().enumerate() {
+            assert_eq!(row.weight(), 1 << (i as u32).count_ones());
+        }
+        assert_eq!(f.kronecker_power(0), BitMatrix::identity(1));
+        assert_eq!(f.kronecker_power(1), f);
+        assert_eq!(
+            BitMatrix::from_matrix(&f.to_matrix::<i32>().kronecker_power(3)),
+            g
+        );
+    }
+
+    #[test]
+    fn block_diagonal() {
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        let (a, b) = (small(2, 3, &mut rng), small(1, 2, &mut rng));
+        let m = Matrix::block_diagonal(vec![a.clone(), b.clone()]);
+        assert_eq!(m.shape(), Shape::new(3, 5));
+        assert_eq!(m.get_segment(0, 0, 2, 3), a);
+        assert_eq!(m.get_segment(2, 3, 1, 2), b);
+        assert_eq!(m.get_segment(0, 3, 2, 2), Matrix::zero(2, 2));
+        assert_eq!(m.get_segment(2, 0, 1, 3), Matrix::zero(1, 3));
+
+        let bits =
+            BitMatrix::block_diagonal(vec![BitMatrix::from_matrix(&a), BitMatrix::from_matrix(&b)]);
+        assert_eq!(bits, BitMatrix::from_matrix(&m));
+        assert_eq!(
+            BitMatrix::block_diagonal(vec![BitMatrix::identity(2); 3]),
+            BitMatrix::identity(6)
+        );
+    }
+
+    #[test]
+    fn from_blocks() {
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        let (a, b) = (small(2, 3, &mut rng), small(2, 1, &mut rng));
+        let (c, d) = (small(4, 3, &mut rng), small(4, 1, &mut rng));
+        let m = Matrix::from_blocks(vec![vec![a.clone(), b.clone()], vec![c.clone(), d.clone()]]);
+        assert_eq!(m.shape(), Shape::new(6, 4));
+        assert_eq!(m.get_segment(0, 0, 2, 3), a);
+        assert_eq!(m.get_segment(0, 3, 2, 1), b);
+        assert_eq!(m.get_segment(2, 0, 4, 3), c);
+        assert_eq!(m.get_segment(2, 3, 4, 1), d);
+
+        let bits = |m: &Matrix<i32>| BitMatrix::from_matrix(m);
+        assert_eq!(
+            BitMatrix::from_blocks(vec![vec![bits(&a), bits(&b)], vec![bits(&c), bits(&d)]]),
+            bits(&m)
+        );
+        assert_eq!(Matrix::<i32>::from_blocks(vec![]).shape(), Shape::new(0, 0));
+        assert_eq!(BitMatrix::from_blocks(vec![]).shape(), Shape::new(0, 0));
+
+        // the rows have the same width, but the blocks do not line up
+        let misaligned = vec![vec![a.clone(), b.clone()], vec![d.clone(), c.clone()]];
+        assert_eq!(
+            Matrix::try_from_blocks(misaligned),
+            Err(MatrixError::Dimensions {
+                operation: "align",
+                left: Shape::new(4, 3),
+                right: Shape::new(4, 1),
+            })
+        );
+        assert_eq!(
+            BitMatrix::try_from_blocks(vec![vec![bits(&a), bits(&b)], vec![bits(&c)]]),
+            Err(MatrixError::Blocks {
+                row: 1,
+                expected: 2,
+                found: 1,
+            })
+        );
+        assert_eq!(
+            Matrix::try_from_blocks(vec![vec![a.clone()], vec![]])
+                .unwrap_err()
+                .to_string(),
+            "block row 1 has 0 blocks, expected 1"
+        );
+        assert!(Matrix::try_from_blocks(vec![vec![a, c]]).is_err());
+    }
+}
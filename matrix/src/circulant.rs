@@ -0,0 +1,606 @@
+extern crate zero_one;
+use self::zero_one::{One, Zero};
+use inverse::Inverse;
+use matrix::Matrix;
+use std::mem;
+use std::ops;
+use vector::Vector;
+
+/// A circulant matrix, in which every row is the previous row rotated one
+/// position to the right.
+///
+/// Only the first row `(a_0, ..., a_{p-1})` is stored. It is identified with
+/// the polynomial `a(x)` in `T[x] / (x^p - 1)`, so that multiplying a row
+/// vector `v` by the circulant is the polynomial product `v(x) a(x)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Circulant<T> {
+    row: Vector<T>,
+}
+
+/// Add `a(x) b(x) mod x^p - 1` to `acc`, where `a(x)` is given by the `p`
+/// entries of `a` starting at `offset`.
+fn add_cyclic_product<T>(acc: &mut [T], a: &Vector<T>, offset: usize, b: &Vector<T>)
+where
+    for<'b> &'b T: ops::Mul<Output = T>,
+    T: ops::Add<Output = T> + Zero + PartialEq,
+{
+    let p = b.len();
+    let zero = T::zero();
+    for i in 0..p {
+        let coefficient = &a[offset + i];
+        if *coefficient == zero {
+            continue;
+        }
+        for j in 0..p {
+            let k = if i + j >= p { i + j - p } else { i + j };
+            let sum = mem::replace(&mut acc[k], T::zero());
+            acc[k] = sum + coefficient * &b[j];
+        }
+    }
+}
+
+impl<T> Circulant<T> {
+    pub fn from_row(row: Vector<T>) -> Circulant<T> {
+        assert!(!row.is_empty(), "A circulant should have at least one row");
+        Circulant { row }
+    }
+
+    pub fn size(&self) -> usize {
+        self.row.len()
+    }
+
+    /// The first row, or equivalently the coefficients of `a(x)`
+    pub fn row(&self) -> &Vector<T> {
+        &self.row
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> &T {
+        let p = self.size();
+        assert!(row < p && col < p, "Index out of bounds");
+        &self.row[(col + p - row) % p]
+    }
+}
+
+impl<T: Zero> Circulant<T> {
+    pub fn zero(size: usize) -> Circulant<T> {
+        Circulant::from_row(Vector::from_vec((0..size).map(|_| T::zero()).collect()))
+    }
+}
+
+impl<T: Zero + One> Circulant<T> {
+    pub fn identity(size: usize) -> Circulant<T> {
+        Circulant::from_row(Vector::from_vec(
+            (0..size)
+                .map(|i| if i == 0 { T::one() } else { T::zero() })
+                .collect(),
+        ))
+    }
+}
+
+impl<T: Clone> Circulant<T> {
+    /// The transpose, which is the circulant of `a(x^-1)`
+    pub fn transpose(&self) -> Circulant<T> {
+        let p = self.size();
+        Circulant::from_row(Vector::from_vec(
+            (0..p).map(|j| self.row[(p - j) % p].clone()).collect(),
+        ))
+    }
+
+    /// Expand into a dense matrix
+    pub fn to_matrix(&self) -> Matrix<T> {
+        let p = self.size();
+        Matrix::from_vec(
+            (0..p)
+                .map(|col| Vector::from_vec((0..p).map(|row| self.get(row, col).clone()).collect()))
+                .collect(),
+        )
+    }
+}
+
+type Polynomial<T> = Vec<T>;
+
+fn trim<T: Zero + PartialEq>(mut a: Polynomial<T>) -> Polynomial<T> {
+    let zero = T::zero();
+    while a.last() == Some(&zero) {
+        a.pop();
+    }
+    a
+}
+
+fn poly_sub<T>(a: &[T], b: &[T]) -> Polynomial<T>
+where
+    T: Clone + Zero + PartialEq + ops::Sub<Output = T>,
+{
+    let len = if a.len() > b.len() { a.len() } else { b.len() };
+    trim(
+        (0..len)
+            .map(|i| {
+                let x = a.get(i).cloned().unwrap_or_else(T::zero);
+                let y = b.get(i).cloned().unwrap_or_else(T::zero);
+                x - y
+            })
+            .collect(),
+    )
+}
+
+fn poly_mul<T>(a: &[T], b: &[T]) -> Polynomial<T>
+where
+    T: Clone + Zero + PartialEq + ops::Add<Output = T> + ops::Mul<Output = T>,
+{
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut result = vec![T::zero(); a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            let sum = mem::replace(&mut result[i + j], T::zero());
+            result[i + j] = sum + x.clone() * y.clone();
+        }
+    }
+    trim(result)
+}
+
+/// Divide `a` by `b`, if the leading coefficient of `b` is invertible.
+fn poly_divmod<T>(a: &[T], b: &[T]) -> Option<(Polynomial<T>, Polynomial<T>)>
+where
+    T: Clone
+        + Zero
+        + PartialEq
+        + Inverse
+        + ops::Add<Output = T>
+        + ops::Sub<Output = T>
+        + ops::Mul<Output = T>,
+{
+    let lead = b
+        .last()
+        .expect("Division by the zero polynomial")
+        .inverse()?;
+    let mut remainder = a.to_vec();
+    if remainder.len() < b.len() {
+        return Some((Vec::new(), remainder));
+    }
+    let mut quotient = vec![T::zero(); remainder.len() - b.len() + 1];
+    while remainder.len() >= b.len() {
+        let shift = remainder.len() - b.len();
+        let factor = remainder.last().unwrap().clone() * lead.clone();
+        for (i, y) in b.iter().enumerate() {
+            let x = mem::replace(&mut remainder[shift + i], T::zero());
+            remainder[shift + i] = x - factor.clone() * y.clone();
+        }
+        quotient[shift] = factor;
+        // the leading coefficient is now zero
+        remainder.pop();
+        remainder = trim(remainder);
+    }
+    Some((trim(quotient), remainder))
+}
+
+impl<T> Circulant<T>
+where
+    T: Clone
+        + Zero
+        + One
+        + PartialEq
+        + Inverse
+        + ops::Add<Output = T>
+        + ops::Sub<Output = T>
+        + ops::Mul<Output = T>,
+{
+    /// Compute the inverse with the extended Euclidean algorithm on
+    /// `a(x)` and `x^p - 1`.
+    ///
+    /// This finds the inverse whenever it exists if `T` is a field; over
+    /// other rings it may give up when it meets a non-invertible coefficient.
+    pub fn inverse(&self) -> Option<Circulant<T>> {
+        let p = self.size();
+        let mut modulus = vec![T::zero(); p + 1];
+        modulus[0] = T::zero() - T::one();
+        modulus[p] = T::one();
+
+        let (mut r0, mut r1) = (modulus, trim((0..p).map(|i| self.row[i].clone()).collect()));
+        let (mut s0, mut s1) = (Vec::new(), vec![T::one()]);
+        while !r1.is_empty() {
+            let (quotient, remainder) = poly_divmod(&r0, &r1)?;
+            let s = poly_sub(&s0, &poly_mul(&quotient, &s1));
+            r0 = mem::replace(&mut r1, remainder);
+            s0 = mem::replace(&mut s1, s);
+        }
+
+        // r0 is the gcd, which should be a unit
+        if r0.len() != 1 {
+            return None;
+        }
+        let scale = r0[0].inverse()?;
+        let mut coefficients = vec![T::zero(); p];
+        for (i, c) in s0.into_iter().enumerate() {
+            let sum = mem::replace(&mut coefficients[i % p], T::zero());
+            coefficients[i % p] = sum + c * scale.clone();
+        }
+        Some(Circulant::from_row(Vector::from_vec(coefficients)))
+    }
+}
+
+impl<'a, T> ops::Mul<&'a Circulant<T>> for &'a Circulant<T>
+where
+    for<'b> &'b T: ops::Mul<Output = T>,
+    T: ops::Add<Output = T> + Zero + PartialEq,
+{
+    type Output = Circulant<T>;
+
+    fn mul(self, other: &'a Circulant<T>) -> Circulant<T> {
+        assert_eq!(
+            self.size(),
+            other.size(),
+            "Circulants should be of the same size"
+        );
+        let mut result: Vec<T> = (0..self.size()).map(|_| T::zero()).collect();
+        add_cyclic_product::<T>(&mut result, &self.row, 0, &other.row);
+        Circulant::from_row(Vector::from_vec(result))
+    }
+}
+
+impl<T> ops::Mul<Circulant<T>> for Circulant<T>
+where
+    for<'a> &'a Circulant<T>: ops::Mul<Output = Circulant<T>>,
+{
+    type Output = Circulant<T>;
+
+    fn mul(self, other: Circulant<T>) -> Circulant<T> {
+        &self * &other
+    }
+}
+
+macro_rules! pointwise_operator {
+    ($type:ident, $funcname:ident, $operator:tt) => {
+        impl<'a, T> ops::$type<&'a Circulant<T>> for &'a Circulant<T>
+            where for<'b> &'b Vector<T>: ops::$type<Output=Vector<T>>
+        {
+            type Output = Circulant<T>;
+
+            fn $funcname(self, other: &'a Circulant<T>) -> Circulant<T> {
+                Circulant::from_row(&self.row $operator &other.row)
+            }
+        }
+    }
+}
+
+pointwise_operator!(Add, add, +);
+pointwise_operator!(Sub, sub, -);
+
+/// Multiply a row vector by the circulant, i.e. compute `v(x) a(x)`
+impl<'a, T> ops::Mul<&'a Circulant<T>> for &'a Vector<T>
+where
+    for<'b> &'b T: ops::Mul<Output = T>,
+    T: ops::Add<Output = T> + Zero + PartialEq,
+{
+    type Output = Vector<T>;
+
+    fn mul(self, other: &'a Circulant<T>) -> Vector<T> {
+        assert_eq!(
+            self.len(),
+            other.size(),
+            "The length of vector should match the size of the circulant"
+        );
+        let mut result: Vec<T> = (0..other.size()).map(|_| T::zero()).collect();
+        add_cyclic_product::<T>(&mut result, self, 0, &other.row);
+        Vector::from_vec(result)
+    }
+}
+
+/// A matrix built from a grid of circulant blocks of equal size
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuasiCyclicMatrix<T> {
+    blocks: Vec<Vec<Circulant<T>>>,
+}
+
+impl<T> QuasiCyclicMatrix<T> {
+    /// Build the matrix from its block rows
+    pub fn from_blocks(blocks: Vec<Vec<Circulant<T>>>) -> QuasiCyclicMatrix<T> {
+        assert!(
+            !blocks.is_empty() && !blocks[0].is_empty(),
+            "A quasi-cyclic matrix should have at least one block"
+        );
+        let block_cols = blocks[0].len();
+        let block_size = blocks[0][0].size();
+        for block_row in &blocks {
+            assert_eq!(
+                block_row.len(),
+                block_cols,
+                "All block rows must be the same length"
+            );
+            for block in block_row {
+                assert_eq!(
+                    block.size(),
+                    block_size,
+                    "All blocks must be of the same size"
+                );
+            }
+        }
+        QuasiCyclicMatrix { blocks }
+    }
+
+    pub fn block_size(&self) -> usize {
+        self.blocks[0][0].size()
+    }
+
+    pub fn block_rows(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn block_cols(&self) -> usize {
+        self.blocks[0].len()
+    }
+
+    pub fn nrows(&self) -> usize {
+        self.block_rows() * self.block_size()
+    }
+
+    pub fn ncols(&self) -> usize {
+        self.block_cols() * self.block_size()
+    }
+
+    pub fn block(&self, block_row: usize, block_col: usize) -> &Circulant<T> {
+        &self.blocks[block_row][block_col]
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> &T {
+        let p = self.block_size();
+        assert!(
+            row < self.nrows() && col < self.ncols(),
+            "Index out of bounds"
+        );
+        self.blocks[row / p][col / p].get(row % p, col % p)
+    }
+}
+
+impl<T: Clone> QuasiCyclicMatrix<T> {
+    pub fn transpose(&self) -> QuasiCyclicMatrix<T> {
+        QuasiCyclicMatrix {
+            blocks: (0..self.block_cols())
+                .map(|j| {
+                    (0..self.block_rows())
+                        .map(|i| self.blocks[i][j].transpose())
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    /// Expand into a dense matrix
+    pub fn to_matrix(&self) -> Matrix<T> {
+        let mut result: Option<Matrix<T>> = None;
+        for block_row in &self.blocks {
+            let mut row = block_row[0].to_matrix();
+            for block in &block_row[1..] {
+                row.augment(block.to_matrix());
+            }
+            match result {
+                Some(ref mut matrix) => matrix.stack(row),
+                None => result = Some(row),
+            }
+        }
+        result.unwrap()
+    }
+}
+
+impl<'a, T> ops::Mul<&'a QuasiCyclicMatrix<T>> for &'a QuasiCyclicMatrix<T>
+where
+    for<'b> &'b T: ops::Mul<Output = T>,
+    T: ops::Add<Output = T> + Zero + PartialEq,
+{
+    type Output = QuasiCyclicMatrix<T>;
+
+    fn mul(self, other: &'a QuasiCyclicMatrix<T>) -> QuasiCyclicMatrix<T> {
+        assert_eq!(
+            self.block_size(),
+            other.block_size(),
+            "The blocks should be of the same size"
+        );
+        assert_eq!(
+            self.block_cols(),
+            other.block_rows(),
+            "The number of block columns should match the number of block rows"
+        );
+        let p = self.block_size();
+        let blocks = (0..self.block_rows())
+            .map(|i| {
+                (0..other.block_cols())
+                    .map(|j| {
+                        let mut acc: Vec<T> = (0..p).map(|_| T::zero()).collect();
+                        for k in 0..self.block_cols() {
+                            add_cyclic_product::<T>(
+                                &mut acc,
+                                &self.blocks[i][k].row,
+                                0,
+                                &other.blocks[k][j].row,
+                            );
+                        }
+                        Circulant::from_row(Vector::from_vec(acc))
+                    })
+                    .collect()
+            })
+            .collect();
+        QuasiCyclicMatrix { blocks }
+    }
+}
+
+impl<'a, T> ops::Mul<&'a QuasiCyclicMatrix<T>> for &'a Vector<T>
+where
+    for<'b> &'b T: ops::Mul<Output = T>,
+    T: ops::Add<Output = T> + Zero + PartialEq,
+{
+    type Output = Vector<T>;
+
+    fn mul(self, other: &'a QuasiCyclicMatrix<T>) -> Vector<T> {
+        assert_eq!(
+            self.len(),
+            other.nrows(),
+            "The length of vector should match the number of matrix rows"
+        );
+        let p = other.block_size();
+        let mut result: Vec<T> = (0..other.ncols()).map(|_| T::zero()).collect();
+        for j in 0..other.block_cols() {
+            let acc = &mut result[j * p..(j + 1) * p];
+            for i in 0..other.block_rows() {
+                add_cyclic_product::<T>(acc, self, i * p, &other.blocks[i][j].row);
+            }
+        }
+        Vector::from_vec(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate bit_vector;
+    use self::bit_vector::Bit;
+    use super::*;
+
+    fn bits(values: &[u8]) -> Vector<Bit<bool>> {
+        Vector::from_vec(values.iter().map(|&v| Bit::from(v == 1)).collect())
+    }
+
+    #[test]
+    fn get_rotates_rows() {
+        let c = Circulant::from_row(Vector::from_vec(vec![1, 2, 3]));
+        assert_eq!(c.size(), 3);
+        assert_eq!(*c.get(0, 0), 1);
+        assert_eq!(*c.get(1, 0), 3);
+        assert_eq!(*c.get(1, 1), 1);
+        assert_eq!(*c.get(2, 0), 2);
+    }
+
+    #[test]
+    fn to_matrix() {
+        let c = Circulant::from_row(Vector::from_vec(vec![1, 2, 3]));
+        let expected = Matrix::from_vec(vec![
+            Vector::from_vec(vec![1, 3, 2]),
+            Vector::from_vec(vec![2, 1, 3]),
+            Vector::from_vec(vec![3, 2, 1]),
+        ]);
+        assert_eq!(c.to_matrix(), expected);
+        assert_eq!(c.transpose().to_matrix(), expected.transpose());
+    }
+
+    #[test]
+    fn identity_and_zero() {
+        let i: Circulant<i32> = Circulant::identity(4);
+        assert_eq!(i.to_matrix(), Matrix::identity(4));
+        let z: Circulant<i32> = Circulant::zero(4);
+        assert_eq!(z.to_matrix(), Matrix::zero(4, 4));
+    }
+
+    #[test]
+    fn multiplication_is_polynomial_multiplication() {
+        let a = Circulant::from_row(Vector::from_vec(vec![1, 2, 0, 0]));
+        let b = Circulant::from_row(Vector::from_vec(vec![0, 0, 3, 1]));
+        // (1 + 2x)(3x^2 + x^3) = 3x^2 + 7x^3 + 2x^4 = 2 + 3x^2 + 7x^3
+        let product = &a * &b;
+        assert_eq!(product.row(), &Vector::from_vec(vec![2, 0, 3, 7]));
+        assert_eq!(product.to_matrix(), a.to_matrix() * b.to_matrix());
+        assert_eq!(a.clone() * b.clone(), product);
+    }
+
+    #[test]
+    fn vector_multiplication() {
+        let c = Circulant::from_row(Vector::from_vec(vec![1, 2, 3]));
+        let v = Vector::from_vec(vec![4, 5, 6]);
+        assert_eq!(&v * &c, &v * &c.to_matrix());
+    }
+
+    #[test]
+    fn addition() {
+        let a = Circulant::from_row(Vector::from_vec(vec![1, 2, 3]));
+        let b = Circulant::from_row(Vector::from_vec(vec![1, 1, 1]));
+        assert_eq!((&a + &b).row(), &Vector::from_vec(vec![2, 3, 4]));
+        assert_eq!((&a - &b).row(), &Vector::from_vec(vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn binary_inverse() {
+        // x^7 - 1 = (x + 1)(x^3 + x + 1)(x^3 + x^2 + 1), so 1 + x + x^2 is a unit
+        let a = Circulant::from_row(bits(&[1, 1, 1, 0, 0, 0, 0]));
+        let inverse = a.inverse().expect("1 + x + x^2 should be invertible");
+        assert_eq!(&a * &inverse, Circulant::identity(7));
+        assert_eq!(&inverse * &a, Circulant::identity(7));
+    }
+
+    #[test]
+    fn binary_not_invertible() {
+        let even_weight = Circulant::from_row(bits(&[1, 1, 0, 0, 0, 0, 0]));
+        assert_eq!(even_weight.inverse(), None);
+        let factor = Circulant::from_row(bits(&[1, 1, 0, 1, 0, 0, 0]));
+        assert_eq!(factor.inverse(), None);
+        let zero: Circulant<Bit<bool>> = Circulant::zero(7);
+        assert_eq!(zero.inverse(), None);
+    }
+
+    #[test]
+    fn integer_shift_inverse() {
+        let shift = Circulant::from_row(Vector::from_vec(vec![0, 1, 0, 0, 0]));
+        let inverse = shift.inverse().unwrap();
+        assert_eq!(inverse.row(), &Vector::from_vec(vec![0, 0, 0, 0, 1]));
+        assert_eq!(&shift * &inverse, Circulant::identity(5));
+        let two = Circulant::from_row(Vector::from_vec(vec![2, 0, 0]));
+        assert_eq!(two.inverse(), None);
+    }
+
+    fn quasi_cyclic() -> QuasiCyclicMatrix<i32> {
+        QuasiCyclicMatrix::from_blocks(vec![
+            vec![
+                Circulant::from_row(Vector::from_vec(vec![1, 2, 3])),
+                Circulant::from_row(Vector::from_vec(vec![0, 1, 0])),
+            ],
+            vec![
+                Circulant::from_row(Vector::from_vec(vec![4, 0, 0])),
+                Circulant::from_row(Vector::from_vec(vec![1, 1, 5])),
+            ],
+            vec![
+                Circulant::from_row(Vector::from_vec(vec![0, 0, 7])),
+                Circulant::identity(3),
+            ],
+        ])
+    }
+
+    #[test]
+    fn quasi_cyclic_dimensions() {
+        let m = quasi_cyclic();
+        assert_eq!(m.block_size(), 3);
+        assert_eq!(m.block_rows(), 3);
+        assert_eq!(m.block_cols(), 2);
+        assert_eq!(m.nrows(), 9);
+        assert_eq!(m.ncols(), 6);
+    }
+
+    #[test]
+    #[should_panic]
+    fn quasi_cyclic_unequal_blocks() {
+        QuasiCyclicMatrix::from_blocks(vec![vec![
+            Circulant::<i32>::identity(3),
+            Circulant::identity(4),
+        ]]);
+    }
+
+    #[test]
+    fn quasi_cyclic_to_matrix() {
+        let m = quasi_cyclic();
+        let dense = m.to_matrix();
+        assert_eq!(dense.nrows(), 9);
+        assert_eq!(dense.ncols(), 6);
+        for row in 0..9 {
+            for col in 0..6 {
+                assert_eq!(dense[col][row], *m.get(row, col));
+            }
+        }
+        assert_eq!(m.transpose().to_matrix(), dense.transpose());
+    }
+
+    #[test]
+    fn quasi_cyclic_products() {
+        let m = quasi_cyclic();
+        let v = Vector::from_vec(vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(&v * &m, &v * &m.to_matrix());
+
+        let t = m.transpose();
+        assert_eq!((&m * &t).to_matrix(), m.to_matrix() * t.to_matrix());
+    }
+}
@@ -0,0 +1,107 @@
+use shape::Shape;
+use std::error;
+use std::fmt;
+
+/// An operation on matrices or vectors of incompatible dimensions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatrixError {
+    /// The vectors a matrix is built from differ in length
+    Ragged {
+        index: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// The rows of a grid of blocks differ in their number of blocks
+    Blocks {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// Vectors of different lengths were combined
+    Length { left: usize, right: usize },
+    /// The shapes of the operands do not fit the operation
+    Dimensions {
+        operation: &'static str,
+        left: Shape,
+        right: Shape,
+    },
+    /// A segment of shape `size` at `(row, col)` does not fit in the matrix
+    OutOfBounds {
+        position: (usize, usize),
+        size: Shape,
+        shape: Shape,
+    },
+    /// Entry `index` was selected out of only `len` entries
+    Selection { index: usize, len: usize },
+    /// `value` is out of range or repeated in a permutation of `len`
+    Permutation { value: usize, len: usize },
+    /// The rows are dependent, so there is no systematic form
+    Rank { rank: usize, rows: usize },
+    /// The identity block of a systematic matrix is missing
+    NotSystematic,
+}
+
+impl fmt::Display for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MatrixError::Ragged {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "vector {} has length {}, expected {}",
+                index, found, expected
+            ),
+            MatrixError::Blocks {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "block row {} has {} blocks, expected {}",
+                row, found, expected
+            ),
+            MatrixError::Length { left, right } => {
+                write!(
+                    f,
+                    "vectors of length {} and {} can not be combined",
+                    left, right
+                )
+            }
+            MatrixError::Dimensions {
+                operation,
+                left,
+                right,
+            } => write!(f, "can not {} a {} and a {} matrix", operation, left, right),
+            MatrixError::OutOfBounds {
+                position,
+                size,
+                shape,
+            } => write!(
+                f,
+                "a {} segment at ({}, {}) does not fit in a {} matrix",
+                size, position.0, position.1, shape
+            ),
+            MatrixError::Selection { index, len } => {
+                write!(f, "can not select entry {} of {}", index, len)
+            }
+            MatrixError::Permutation { value, len } => write!(
+                f,
+                "{} is out of range or repeated in a permutation of {}",
+                value, len
+            ),
+            MatrixError::Rank { rank, rows } => {
+                write!(f, "the {} rows only have rank {}", rows, rank)
+            }
+            MatrixError::NotSystematic => write!(f, "the matrix is not in systematic form"),
+        }
+    }
+}
+
+impl error::Error for MatrixError {}
+
+/// Unwrap the result of a `try_*` operation, for the panicking variants
+pub(crate) fn unwrap<T>(result: Result<T, MatrixError>) -> T {
+    result.unwrap_or_else(|e| panic!("{}", e))
+}
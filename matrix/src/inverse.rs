@@ -0,0 +1,95 @@
+extern crate bit_vector;
+extern crate zero_one;
+use self::bit_vector::Bit;
+use self::zero_one::One;
+
+/// Multiplicative inverses, for the elements that have one.
+pub trait Inverse: Sized {
+    /// Get `a` such that `a * self == one`, if it exists
+    ///
+    /// # Examples
+    /// ```
+    /// use matrix::Inverse;
+    /// assert_eq!(2.0f64.inverse(), Some(0.5));
+    /// assert_eq!(2i32.inverse(), None);
+    /// assert_eq!((-1i32).inverse(), Some(-1));
+    /// ```
+    fn inverse(&self) -> Option<Self>;
+}
+
+macro_rules! impl_inverse_float {
+    ($t: ty) => {
+        impl Inverse for $t {
+            #[inline]
+            fn inverse(&self) -> Option<$t> {
+                if *self == 0.0 {
+                    None
+                } else {
+                    Some(1.0 / *self)
+                }
+            }
+        }
+    };
+}
+
+impl_inverse_float!(f32);
+impl_inverse_float!(f64);
+
+macro_rules! impl_inverse_integer {
+    ($t: ty) => {
+        /// Only the units `1` and `-1` are invertible in the integers
+        impl Inverse for $t {
+            #[inline]
+            fn inverse(&self) -> Option<$t> {
+                if *self == 1 || *self == -1 {
+                    Some(*self)
+                } else {
+                    None
+                }
+            }
+        }
+    };
+}
+
+impl_inverse_integer!(i32);
+impl_inverse_integer!(i64);
+
+/// GF(2) is a field: one is its own inverse
+impl<T: One + PartialEq> Inverse for Bit<T> {
+    #[inline]
+    fn inverse(&self) -> Option<Bit<T>> {
+        if *self == Bit::one() {
+            Some(Bit::one())
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::zero_one::Zero;
+    use super::*;
+
+    #[test]
+    fn floats() {
+        assert_eq!(4.0f32.inverse(), Some(0.25));
+        assert_eq!(0.0f64.inverse(), None);
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(1i64.inverse(), Some(1));
+        assert_eq!((-1i64).inverse(), Some(-1));
+        assert_eq!(0i32.inverse(), None);
+        assert_eq!(3i32.inverse(), None);
+    }
+
+    #[test]
+    fn bits() {
+        let one: Bit<bool> = Bit::one();
+        let zero: Bit<bool> = Bit::zero();
+        assert_eq!(one.inverse(), Some(one));
+        assert_eq!(zero.inverse(), None);
+    }
+}
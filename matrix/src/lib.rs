@@ -1,5 +1,33 @@
+mod error;
+mod shape;
 mod vector;
 mod matrix;
+mod view;
+mod permutation;
+mod systematic;
+mod row_matrix;
+mod sparse;
+mod inverse;
+mod circulant;
+mod bit_matrix;
+mod block;
+mod text;
+mod alist;
+mod binary;
+#[cfg(feature = "serde")]
+mod serialize;
 
+pub use error::MatrixError;
+pub use shape::Shape;
 pub use vector::Vector;
 pub use matrix::Matrix;
+pub use view::{MatrixView, MatrixViewMut, VectorView};
+pub use permutation::Permutation;
+pub use systematic::Systematic;
+pub use row_matrix::RowMatrix;
+pub use sparse::{Elimination, Layout, SparseBinaryMatrix, SparseMatrix};
+pub use inverse::Inverse;
+pub use circulant::{Circulant, QuasiCyclicMatrix};
+pub use bit_matrix::BitMatrix;
+pub use text::{Field, ReadError};
+pub use binary::{crc32, is_binary, BinaryError, BinaryFormat, BinaryReader, BinaryWriter, MAGIC, VERSION};
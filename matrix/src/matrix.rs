@@ -1,11 +1,12 @@
 extern crate rand;
 extern crate zero_one;
-use vector::Vector;
+use vector::{self, Vector};
 use self::zero_one::{One, Zero};
 use std::ops;
-use std::rc::Rc;
+use error::{unwrap, MatrixError};
+use shape::Shape;
 
-use self::rand::Rand;
+use self::rand::{Rand, Rng};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Matrix<T> {
@@ -39,8 +40,23 @@ impl<T: Zero + One> Matrix<T> {
 }
 
 impl<T: Rand> Matrix<T> {
-    pub fn random(rows: usize, columns: usize) -> Matrix<T> {
-        Matrix::from_function(rows, columns, |_, _| rand::random::<T>())
+    /// A matrix of random entries drawn from `rng`
+    pub fn random<R: Rng>(rows: usize, columns: usize, rng: &mut R) -> Matrix<T> {
+        Matrix::random_with(rows, columns, rng, |rng| rng.gen())
+    }
+}
+
+impl<T> Matrix<T> {
+    /// A matrix of entries drawn one by one with `sample`, column by column
+    pub fn random_with<R, F>(rows: usize, columns: usize, rng: &mut R, mut sample: F) -> Matrix<T>
+    where
+        R: Rng,
+        F: FnMut(&mut R) -> T,
+    {
+        let columns = (0..columns)
+            .map(|_| (0..rows).map(|_| sample(rng)).collect())
+            .collect();
+        Matrix { columns }
     }
 }
 
@@ -62,13 +78,22 @@ impl<T> ops::IndexMut<usize> for Matrix<T> {
 
 impl<T> Matrix<T> {
     pub fn from_vec(columns: Vec<Vector<T>>) -> Matrix<T> {
-        if !columns.is_empty() {
-            let len_first = columns[0].len();
-            for col in &columns {
-                assert_eq!(len_first, col.len(), "All columns must be the same length");
+        unwrap(Matrix::try_from_vec(columns))
+    }
+
+    /// Build a matrix from its columns, which should have the same length
+    pub fn try_from_vec(columns: Vec<Vector<T>>) -> Result<Matrix<T>, MatrixError> {
+        if let Some(first) = columns.first() {
+            let expected = first.len();
+            if let Some(index) = columns.iter().position(|col| col.len() != expected) {
+                return Err(MatrixError::Ragged {
+                    index,
+                    expected,
+                    found: columns[index].len(),
+                });
             }
         }
-        Matrix { columns }
+        Ok(Matrix { columns })
     }
 
     pub fn from_function(
@@ -93,104 +118,132 @@ impl<T> Matrix<T> {
         self.columns[0].len()
     }
 
-    #[inline]
-    pub fn get_segment(&self, row: usize, col: usize, rows: usize, cols: usize) -> Matrix<T> {
-        assert!(
-            row + rows < self.nrows(),
-            "Index out of bounds: too many rows"
-        );
-        assert!(
-            col + cols < self.ncols(),
-            "Index out of bounds: too many cols"
-        );
-
-        let mut columns: Vec<Vector<T>> = Vec::with_capacity(col + cols);
-        for col in &self.columns[col..col + cols] {
-            let rows = &col[row..row + rows];
-            let rows = rows.to_vec();
-
-            columns.push(Vector::from_rc_vec(rows));
-        }
+    pub(crate) fn into_columns(self) -> Vec<Vector<T>> {
+        self.columns
+    }
 
-        Matrix { columns }
+    /// The shape; a matrix without columns has no rows either
+    pub fn shape(&self) -> Shape {
+        Shape::new(self.nrows(), self.ncols())
     }
 
     // Glue the other matrix to the right of this matrix
     pub fn augment(&mut self, other: Matrix<T>) {
-        assert_eq!(
-            self.nrows(),
-            other.nrows(),
-            "they should have the same number of rows"
-        );
-        self.columns.extend(other.columns.into_iter());
+        unwrap(self.try_augment(other))
+    }
+
+    /// Glue the other matrix to the right of this matrix, if they have the
+    /// same number of rows or this matrix has no columns
+    pub fn try_augment(&mut self, other: Matrix<T>) -> Result<(), MatrixError> {
+        if self.ncols() > 0 {
+            self.shape().check_augment(other.shape())?;
+        }
+        self.columns.extend(other.columns);
+        Ok(())
     }
 
     // Put the other matrix below this matrix.
     pub fn stack(&mut self, other: Matrix<T>) {
-        assert_eq!(
-            self.ncols(),
-            other.ncols(),
-            "they should have the same number of columns"
-        );
+        unwrap(self.try_stack(other))
+    }
+
+    /// Put the other matrix below this matrix, if they have the same number
+    /// of columns or this matrix has no columns
+    pub fn try_stack(&mut self, other: Matrix<T>) -> Result<(), MatrixError> {
+        if self.ncols() == 0 {
+            *self = other;
+            return Ok(());
+        }
+        self.shape().check_stack(other.shape())?;
         for (i, col) in other.columns.into_iter().enumerate() {
-            self.columns[i].extend(col.into_iter());
+            self.columns[i].extend(col);
         }
+        Ok(())
+    }
+}
+
+impl<T: Clone> Matrix<T> {
+    #[inline]
+    pub fn get_segment(&self, row: usize, col: usize, rows: usize, cols: usize) -> Matrix<T> {
+        unwrap(self.try_get_segment(row, col, rows, cols))
+    }
+
+    /// Take the `rows x cols` segment at `(row, col)`
+    pub fn try_get_segment(
+        &self,
+        row: usize,
+        col: usize,
+        rows: usize,
+        cols: usize,
+    ) -> Result<Matrix<T>, MatrixError> {
+        self.shape()
+            .check_segment(row, col, Shape::new(rows, cols))?;
+        Ok(Matrix::from_vec(
+            self.columns[col..col + cols]
+                .iter()
+                .map(|column| Vector::from_vec(column.as_slice()[row..row + rows].to_vec()))
+                .collect(),
+        ))
     }
 
     // compute the transpose
     pub fn transpose(&self) -> Matrix<T> {
-        let cols = self.ncols();
-        let rows = self.nrows();
-        let mut new_columns: Vec<Vec<Rc<T>>> = Vec::with_capacity(rows);
-        for _i in 0..rows {
-            new_columns.push(Vec::with_capacity(cols));
-        }
-        for column in &self.columns {
-            let prior = column.clone().into_iter().enumerate();
-            for (i, e) in prior {
-                new_columns[i].push(e);
-            }
-        }
         Matrix {
-            columns: new_columns.into_iter().map(Vector::from_rc_vec).collect(),
+            columns: vector::transpose(self.columns.clone(), self.nrows()),
         }
     }
 }
 
-impl<T: Copy> Matrix<T> {
+impl<T> Matrix<T> {
     #[inline]
     pub fn set_segment(&mut self, row: usize, col: usize, segment: Matrix<T>) {
-        let rows = segment.nrows();
-        let cols = segment.ncols();
-        assert!(
-            row + rows < self.nrows(),
-            "Index out of bounds: too many rows"
-        );
-        assert!(
-            col + cols < self.ncols(),
-            "Index out of bounds: too many cols"
-        );
+        unwrap(self.try_set_segment(row, col, segment))
+    }
 
+    /// Overwrite the entries from `(row, col)` on with the segment
+    pub fn try_set_segment(
+        &mut self,
+        row: usize,
+        col: usize,
+        segment: Matrix<T>,
+    ) -> Result<(), MatrixError> {
+        self.shape().check_segment(row, col, segment.shape())?;
         let columns: Vec<Vector<T>> = segment.columns;
 
         for (i, column) in columns.into_iter().enumerate() {
             for (j, element) in column.into_iter().enumerate() {
-                self.columns[col + i][row + j] = *element;
+                self.columns[col + i][row + j] = element;
             }
         }
+        Ok(())
     }
 }
 
 macro_rules! pointwise_operator {
-    ($type:ident, $funcname:ident, $operator:tt) => {
+    ($type:ident, $funcname:ident, $tryname:ident, $verb:expr, $operator:tt) => {
+        impl<T> Matrix<T> {
+            #[doc = concat!("`self ", stringify!($operator), " other`, or an error if the dimensions differ")]
+            pub fn $tryname<'a>(&'a self, other: &'a Matrix<T>) -> Result<Matrix<T>, MatrixError>
+                where &'a T: ops::$type<Output = T>
+            {
+                self.shape().check_same(other.shape(), $verb)?;
+                let columns = self.columns
+                    .iter()
+                    .zip(&other.columns)
+                    .map(|(a, b)| a.$tryname(b))
+                    .collect::<Result<_, _>>()?;
+
+                Ok(Matrix { columns })
+            }
+        }
+
         impl<T> ops::$type for Matrix<T>
             where Vector<T>: ops::$type<Output=Vector<T>>
         {
             type Output = Matrix<T>;
 
             fn $funcname(self, other: Matrix<T>) -> Self::Output {
-                debug_assert_eq!(self.ncols(), other.nrows(),
-                            "they should have the same number of columns");
+                unwrap(self.shape().check_same(other.shape(), $verb));
                 let columns: Vec<Vector<T>> = self.columns
                     .into_iter()
                     .zip(other.columns)
@@ -202,15 +255,15 @@ macro_rules! pointwise_operator {
         }
 
         impl<'a, T> ops::$type for &'a Matrix<T>
-            where Vector<T>: ops::$type<Output=Vector<T>>
+            where &'a Vector<T>: ops::$type<Output=Vector<T>>
         {
             type Output = Matrix<T>;
 
-            fn $funcname(self, other: &Matrix<T>) -> Self::Output {
+            fn $funcname(self, other: &'a Matrix<T>) -> Self::Output {
+                unwrap(self.shape().check_same(other.shape(), $verb));
                 let columns: Vec<Vector<T>> = self.columns
                     .iter()
-                    .cloned()
-                    .zip(other.columns.to_vec())
+                    .zip(&other.columns)
                     .map(|(a, b)| a $operator b)
                     .collect();
 
@@ -221,8 +274,74 @@ macro_rules! pointwise_operator {
     }
 }
 
-pointwise_operator!(Add, add, +);
-pointwise_operator!(Sub, sub, -);
+pointwise_operator!(Add, add, try_add, "add", +);
+pointwise_operator!(Sub, sub, try_sub, "subtract", -);
+
+impl<T> Matrix<T> {
+    /// The product `v M` of a row vector and the matrix, or an error if the
+    /// length of `v` is not the number of rows
+    pub fn try_vector_mul<'a>(&'a self, v: &'a Vector<T>) -> Result<Vector<T>, MatrixError>
+    where
+        &'a Vector<T>: ops::Mul<Output = T>,
+    {
+        Shape::row(v.len()).check_product(self.shape())?;
+        let result: Vec<T> = self.columns.iter().map(|c| v * c).collect();
+
+        Ok(Vector::from_vec(result))
+    }
+}
+
+impl<T: ops::Add<Output = T> + Zero> Matrix<T> {
+    /// The product `self other`, or an error if the number of columns of
+    /// `self` is not the number of rows of `other`
+    pub fn try_mul(&self, other: &Matrix<T>) -> Result<Matrix<T>, MatrixError>
+    where
+        for<'b> &'b T: ops::Mul<Output = T>,
+    {
+        self.shape().check_product(other.shape())?;
+        // index the columns directly rather than through a view, which
+        // would have to map every index through its selection
+        Ok(Matrix::from_vec(
+            other
+                .columns
+                .iter()
+                .map(|column| self.column_combination(column))
+                .collect(),
+        ))
+    }
+
+    /// The entries of `M v`, for `v` of the right length
+    fn column_combination(&self, v: &Vector<T>) -> Vector<T>
+    where
+        for<'b> &'b T: ops::Mul<Output = T>,
+    {
+        (0..self.nrows())
+            .map(|row| {
+                self.columns
+                    .iter()
+                    .zip(v.iter())
+                    .fold(T::zero(), |sum, (column, x)| sum + &column[row] * x)
+            })
+            .collect()
+    }
+
+    pub fn mul_vector(&self, v: &Vector<T>) -> Vector<T>
+    where
+        for<'b> &'b T: ops::Mul<Output = T>,
+    {
+        unwrap(self.try_mul_vector(v))
+    }
+
+    /// The product `M v` with a column vector, such as a syndrome, or an
+    /// error if the length of `v` is not the number of columns
+    pub fn try_mul_vector(&self, v: &Vector<T>) -> Result<Vector<T>, MatrixError>
+    where
+        for<'b> &'b T: ops::Mul<Output = T>,
+    {
+        self.shape().check_product(Shape::column(v.len()))?;
+        Ok(self.column_combination(v))
+    }
+}
 
 impl<'a, T> ops::Mul<&'a Matrix<T>> for &'a Vector<T>
 where
@@ -231,14 +350,7 @@ where
     type Output = Vector<T>;
 
     fn mul(self, other: &'a Matrix<T>) -> Self::Output {
-        debug_assert_eq!(
-            self.len(),
-            other.nrows(),
-            "The number of columns should match the number of rows"
-        );
-        let result: Vec<T> = other.columns.iter().map(|c| self * c).collect();
-
-        Vector::from_vec(result)
+        unwrap(other.try_vector_mul(self))
     }
 }
 
@@ -249,12 +361,6 @@ where
     type Output = Vector<T>;
 
     fn mul(self, other: Matrix<T>) -> Self::Output {
-        debug_assert_eq!(
-            self.len(),
-            other.nrows(),
-            "The length of vector should match the number of matrix rows"
-        );
-
         &self * &other
     }
 }
@@ -267,34 +373,7 @@ where
     type Output = Matrix<T>;
 
     fn mul(self, other: &'a Matrix<T>) -> Self::Output {
-        debug_assert_eq!(
-            self.ncols(),
-            other.nrows(),
-            "The number of columns should match the number of rows"
-        );
-        let k = self.ncols();
-        let m = self.nrows();
-        let n = other.ncols();
-
-        let mut columns: Vec<Vec<Rc<T>>> = Vec::with_capacity(m);
-        for _ in 0..n {
-            columns.push(Vec::with_capacity(m));
-        }
-
-        for i in 0..m {
-            for j in 0..n {
-                let col = &mut columns[j];
-                let mut sum = T::zero();
-                for l in 0..k {
-                    sum = sum + &self[l][i] * &other[j][l];
-                }
-                col.push(Rc::new(sum));
-            }
-        }
-
-        Matrix {
-            columns: columns.into_iter().map(Vector::from_rc_vec).collect(),
-        }
+        unwrap(self.try_mul(other))
     }
 }
 
@@ -305,19 +384,19 @@ where
     type Output = Matrix<T>;
 
     fn mul(self, other: Matrix<T>) -> Self::Output {
-        debug_assert_eq!(
-            self.ncols(),
-            other.nrows(),
-            "The number of columns should match the number of rows"
-        );
         &self * &other
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::rand::{SeedableRng, XorShiftRng};
     use super::*;
 
+    fn rng() -> XorShiftRng {
+        XorShiftRng::from_seed([1, 2, 3, 4])
+    }
+
     #[test]
     fn ncols_and_rows() {
         let m = Matrix {
@@ -341,9 +420,12 @@ mod tests {
 
     #[test]
     fn random() {
-        let m: Matrix<i32> = Matrix::random(9, 10);
+        let m: Matrix<i32> = Matrix::random(9, 10, &mut rng());
         assert_eq!(m.nrows(), 9);
         assert_eq!(m.ncols(), 10);
+        assert_eq!(Matrix::random(9, 10, &mut rng()), m);
+        let small = Matrix::random_with(3, 4, &mut rng(), |rng| rng.gen_range(0, 5));
+        assert!((0..4).all(|col| small[col].iter().all(|&x| (0..5).contains(&x))));
     }
 
     #[test]
@@ -364,7 +446,7 @@ mod tests {
             assert_eq!(10, m.columns[i].len());
         }
 
-        assert_eq!(10, (0..10).map(|i| m.columns[i][i]).sum());
+        assert_eq!(10, (0..10).map(|i| m.columns[i][i]).sum::<i32>());
 
         // doesn't work without :i32
         let acc: i32 = (0..10)
@@ -415,6 +497,33 @@ mod tests {
         assert_eq!(3, acc);
     }
 
+    #[test]
+    fn segment_edges() {
+        let m: Matrix<i32> = Matrix::from_function(4, 5, |i, j| (10 * i + j) as i32);
+        // touching the last row and column
+        let corner = m.get_segment(2, 3, 2, 2);
+        assert_eq!(corner.shape(), Shape::new(2, 2));
+        assert_eq!(corner[1][1], 43);
+        assert_eq!(m.get_segment(0, 0, 4, 5), m);
+        assert_eq!(m.get_segment(4, 5, 0, 0).shape(), Shape::new(0, 0));
+        assert!(m.try_get_segment(3, 0, 2, 1).is_err());
+        assert!(m.try_get_segment(0, 4, 1, 2).is_err());
+
+        let mut n: Matrix<i32> = Matrix::zero(4, 5);
+        n.set_segment(2, 3, corner);
+        assert_eq!(n[4][3], 43);
+        n.set_segment(0, 0, m.clone());
+        assert_eq!(n, m);
+        assert!(n.try_set_segment(1, 0, m.clone()).is_err());
+        assert_eq!(n, m);
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_segment_out_of_bounds() {
+        Matrix::<i32>::identity(3).get_segment(1, 1, 3, 1);
+    }
+
     #[test]
     fn set_segment() {
         let mut m: Matrix<i32> = Matrix::zero(10, 10);
@@ -468,7 +577,7 @@ mod tests {
     fn addition_different_col_size() {
         let m1: Matrix<i32> = Matrix::zero(1, 3);
         let m2: Matrix<i32> = Matrix::zero(1, 4);
-        m1 + m2;
+        let _ = m1 + m2;
     }
 
     #[test]
@@ -476,7 +585,31 @@ mod tests {
     fn addition_different_row_size() {
         let m1: Matrix<i32> = Matrix::zero(2, 3);
         let m2: Matrix<i32> = Matrix::zero(1, 3);
-        m1 + m2;
+        let _ = m1 + m2;
+    }
+
+    #[test]
+    #[should_panic]
+    fn addition_transposed() {
+        let m1: Matrix<i32> = Matrix::zero(2, 3);
+        let m2: Matrix<i32> = Matrix::zero(3, 2);
+        let _ = m1 + m2;
+    }
+
+    #[test]
+    #[should_panic]
+    fn addition_reference_different_size() {
+        let m1: Matrix<i32> = Matrix::zero(3, 3);
+        let m2: Matrix<i32> = Matrix::zero(3, 4);
+        let _ = &m1 + &m2;
+    }
+
+    #[test]
+    #[should_panic]
+    fn subtraction_reference_different_row_size() {
+        let m1: Matrix<i32> = Matrix::zero(3, 3);
+        let m2: Matrix<i32> = Matrix::zero(2, 3);
+        let _ = &m1 - &m2;
     }
 
     #[test]
@@ -543,7 +676,7 @@ mod tests {
 
     #[test]
     fn matrix_mul_no_reference() {
-        let m1: Matrix<i32> = Matrix::random(10, 10);
+        let m1: Matrix<i32> = Matrix::random(10, 10, &mut rng());
         let m2: Matrix<i32> = Matrix::identity(10);
         let m = m1.clone() * m2;
         for i in 0..10 {
@@ -555,7 +688,7 @@ mod tests {
 
     #[test]
     fn matrix_mul_reference() {
-        let m1: Matrix<i32> = Matrix::random(10, 10);
+        let m1: Matrix<i32> = Matrix::random(10, 10, &mut rng());
         let m2: Matrix<i32> = Matrix::identity(10);
         let m = &m1 * &m2;
         for i in 0..10 {
@@ -585,6 +718,59 @@ mod tests {
         assert_eq!(m1 * m2, expected);
     }
 
+    #[test]
+    #[should_panic]
+    fn matrix_mul_wrong_dimensions() {
+        let m1: Matrix<i32> = Matrix::zero(2, 3);
+        let m2: Matrix<i32> = Matrix::zero(2, 3);
+        let _ = &m1 * &m2;
+    }
+
+    #[test]
+    fn try_operations() {
+        let m: Matrix<i32> = Matrix::identity(3);
+        let wide: Matrix<i32> = Matrix::zero(3, 4);
+        assert_eq!(m.try_mul(&wide), Ok(wide.clone()));
+        assert_eq!(
+            wide.try_mul(&m),
+            Err(MatrixError::Dimensions {
+                operation: "multiply",
+                left: Shape::new(3, 4),
+                right: Shape::new(3, 3),
+            })
+        );
+        assert_eq!(m.try_add(&m).unwrap()[1][1], 2);
+        assert!(m.try_sub(&wide).is_err());
+        let v = Vector::from_vec(vec![1, 2, 3]);
+        assert_eq!(m.try_vector_mul(&v), Ok(v.clone()));
+        assert!(wide.transpose().try_vector_mul(&v).is_err());
+
+        let mut a = m.clone();
+        assert!(a.try_augment(Matrix::zero(2, 2)).is_err());
+        assert!(a.try_stack(Matrix::zero(2, 2)).is_err());
+        assert_eq!(a, m);
+        assert!(a.try_augment(wide).is_ok());
+        assert_eq!(a.ncols(), 7);
+
+        assert_eq!(
+            Matrix::try_from_vec(vec![
+                Vector::from_vec(vec![1]),
+                Vector::from_vec(vec![1, 2]),
+            ]),
+            Err(MatrixError::Ragged {
+                index: 1,
+                expected: 1,
+                found: 2,
+            })
+        );
+        let error = m.try_get_segment(2, 0, 2, 1).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "a 2x1 segment at (2, 0) does not fit in a 3x3 matrix"
+        );
+        assert!(a.try_set_segment(0, 5, Matrix::identity(3)).is_err());
+    }
+
     #[test]
     fn matrix_transpose() {
         let m1: Matrix<i32> = Matrix::identity(10);
@@ -676,6 +862,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn glue_to_empty() {
+        let mut m: Matrix<i32> = Matrix::from_vec(vec![]);
+        m.augment(Matrix::identity(2));
+        assert_eq!(m.shape(), Shape::new(2, 2));
+        let mut m: Matrix<i32> = Matrix::from_vec(vec![]);
+        m.stack(Matrix::zero(1, 3));
+        assert_eq!(m.shape(), Shape::new(1, 3));
+        let mut m: Matrix<i32> = Matrix::zero(0, 3);
+        m.stack(Matrix::identity(3));
+        assert_eq!(m, Matrix::identity(3));
+    }
+
     #[test]
     #[should_panic]
     fn stack_unequal_sizes() {
@@ -0,0 +1,333 @@
+extern crate bit_vector;
+extern crate rand;
+extern crate zero_one;
+use self::bit_vector::BitVector;
+use self::rand::Rng;
+use self::zero_one::{One, Zero};
+use bit_matrix::BitMatrix;
+use error::{unwrap, MatrixError};
+use matrix::Matrix;
+use std::mem;
+use std::ops;
+use vector::Vector;
+
+/// A permutation of `0..len`.
+///
+/// Permuting a sequence `x` gives the sequence `y` with `y[i] = x[p[i]]`,
+/// so permuting by `p` and then by `q` is permuting by `p.compose(&q)`.
+/// On the columns of a matrix `M` this is `M P`, with `P` the permutation
+/// matrix from `to_matrix`; on its rows it is `P^T M`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Permutation {
+    map: Vec<usize>,
+}
+
+impl Permutation {
+    pub fn identity(len: usize) -> Permutation {
+        Permutation {
+            map: (0..len).collect(),
+        }
+    }
+
+    pub fn from_vec(map: Vec<usize>) -> Permutation {
+        unwrap(Permutation::try_from_vec(map))
+    }
+
+    /// The permutation sending `i` to `map[i]`, or an error if `map` does
+    /// not hold every number in `0..map.len()` exactly once
+    pub fn try_from_vec(map: Vec<usize>) -> Result<Permutation, MatrixError> {
+        let len = map.len();
+        let mut seen = vec![false; len];
+        for &value in &map {
+            if value >= len || seen[value] {
+                return Err(MatrixError::Permutation { value, len });
+            }
+            seen[value] = true;
+        }
+        Ok(Permutation { map })
+    }
+
+    /// A uniformly random permutation
+    pub fn random<R: Rng>(len: usize, rng: &mut R) -> Permutation {
+        let mut map: Vec<usize> = (0..len).collect();
+        rng.shuffle(&mut map);
+        Permutation { map }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.map.iter().enumerate().all(|(i, &value)| i == value)
+    }
+
+    pub fn as_slice(&self) -> &[usize] {
+        &self.map
+    }
+
+    pub fn into_vec(self) -> Vec<usize> {
+        self.map
+    }
+
+    /// The permutation `i -> self[other[i]]`
+    pub fn compose(&self, other: &Permutation) -> Permutation {
+        unwrap(self.try_compose(other))
+    }
+
+    /// The permutation `i -> self[other[i]]`, or an error if the lengths
+    /// differ
+    pub fn try_compose(&self, other: &Permutation) -> Result<Permutation, MatrixError> {
+        self.check_len(other.len())?;
+        Ok(Permutation {
+            map: other.map.iter().map(|&i| self.map[i]).collect(),
+        })
+    }
+
+    pub fn inverse(&self) -> Permutation {
+        let mut map = vec![0; self.len()];
+        for (i, &value) in self.map.iter().enumerate() {
+            map[value] = i;
+        }
+        Permutation { map }
+    }
+
+    /// The permutation matrix `P`, with a one in row `self[j]` of column `j`
+    pub fn to_matrix<T: Zero + One>(&self) -> Matrix<T> {
+        Matrix::from_vec(
+            self.map
+                .iter()
+                .map(|&row| {
+                    (0..self.len())
+                        .map(|i| if i == row { T::one() } else { T::zero() })
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// Permute the bits of `v`
+    pub fn permute_bits(&self, v: &BitVector) -> BitVector {
+        unwrap(self.try_permute_bits(v))
+    }
+
+    /// Permute the bits of `v`, or give an error if its length differs
+    pub fn try_permute_bits(&self, v: &BitVector) -> Result<BitVector, MatrixError> {
+        self.check_len(v.len())?;
+        let mut result = BitVector::zero(v.len());
+        for (i, &j) in self.map.iter().enumerate() {
+            if v.get(j) {
+                result.set(i, true);
+            }
+        }
+        Ok(result)
+    }
+
+    fn check_len(&self, len: usize) -> Result<(), MatrixError> {
+        if self.len() == len {
+            Ok(())
+        } else {
+            Err(MatrixError::Length {
+                left: self.len(),
+                right: len,
+            })
+        }
+    }
+
+    /// Move the items around, which should be as many as this permutes
+    fn gather<T>(&self, items: Vec<T>) -> Vec<T> {
+        let mut items: Vec<Option<T>> = items.into_iter().map(Some).collect();
+        self.map
+            .iter()
+            .map(|&i| items[i].take().expect("a permutation repeats no index"))
+            .collect()
+    }
+}
+
+impl ops::Index<usize> for Permutation {
+    type Output = usize;
+
+    fn index(&self, idx: usize) -> &usize {
+        &self.map[idx]
+    }
+}
+
+impl<T> Vector<T> {
+    pub fn permute(&mut self, permutation: &Permutation) {
+        unwrap(self.try_permute(permutation))
+    }
+
+    /// Permute the entries, or give an error if the lengths differ
+    pub fn try_permute(&mut self, permutation: &Permutation) -> Result<(), MatrixError> {
+        permutation.check_len(self.len())?;
+        let entries = mem::replace(self, Vector::empty()).into_vec();
+        *self = Vector::from_vec(permutation.gather(entries));
+        Ok(())
+    }
+}
+
+impl<T> Matrix<T> {
+    pub fn permute_columns(&mut self, permutation: &Permutation) {
+        unwrap(self.try_permute_columns(permutation))
+    }
+
+    /// Permute the columns, giving `M P`, or an error if the permutation
+    /// does not have the number of columns as length
+    pub fn try_permute_columns(&mut self, permutation: &Permutation) -> Result<(), MatrixError> {
+        permutation.check_len(self.ncols())?;
+        let columns = mem::replace(self, Matrix::from_vec(vec![])).into_columns();
+        *self = Matrix::from_vec(permutation.gather(columns));
+        Ok(())
+    }
+
+    pub fn permute_rows(&mut self, permutation: &Permutation) {
+        unwrap(self.try_permute_rows(permutation))
+    }
+
+    /// Permute the rows, giving `P^T M`, or an error if the permutation does
+    /// not have the number of rows as length
+    pub fn try_permute_rows(&mut self, permutation: &Permutation) -> Result<(), MatrixError> {
+        permutation.check_len(self.nrows())?;
+        for col in 0..self.ncols() {
+            self[col].permute(permutation);
+        }
+        Ok(())
+    }
+}
+
+impl BitMatrix {
+    pub fn permute_columns(&mut self, permutation: &Permutation) {
+        unwrap(self.try_permute_columns(permutation))
+    }
+
+    /// Permute the columns, giving `M P`, or an error if the permutation
+    /// does not have the number of columns as length
+    pub fn try_permute_columns(&mut self, permutation: &Permutation) -> Result<(), MatrixError> {
+        permutation.check_len(self.ncols())?;
+        let rows = self
+            .rows()
+            .iter()
+            .map(|row| permutation.permute_bits(row))
+            .collect();
+        *self = BitMatrix::from_rows(self.ncols(), rows);
+        Ok(())
+    }
+
+    pub fn permute_rows(&mut self, permutation: &Permutation) {
+        unwrap(self.try_permute_rows(permutation))
+    }
+
+    /// Permute the rows, giving `P^T M`, or an error if the permutation does
+    /// not have the number of rows as length
+    pub fn try_permute_rows(&mut self, permutation: &Permutation) -> Result<(), MatrixError> {
+        permutation.check_len(self.nrows())?;
+        let ncols = self.ncols();
+        let rows = mem::replace(self, BitMatrix::zero(0, ncols)).into_rows();
+        *self = BitMatrix::from_rows(ncols, permutation.gather(rows));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rand::{SeedableRng, XorShiftRng};
+    use super::*;
+
+    #[test]
+    fn group_laws() {
+        let p = Permutation::from_vec(vec![2, 0, 3, 1]);
+        let q = Permutation::from_vec(vec![1, 3, 2, 0]);
+        assert_eq!(p[0], 2);
+        assert_eq!(p.compose(&q).as_slice(), [0, 1, 3, 2]);
+        assert!(p.compose(&p.inverse()).is_identity());
+        assert!(p.inverse().compose(&p).is_identity());
+        assert_eq!(p.inverse().inverse(), p);
+        assert_eq!(p.compose(&Permutation::identity(4)), p);
+        assert_eq!(p.compose(&q).inverse(), q.inverse().compose(&p.inverse()));
+        assert!(p.try_compose(&Permutation::identity(3)).is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            Permutation::try_from_vec(vec![0, 2, 0]),
+            Err(MatrixError::Permutation { value: 0, len: 3 })
+        );
+        assert_eq!(
+            Permutation::try_from_vec(vec![3, 0, 1])
+                .unwrap_err()
+                .to_string(),
+            "3 is out of range or repeated in a permutation of 3"
+        );
+        assert!(Permutation::try_from_vec(vec![]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn random() {
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        let p = Permutation::random(50, &mut rng);
+        assert_eq!(
+            Permutation::try_from_vec(p.clone().into_vec()),
+            Ok(p.clone())
+        );
+        assert!(!p.is_identity());
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        assert_eq!(Permutation::random(50, &mut rng), p);
+    }
+
+    #[test]
+    fn permute_vectors() {
+        let p = Permutation::from_vec(vec![2, 0, 3, 1]);
+        let q = Permutation::from_vec(vec![1, 3, 2, 0]);
+        let mut v = Vector::from_vec(vec!['a', 'b', 'c', 'd']);
+        v.permute(&p);
+        assert_eq!(v.as_slice(), ['c', 'a', 'd', 'b']);
+        v.permute(&q);
+        let mut w = Vector::from_vec(vec!['a', 'b', 'c', 'd']);
+        w.permute(&p.compose(&q));
+        assert_eq!(v, w);
+        assert_eq!(
+            v.try_permute(&Permutation::identity(2)),
+            Err(MatrixError::Length { left: 2, right: 4 })
+        );
+
+        let bits = BitVector::from_support(4, &[0, 3]);
+        assert_eq!(p.permute_bits(&bits), BitVector::from_support(4, &[1, 2]));
+        let entries = Vector::from_vec(vec![1, 0, 0, 1]);
+        assert_eq!(
+            &entries * &p.to_matrix::<i32>(),
+            Vector::from_vec(vec![0, 1, 1, 0])
+        );
+    }
+
+    #[test]
+    fn permute_matrices() {
+        let m: Matrix<i32> = Matrix::from_function(3, 4, |col, row| (10 * row + col) as i32);
+        let p = Permutation::from_vec(vec![2, 0, 3, 1]);
+        let mut columns = m.clone();
+        columns.permute_columns(&p);
+        assert_eq!(columns, &m * &p.to_matrix::<i32>());
+        assert_eq!(columns[0], m[2]);
+
+        let r = Permutation::from_vec(vec![1, 2, 0]);
+        let mut rows = m.clone();
+        rows.permute_rows(&r);
+        assert_eq!(rows, &r.to_matrix::<i32>().transpose() * &m);
+        assert_eq!(rows[0].as_slice(), [10, 20, 0]);
+        assert!(rows.try_permute_rows(&p).is_err());
+        assert!(rows.try_permute_columns(&r).is_err());
+
+        let mut bits = BitMatrix::from_matrix(&m.clone().transpose());
+        bits.permute_rows(&p);
+        bits.permute_columns(&r);
+        let mut expected = m.transpose();
+        expected.permute_rows(&p);
+        expected.permute_columns(&r);
+        assert_eq!(bits, BitMatrix::from_matrix(&expected));
+        assert!(bits.try_permute_columns(&p).is_err());
+    }
+}
@@ -0,0 +1,372 @@
+extern crate zero_one;
+use self::zero_one::{One, Zero};
+use error::{unwrap, MatrixError};
+use inverse::Inverse;
+use matrix::Matrix;
+use shape::Shape;
+use std::ops;
+use vector::{self, Vector};
+
+/// A dense matrix stored as a list of rows.
+///
+/// This is the layout for algorithms built on row operations, such as
+/// elimination on the rows of a parity-check matrix: swapping rows moves two
+/// vectors and adding a row to another touches only those two, where the
+/// column-major `Matrix` has to visit every column. Converting an owned
+/// matrix between the layouts moves the entries instead of cloning them, and
+/// `into_transpose` reinterprets the rows as columns for free.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RowMatrix<T> {
+    ncols: usize,
+    rows: Vec<Vector<T>>,
+}
+
+impl<T: Zero> RowMatrix<T> {
+    pub fn zero(nrows: usize, ncols: usize) -> RowMatrix<T> {
+        RowMatrix {
+            ncols,
+            rows: (0..nrows)
+                .map(|_| Vector::from_vec((0..ncols).map(|_| T::zero()).collect()))
+                .collect(),
+        }
+    }
+}
+
+impl<T: Zero + One> RowMatrix<T> {
+    pub fn identity(size: usize) -> RowMatrix<T> {
+        RowMatrix {
+            ncols: size,
+            rows: (0..size)
+                .map(|i| {
+                    Vector::from_vec(
+                        (0..size)
+                            .map(|j| if i == j { T::one() } else { T::zero() })
+                            .collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<T> RowMatrix<T> {
+    pub fn from_rows(ncols: usize, rows: Vec<Vector<T>>) -> RowMatrix<T> {
+        unwrap(RowMatrix::try_from_rows(ncols, rows))
+    }
+
+    /// Build a matrix from rows that should all have length `ncols`
+    pub fn try_from_rows(ncols: usize, rows: Vec<Vector<T>>) -> Result<RowMatrix<T>, MatrixError> {
+        if let Some(index) = rows.iter().position(|row| row.len() != ncols) {
+            return Err(MatrixError::Ragged {
+                index,
+                expected: ncols,
+                found: rows[index].len(),
+            });
+        }
+        Ok(RowMatrix { ncols, rows })
+    }
+
+    /// The transpose, with the rows of this matrix as its columns
+    pub fn into_transpose(self) -> Matrix<T> {
+        Matrix::from_vec(self.rows)
+    }
+
+    pub fn nrows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn ncols(&self) -> usize {
+        self.ncols
+    }
+
+    pub fn shape(&self) -> Shape {
+        Shape::new(self.nrows(), self.ncols)
+    }
+
+    pub fn row(&self, idx: usize) -> &Vector<T> {
+        &self.rows[idx]
+    }
+
+    pub fn rows(&self) -> &[Vector<T>] {
+        &self.rows
+    }
+
+    pub fn swap_rows(&mut self, a: usize, b: usize) {
+        self.rows.swap(a, b);
+    }
+
+    /// Glue the other matrix below this one
+    pub fn stack(&mut self, other: RowMatrix<T>) {
+        unwrap(self.try_stack(other))
+    }
+
+    /// Glue the other matrix below this one, if they have the same number
+    /// of columns
+    pub fn try_stack(&mut self, other: RowMatrix<T>) -> Result<(), MatrixError> {
+        self.shape().check_stack(other.shape())?;
+        self.rows.extend(other.rows);
+        Ok(())
+    }
+}
+
+impl<T> Matrix<T> {
+    /// The transpose, with the columns of this matrix as its rows
+    pub fn into_transpose(self) -> RowMatrix<T> {
+        let ncols = self.nrows();
+        RowMatrix {
+            ncols,
+            rows: self.into_columns(),
+        }
+    }
+}
+
+impl<T: Clone> RowMatrix<T> {
+    /// Convert a column-major matrix
+    pub fn from_matrix(matrix: &Matrix<T>) -> RowMatrix<T> {
+        RowMatrix::from(matrix.clone())
+    }
+
+    /// Convert to a column-major matrix
+    pub fn to_matrix(&self) -> Matrix<T> {
+        Matrix::from(self.clone())
+    }
+
+    pub fn transpose(&self) -> RowMatrix<T> {
+        RowMatrix::from(self.clone().into_transpose())
+    }
+}
+
+impl<T> From<Matrix<T>> for RowMatrix<T> {
+    fn from(matrix: Matrix<T>) -> RowMatrix<T> {
+        let (nrows, ncols) = (matrix.nrows(), matrix.ncols());
+        RowMatrix {
+            ncols,
+            rows: vector::transpose(matrix.into_columns(), nrows),
+        }
+    }
+}
+
+impl<T> From<RowMatrix<T>> for Matrix<T> {
+    fn from(matrix: RowMatrix<T>) -> Matrix<T> {
+        Matrix::from_vec(vector::transpose(matrix.rows, matrix.ncols))
+    }
+}
+
+impl<T> ops::Index<usize> for RowMatrix<T> {
+    type Output = Vector<T>;
+
+    #[inline]
+    fn index(&self, idx: usize) -> &Vector<T> {
+        &self.rows[idx]
+    }
+}
+
+impl<T> ops::IndexMut<usize> for RowMatrix<T> {
+    #[inline]
+    fn index_mut(&mut self, idx: usize) -> &mut Vector<T> {
+        &mut self.rows[idx]
+    }
+}
+
+impl<T: Clone> RowMatrix<T> {
+    /// Update every entry `x` of row `target` with the entry `y` of row
+    /// `source` in the same column, in place
+    fn combine_rows<F: FnMut(&mut T, &T)>(&mut self, target: usize, source: usize, mut f: F) {
+        if target == source {
+            let source = self.rows[source].clone();
+            for (x, y) in self.rows[target].iter_mut().zip(source.iter()) {
+                f(x, y);
+            }
+            return;
+        }
+        let (target, source) = if target < source {
+            let (head, tail) = self.rows.split_at_mut(source);
+            (&mut head[target], &tail[0])
+        } else {
+            let (head, tail) = self.rows.split_at_mut(target);
+            (&mut tail[0], &head[source])
+        };
+        for (x, y) in target.iter_mut().zip(source.iter()) {
+            f(x, y);
+        }
+    }
+}
+
+impl<T: Clone + ops::Add<Output = T>> RowMatrix<T> {
+    /// Add row `source` to row `target`
+    pub fn add_row(&mut self, target: usize, source: usize) {
+        self.combine_rows(target, source, |x, y| *x = x.clone() + y.clone());
+    }
+}
+
+impl<T: Clone + ops::Mul<Output = T>> RowMatrix<T> {
+    /// Multiply every entry of a row by `factor`
+    pub fn scale_row(&mut self, row: usize, factor: T) {
+        for x in self.rows[row].iter_mut() {
+            *x = factor.clone() * x.clone();
+        }
+    }
+}
+
+impl<T: Clone + ops::Sub<Output = T> + ops::Mul<Output = T>> RowMatrix<T> {
+    /// Subtract `factor` times row `source` from row `target`
+    pub fn sub_multiple(&mut self, target: usize, source: usize, factor: T) {
+        self.combine_rows(target, source, |x, y| {
+            *x = x.clone() - factor.clone() * y.clone()
+        });
+    }
+}
+
+impl<T> RowMatrix<T>
+where
+    T: Clone + Zero + PartialEq + ops::Add<Output = T> + ops::Mul<Output = T>,
+{
+    /// Multiply the matrix by a column vector: `M v`
+    pub fn mul_vector(&self, v: &Vector<T>) -> Vector<T> {
+        unwrap(self.try_mul_vector(v))
+    }
+
+    /// `M v`, or an error if the length of `v` is not the number of columns
+    pub fn try_mul_vector(&self, v: &Vector<T>) -> Result<Vector<T>, MatrixError> {
+        self.shape().check_product(Shape::column(v.len()))?;
+        Ok(Vector::from_vec(
+            self.rows
+                .iter()
+                .map(|row| {
+                    (0..self.ncols).fold(T::zero(), |sum, j| sum + row[j].clone() * v[j].clone())
+                })
+                .collect(),
+        ))
+    }
+}
+
+impl<T> RowMatrix<T>
+where
+    T: Clone + Zero + PartialEq + Inverse + ops::Sub<Output = T> + ops::Mul<Output = T>,
+{
+    /// Bring the matrix in reduced row echelon form with Gauss-Jordan
+    /// elimination, with the zero rows at the bottom.
+    ///
+    /// Returns the pivot columns, one for every non-zero row. Over a field
+    /// their number is the rank; over other rings only invertible entries
+    /// are used as pivots.
+    pub fn reduce_row_echelon(&mut self) -> Vec<usize> {
+        let nrows = self.nrows();
+        let mut pivots = Vec::new();
+        for col in 0..self.ncols {
+            let rank = pivots.len();
+            if rank == nrows {
+                break;
+            }
+            let pivot =
+                (rank..nrows).find_map(|row| self.rows[row][col].inverse().map(|i| (row, i)));
+            let (pivot, inverse) = match pivot {
+                Some(pivot) => pivot,
+                None => continue,
+            };
+            self.swap_rows(rank, pivot);
+            self.scale_row(rank, inverse);
+            for row in 0..nrows {
+                let factor = self.rows[row][col].clone();
+                if row != rank && factor != T::zero() {
+                    self.sub_multiple(row, rank, factor);
+                }
+            }
+            pivots.push(col);
+        }
+        pivots
+    }
+
+    pub fn rank(&self) -> usize {
+        self.clone().reduce_row_echelon().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> RowMatrix<f64> {
+        RowMatrix::from_rows(
+            3,
+            vec![
+                Vector::from_vec(vec![0.0, 2.0, 4.0]),
+                Vector::from_vec(vec![1.0, 1.0, 1.0]),
+                Vector::from_vec(vec![2.0, 4.0, 6.0]),
+            ],
+        )
+    }
+
+    #[test]
+    fn layouts() {
+        let m: Matrix<i32> = Matrix::from_function(3, 4, |col, row| (10 * row + col) as i32);
+        let r = RowMatrix::from_matrix(&m);
+        assert_eq!(r.shape(), Shape::new(3, 4));
+        assert_eq!(r[2][1], 21);
+        assert_eq!(r.to_matrix(), m);
+        assert_eq!(Matrix::from(RowMatrix::from(m.clone())), m);
+        assert_eq!(r.transpose().to_matrix(), m.transpose());
+        // reinterpreting the storage transposes
+        assert_eq!(
+            m.clone().into_transpose(),
+            RowMatrix::from_matrix(&m.transpose())
+        );
+        assert_eq!(r.clone().into_transpose(), m.transpose());
+        assert_eq!(
+            RowMatrix::<i32>::identity(3).to_matrix(),
+            Matrix::identity(3)
+        );
+        assert_eq!(RowMatrix::<i32>::zero(2, 5).to_matrix(), Matrix::zero(2, 5));
+    }
+
+    #[test]
+    fn row_operations() {
+        let mut m = RowMatrix::from_matrix(&Matrix::<i32>::identity(3));
+        m.add_row(0, 2);
+        assert_eq!(m[0], Vector::from_vec(vec![1, 0, 1]));
+        m.scale_row(1, 3);
+        assert_eq!(m[1], Vector::from_vec(vec![0, 3, 0]));
+        m.sub_multiple(0, 1, 2);
+        assert_eq!(m[0], Vector::from_vec(vec![1, -6, 1]));
+        m.swap_rows(0, 2);
+        assert_eq!(m[2], Vector::from_vec(vec![1, -6, 1]));
+        // a row combined with itself
+        let mut same = m.clone();
+        same.add_row(2, 2);
+        assert_eq!(same[2], Vector::from_vec(vec![2, -12, 2]));
+        same.sub_multiple(2, 2, 1);
+        assert_eq!(same[2], Vector::from_vec(vec![0, 0, 0]));
+        assert_eq!(
+            m.mul_vector(&Vector::from_vec(vec![1, 1, 1])),
+            Vector::from_vec(vec![1, 3, -4])
+        );
+        assert!(m.try_mul_vector(&Vector::from_vec(vec![1, 1])).is_err());
+        let mut n = m.clone();
+        n.stack(m);
+        assert_eq!(n.nrows(), 6);
+        assert!(n.try_stack(RowMatrix::zero(1, 2)).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_unequal_rows() {
+        RowMatrix::from_rows(
+            2,
+            vec![Vector::from_vec(vec![1]), Vector::from_vec(vec![1, 2])],
+        );
+    }
+
+    #[test]
+    fn reduce_row_echelon() {
+        let mut m = example();
+        assert_eq!(m.reduce_row_echelon(), vec![0, 1]);
+        assert_eq!(m[0], Vector::from_vec(vec![1.0, 0.0, -1.0]));
+        assert_eq!(m[1], Vector::from_vec(vec![0.0, 1.0, 2.0]));
+        assert_eq!(m[2], Vector::from_vec(vec![0.0, 0.0, 0.0]));
+        assert_eq!(example().rank(), 2);
+        assert_eq!(RowMatrix::<f64>::identity(4).rank(), 4);
+        // over the integers, 2 is not a pivot
+        let m = RowMatrix::from_rows(2, vec![Vector::from_vec(vec![2i32, 0])]);
+        assert_eq!(m.rank(), 0);
+    }
+}
@@ -0,0 +1,168 @@
+//! `Serialize` and `Deserialize` for the `serde` feature.
+//!
+//! A `Vector` is a plain sequence, a `Matrix` its dimensions and its entries column by column,
+//! and a `BitMatrix` its dimensions and its packed rows as one byte string.
+
+extern crate bit_vector;
+extern crate serde;
+extern crate serde_bytes;
+use self::bit_vector::BitVector;
+use self::serde::de::Error;
+use self::serde::ser::SerializeStruct;
+use self::serde::{Deserialize, Deserializer, Serialize, Serializer};
+use self::serde_bytes::{ByteBuf, Bytes};
+use bit_matrix::BitMatrix;
+use matrix::Matrix;
+use vector::Vector;
+
+impl<T: Serialize> Serialize for Vector<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Vector<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Vector<T>, D::Error> {
+        Vec::deserialize(deserializer).map(Vector::from_vec)
+    }
+}
+
+/// The entries of a matrix, column by column
+struct Entries<'a, T: 'a>(&'a Matrix<T>);
+
+impl<'a, T: Serialize> Serialize for Entries<'a, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let m = self.0;
+        serializer.collect_seq((0..m.ncols()).flat_map(|j| m[j].iter()))
+    }
+}
+
+impl<T: Serialize> Serialize for Matrix<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Matrix", 3)?;
+        state.serialize_field("nrows", &self.nrows())?;
+        state.serialize_field("ncols", &self.ncols())?;
+        state.serialize_field("entries", &Entries(self))?;
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Matrix")]
+struct RawMatrix<T> {
+    nrows: usize,
+    ncols: usize,
+    entries: Vec<T>,
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Matrix<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Matrix<T>, D::Error> {
+        let raw = RawMatrix::deserialize(deserializer)?;
+        if raw.nrows.checked_mul(raw.ncols) != Some(raw.entries.len()) {
+            return Err(D::Error::custom(
+                "the number of entries does not match the dimensions",
+            ));
+        }
+        if raw.ncols == 0 && raw.nrows > 0 {
+            return Err(D::Error::custom("a matrix without columns has no rows"));
+        }
+        let nrows = raw.nrows;
+        let mut entries = raw.entries.into_iter();
+        let columns = (0..raw.ncols)
+            .map(|_| Vector::from_vec(entries.by_ref().take(nrows).collect()))
+            .collect();
+        Ok(Matrix::from_vec(columns))
+    }
+}
+
+impl Serialize for BitMatrix {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let bits: Vec<u8> = self.rows().iter().flat_map(|row| row.to_bytes()).collect();
+        let mut state = serializer.serialize_struct("BitMatrix", 3)?;
+        state.serialize_field("nrows", &self.nrows())?;
+        state.serialize_field("ncols", &self.ncols())?;
+        state.serialize_field("bits", Bytes::new(&bits))?;
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "BitMatrix")]
+struct RawBitMatrix {
+    nrows: usize,
+    ncols: usize,
+    bits: ByteBuf,
+}
+
+impl<'de> Deserialize<'de> for BitMatrix {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<BitMatrix, D::Error> {
+        let raw = RawBitMatrix::deserialize(deserializer)?;
+        let row_len = raw.ncols.div_ceil(8);
+        if raw.nrows.checked_mul(row_len) != Some(raw.bits.len()) {
+            return Err(D::Error::custom(
+                "the number of bytes does not match the dimensions",
+            ));
+        }
+        let rows = (0..raw.nrows)
+            .map(|i| {
+                BitVector::from_bytes(raw.ncols, &raw.bits[i * row_len..(i + 1) * row_len])
+                    .ok_or_else(|| D::Error::custom("bits set past the last column"))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(BitMatrix::from_rows(raw.ncols, rows))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+    use super::*;
+
+    #[test]
+    fn vector_json() {
+        let v = Vector::from_vec(vec![1, 2, 3]);
+        let json = serde_json::to_string(&v).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        assert_eq!(serde_json::from_str::<Vector<i32>>(&json).unwrap(), v);
+        assert_eq!(
+            serde_json::to_string(&Vector::repeat(2, 7)).unwrap(),
+            "[7,7]"
+        );
+    }
+
+    #[test]
+    fn matrix_json() {
+        let m = Matrix::from_vec(vec![
+            Vector::from_vec(vec![1, 2]),
+            Vector::from_vec(vec![3, 4]),
+            Vector::from_vec(vec![5, 6]),
+        ]);
+        let json = serde_json::to_string(&m).unwrap();
+        assert_eq!(json, r#"{"nrows":2,"ncols":3,"entries":[1,2,3,4,5,6]}"#);
+        assert_eq!(serde_json::from_str::<Matrix<i32>>(&json).unwrap(), m);
+        let empty: Matrix<i32> = Matrix::zero(0, 0);
+        let json = serde_json::to_string(&empty).unwrap();
+        assert_eq!(serde_json::from_str::<Matrix<i32>>(&json).unwrap(), empty);
+        assert!(
+            serde_json::from_str::<Matrix<i32>>(r#"{"nrows":2,"ncols":2,"entries":[1,2,3]}"#)
+                .is_err()
+        );
+        assert!(
+            serde_json::from_str::<Matrix<i32>>(r#"{"nrows":2,"ncols":0,"entries":[]}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn bit_matrix_json() {
+        let m = BitMatrix::from_text("1000000001\n0110000000\n").unwrap();
+        let json = serde_json::to_string(&m).unwrap();
+        assert_eq!(json, r#"{"nrows":2,"ncols":10,"bits":[1,2,6,0]}"#);
+        assert_eq!(serde_json::from_str::<BitMatrix>(&json).unwrap(), m);
+        assert!(
+            serde_json::from_str::<BitMatrix>(r#"{"nrows":2,"ncols":10,"bits":[1,2,6]}"#).is_err()
+        );
+        assert!(
+            serde_json::from_str::<BitMatrix>(r#"{"nrows":1,"ncols":10,"bits":[1,4]}"#).is_err()
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use error::MatrixError;
+use std::fmt;
+
+/// The number of rows and columns of a matrix.
+///
+/// Every operation on matrices checks the shapes of its operands through
+/// one of the `check_*` methods, so the rules live in one place.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Shape {
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl Shape {
+    pub fn new(rows: usize, cols: usize) -> Shape {
+        Shape { rows, cols }
+    }
+
+    /// The shape of a row vector of length `len`
+    pub fn row(len: usize) -> Shape {
+        Shape::new(1, len)
+    }
+
+    /// The shape of a column vector of length `len`
+    pub fn column(len: usize) -> Shape {
+        Shape::new(len, 1)
+    }
+
+    pub fn transpose(self) -> Shape {
+        Shape::new(self.cols, self.rows)
+    }
+
+    /// The number of entries
+    pub fn len(self) -> usize {
+        self.rows * self.cols
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.len() == 0
+    }
+
+    fn mismatch(self, other: Shape, operation: &'static str) -> MatrixError {
+        MatrixError::Dimensions {
+            operation,
+            left: self,
+            right: other,
+        }
+    }
+
+    /// Check that an entrywise operation, such as addition, applies
+    pub fn check_same(self, other: Shape, operation: &'static str) -> Result<(), MatrixError> {
+        if self == other {
+            Ok(())
+        } else {
+            Err(self.mismatch(other, operation))
+        }
+    }
+
+    /// Check that `self` times `other` is defined, and give its shape
+    pub fn check_product(self, other: Shape) -> Result<Shape, MatrixError> {
+        if self.cols == other.rows {
+            Ok(Shape::new(self.rows, other.cols))
+        } else {
+            Err(self.mismatch(other, "multiply"))
+        }
+    }
+
+    /// Check that `other` can be glued to the right, and give the result
+    pub fn check_augment(self, other: Shape) -> Result<Shape, MatrixError> {
+        if self.rows == other.rows {
+            Ok(Shape::new(self.rows, self.cols + other.cols))
+        } else {
+            Err(self.mismatch(other, "augment"))
+        }
+    }
+
+    /// Check that `other` can be put below, and give the result
+    pub fn check_stack(self, other: Shape) -> Result<Shape, MatrixError> {
+        if self.cols == other.cols {
+            Ok(Shape::new(self.rows + other.rows, self.cols))
+        } else {
+            Err(self.mismatch(other, "stack"))
+        }
+    }
+
+    /// Check that a segment of shape `size` with its top left entry at
+    /// `(row, col)` lies within the matrix. It may touch the last row and
+    /// column, and an empty segment may start just past them.
+    pub fn check_segment(self, row: usize, col: usize, size: Shape) -> Result<(), MatrixError> {
+        let fits = |start: usize, len: usize, bound: usize| {
+            start.checked_add(len).is_some_and(|end| end <= bound)
+        };
+        if fits(row, size.rows, self.rows) && fits(col, size.cols, self.cols) {
+            Ok(())
+        } else {
+            Err(MatrixError::OutOfBounds {
+                position: (row, col),
+                size,
+                shape: self,
+            })
+        }
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.rows, self.cols)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn products() {
+        let a = Shape::new(2, 3);
+        assert_eq!(a.check_product(Shape::new(3, 5)), Ok(Shape::new(2, 5)));
+        assert_eq!(a.check_product(a.transpose()), Ok(Shape::new(2, 2)));
+        assert!(a.check_product(a).is_err());
+        assert_eq!(Shape::row(2).check_product(a), Ok(Shape::row(3)));
+        assert_eq!(a.check_product(Shape::column(3)), Ok(Shape::column(2)));
+        assert!(a.check_product(Shape::column(2)).is_err());
+        // inner dimension zero gives a zero matrix
+        assert_eq!(
+            Shape::new(2, 0).check_product(Shape::new(0, 4)),
+            Ok(Shape::new(2, 4))
+        );
+    }
+
+    #[test]
+    fn entrywise() {
+        let a = Shape::new(2, 3);
+        assert!(a.check_same(a, "add").is_ok());
+        // equal as a product, which the old check accepted
+        assert!(a.check_same(a.transpose(), "add").is_err());
+        assert!(a.check_same(Shape::new(2, 4), "add").is_err());
+        assert!(a.check_same(Shape::new(1, 3), "add").is_err());
+        assert_eq!(
+            a.check_same(Shape::new(1, 3), "subtract")
+                .unwrap_err()
+                .to_string(),
+            "can not subtract a 2x3 and a 1x3 matrix"
+        );
+    }
+
+    #[test]
+    fn gluing() {
+        let a = Shape::new(2, 3);
+        assert_eq!(a.check_augment(Shape::new(2, 1)), Ok(Shape::new(2, 4)));
+        assert!(a.check_augment(Shape::new(3, 3)).is_err());
+        assert_eq!(a.check_stack(Shape::new(4, 3)), Ok(Shape::new(6, 3)));
+        assert!(a.check_stack(Shape::new(2, 2)).is_err());
+    }
+
+    #[test]
+    fn segments() {
+        let a = Shape::new(10, 10);
+        assert!(a.check_segment(0, 0, a).is_ok());
+        assert!(a.check_segment(7, 7, Shape::new(3, 3)).is_ok());
+        assert!(a.check_segment(9, 0, Shape::new(1, 10)).is_ok());
+        assert!(a.check_segment(8, 7, Shape::new(3, 3)).is_err());
+        assert!(a.check_segment(7, 8, Shape::new(3, 3)).is_err());
+        assert!(a.check_segment(10, 10, Shape::new(0, 0)).is_ok());
+        assert!(a.check_segment(11, 0, Shape::new(0, 0)).is_err());
+        assert!(a.check_segment(usize::MAX, 0, Shape::new(2, 1)).is_err());
+        assert_eq!(
+            a.check_segment(8, 0, Shape::new(3, 1)),
+            Err(MatrixError::OutOfBounds {
+                position: (8, 0),
+                size: Shape::new(3, 1),
+                shape: a,
+            })
+        );
+    }
+}
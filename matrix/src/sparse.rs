@@ -0,0 +1,713 @@
+extern crate bit_vector;
+extern crate zero_one;
+use self::bit_vector::BitVector;
+use self::zero_one::{One, Zero};
+use matrix::Matrix;
+use std::collections::BTreeSet;
+use std::mem;
+use std::ops;
+use vector::Vector;
+
+/// The storage order of a sparse matrix
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    /// Compressed sparse rows (CSR)
+    RowMajor,
+    /// Compressed sparse columns (CSC)
+    ColumnMajor,
+}
+
+impl Layout {
+    fn flip(self) -> Layout {
+        match self {
+            Layout::RowMajor => Layout::ColumnMajor,
+            Layout::ColumnMajor => Layout::RowMajor,
+        }
+    }
+}
+
+/// A sparse matrix in compressed row (CSR) or compressed column (CSC) form.
+///
+/// Only the non-zero entries are stored. For CSR the "outer" dimension is the
+/// rows and `indices` holds column indices; for CSC it is the other way around.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparseMatrix<T> {
+    nrows: usize,
+    ncols: usize,
+    layout: Layout,
+    offsets: Vec<usize>,
+    indices: Vec<usize>,
+    values: Vec<T>,
+}
+
+impl<T> SparseMatrix<T> {
+    pub fn nrows(&self) -> usize {
+        self.nrows
+    }
+
+    pub fn ncols(&self) -> usize {
+        self.ncols
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// The number of stored (non-zero) entries
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    fn outer_len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn to_outer_inner(&self, row: usize, col: usize) -> (usize, usize) {
+        match self.layout {
+            Layout::RowMajor => (row, col),
+            Layout::ColumnMajor => (col, row),
+        }
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        assert!(row < self.nrows && col < self.ncols, "Index out of bounds");
+        let (outer, inner) = self.to_outer_inner(row, col);
+        let range = self.offsets[outer]..self.offsets[outer + 1];
+        self.indices[range.clone()]
+            .binary_search(&inner)
+            .ok()
+            .map(|i| &self.values[range.start + i])
+    }
+
+    /// Iterate over the stored entries as `(row, col, value)`
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &T)> {
+        let layout = self.layout;
+        (0..self.outer_len()).flat_map(move |outer| {
+            (self.offsets[outer]..self.offsets[outer + 1]).map(move |i| {
+                let inner = self.indices[i];
+                match layout {
+                    Layout::RowMajor => (outer, inner, &self.values[i]),
+                    Layout::ColumnMajor => (inner, outer, &self.values[i]),
+                }
+            })
+        })
+    }
+}
+
+impl<T: Zero + PartialEq + ops::Add<Output = T>> SparseMatrix<T> {
+    /// Build a sparse matrix from `(row, col, value)` triplets.
+    ///
+    /// Duplicate entries are summed and zeros are not stored.
+    pub fn from_triplets(
+        nrows: usize,
+        ncols: usize,
+        layout: Layout,
+        triplets: Vec<(usize, usize, T)>,
+    ) -> SparseMatrix<T> {
+        let mut entries: Vec<(usize, usize, T)> = triplets
+            .into_iter()
+            .map(|(row, col, value)| {
+                assert!(row < nrows && col < ncols, "Index out of bounds");
+                match layout {
+                    Layout::RowMajor => (row, col, value),
+                    Layout::ColumnMajor => (col, row, value),
+                }
+            })
+            .collect();
+        entries.sort_by_key(|&(outer, inner, _)| (outer, inner));
+
+        let outer_len = match layout {
+            Layout::RowMajor => nrows,
+            Layout::ColumnMajor => ncols,
+        };
+        let mut offsets = vec![0; outer_len + 1];
+        let mut indices = Vec::with_capacity(entries.len());
+        let mut values = Vec::with_capacity(entries.len());
+        let mut entries = entries.into_iter().peekable();
+        while let Some((outer, inner, mut value)) = entries.next() {
+            while entries
+                .peek()
+                .is_some_and(|&(o, i, _)| (o, i) == (outer, inner))
+            {
+                value = value + entries.next().unwrap().2;
+            }
+            if value != T::zero() {
+                indices.push(inner);
+                values.push(value);
+                offsets[outer + 1] += 1;
+            }
+        }
+        for i in 0..outer_len {
+            offsets[i + 1] += offsets[i];
+        }
+
+        SparseMatrix {
+            nrows,
+            ncols,
+            layout,
+            offsets,
+            indices,
+            values,
+        }
+    }
+}
+
+impl<T: Zero + PartialEq + Clone + ops::Add<Output = T>> SparseMatrix<T> {
+    pub fn from_matrix(matrix: &Matrix<T>, layout: Layout) -> SparseMatrix<T> {
+        let zero = T::zero();
+        let mut triplets = Vec::new();
+        for col in 0..matrix.ncols() {
+            for row in 0..matrix.nrows() {
+                if matrix[col][row] != zero {
+                    triplets.push((row, col, matrix[col][row].clone()));
+                }
+            }
+        }
+        SparseMatrix::from_triplets(matrix.nrows(), matrix.ncols(), layout, triplets)
+    }
+}
+
+impl<T: Clone> SparseMatrix<T> {
+    /// Compute the transpose.
+    ///
+    /// This reinterprets CSR storage as CSC (and vice versa), so it does not
+    /// need to reorder any entries.
+    pub fn transpose(&self) -> SparseMatrix<T> {
+        SparseMatrix {
+            nrows: self.ncols,
+            ncols: self.nrows,
+            layout: self.layout.flip(),
+            offsets: self.offsets.clone(),
+            indices: self.indices.clone(),
+            values: self.values.clone(),
+        }
+    }
+
+    /// Convert to the given storage order
+    pub fn to_layout(&self, layout: Layout) -> SparseMatrix<T> {
+        if layout == self.layout {
+            return self.clone();
+        }
+        let outer_len = match layout {
+            Layout::RowMajor => self.nrows,
+            Layout::ColumnMajor => self.ncols,
+        };
+        let mut offsets = vec![0; outer_len + 1];
+        for &inner in &self.indices {
+            offsets[inner + 1] += 1;
+        }
+        for i in 0..outer_len {
+            offsets[i + 1] += offsets[i];
+        }
+
+        let mut next = offsets.clone();
+        let mut indices = vec![0; self.nnz()];
+        let mut values: Vec<Option<T>> = vec![None; self.nnz()];
+        for outer in 0..self.outer_len() {
+            for i in self.offsets[outer]..self.offsets[outer + 1] {
+                let inner = self.indices[i];
+                indices[next[inner]] = outer;
+                values[next[inner]] = Some(self.values[i].clone());
+                next[inner] += 1;
+            }
+        }
+
+        SparseMatrix {
+            nrows: self.nrows,
+            ncols: self.ncols,
+            layout,
+            offsets,
+            indices,
+            values: values.into_iter().map(Option::unwrap).collect(),
+        }
+    }
+}
+
+impl<T: Zero + Clone> SparseMatrix<T> {
+    pub fn to_matrix(&self) -> Matrix<T> {
+        let mut columns: Vec<Vec<T>> = (0..self.ncols)
+            .map(|_| (0..self.nrows).map(|_| T::zero()).collect())
+            .collect();
+        for (row, col, value) in self.iter() {
+            columns[col][row] = value.clone();
+        }
+        Matrix::from_vec(columns.into_iter().map(Vector::from_vec).collect())
+    }
+}
+
+/// Multiply the matrix by a column vector: `M v`
+impl<'a, T> ops::Mul<&'a Vector<T>> for &'a SparseMatrix<T>
+where
+    for<'b> &'b T: ops::Mul<Output = T>,
+    T: ops::Add<Output = T> + Zero,
+{
+    type Output = Vector<T>;
+
+    fn mul(self, other: &'a Vector<T>) -> Vector<T> {
+        assert_eq!(
+            self.ncols,
+            other.len(),
+            "The length of vector should match the number of matrix columns"
+        );
+        let mut result: Vec<T> = (0..self.nrows).map(|_| T::zero()).collect();
+        for (row, col, value) in self.iter() {
+            let acc = mem::replace(&mut result[row], T::zero());
+            result[row] = acc + value * &other[col];
+        }
+        Vector::from_vec(result)
+    }
+}
+
+/// Multiply a row vector by the matrix: `v M`
+impl<'a, T> ops::Mul<&'a SparseMatrix<T>> for &'a Vector<T>
+where
+    for<'b> &'b T: ops::Mul<Output = T>,
+    T: ops::Add<Output = T> + Zero,
+{
+    type Output = Vector<T>;
+
+    fn mul(self, other: &'a SparseMatrix<T>) -> Vector<T> {
+        assert_eq!(
+            self.len(),
+            other.nrows,
+            "The length of vector should match the number of matrix rows"
+        );
+        let mut result: Vec<T> = (0..other.ncols).map(|_| T::zero()).collect();
+        for (row, col, value) in other.iter() {
+            let acc = mem::replace(&mut result[col], T::zero());
+            result[col] = acc + &self[row] * value;
+        }
+        Vector::from_vec(result)
+    }
+}
+
+/// A sparse matrix over GF(2), stored as the sorted positions of the ones
+/// in every row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseBinaryMatrix {
+    ncols: usize,
+    rows: Vec<Vec<usize>>,
+}
+
+/// The result of structured Gaussian elimination
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Elimination {
+    /// The matrix in reduced row echelon form, up to the order of the rows
+    pub matrix: SparseBinaryMatrix,
+    /// The `(row, col)` pivots, in the order in which they were chosen
+    pub pivots: Vec<(usize, usize)>,
+}
+
+impl Elimination {
+    pub fn rank(&self) -> usize {
+        self.pivots.len()
+    }
+}
+
+/// Compute the symmetric difference of two sorted index lists, which is the
+/// sum of the rows over GF(2).
+fn add_rows(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let mut result = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] < b[j] {
+            result.push(a[i]);
+            i += 1;
+        } else if b[j] < a[i] {
+            result.push(b[j]);
+            j += 1;
+        } else {
+            i += 1;
+            j += 1;
+        }
+    }
+    result.extend_from_slice(&a[i..]);
+    result.extend_from_slice(&b[j..]);
+    result
+}
+
+impl SparseBinaryMatrix {
+    /// Build the matrix from the positions of the ones in every row
+    pub fn from_rows(ncols: usize, rows: Vec<Vec<usize>>) -> SparseBinaryMatrix {
+        let rows = rows
+            .into_iter()
+            .map(|mut row| {
+                row.sort();
+                row.dedup();
+                assert!(row.iter().all(|&c| c < ncols), "Index out of bounds");
+                row
+            })
+            .collect();
+        SparseBinaryMatrix { ncols, rows }
+    }
+
+    pub fn zero(nrows: usize, ncols: usize) -> SparseBinaryMatrix {
+        SparseBinaryMatrix {
+            ncols,
+            rows: vec![Vec::new(); nrows],
+        }
+    }
+
+    pub fn nrows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn ncols(&self) -> usize {
+        self.ncols
+    }
+
+    /// The number of ones
+    pub fn nnz(&self) -> usize {
+        self.rows.iter().map(Vec::len).sum()
+    }
+
+    /// The positions of the ones in row `idx`
+    pub fn row(&self, idx: usize) -> &[usize] {
+        &self.rows[idx]
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        assert!(col < self.ncols, "Index out of bounds");
+        self.rows[row].binary_search(&col).is_ok()
+    }
+
+    pub fn column_weights(&self) -> Vec<usize> {
+        let mut weights = vec![0; self.ncols];
+        for row in &self.rows {
+            for &col in row {
+                weights[col] += 1;
+            }
+        }
+        weights
+    }
+
+    pub fn from_matrix<T: Zero + PartialEq>(matrix: &Matrix<T>) -> SparseBinaryMatrix {
+        let zero = T::zero();
+        let mut rows = vec![Vec::new(); matrix.nrows()];
+        for col in 0..matrix.ncols() {
+            for (row, positions) in rows.iter_mut().enumerate() {
+                if matrix[col][row] != zero {
+                    positions.push(col);
+                }
+            }
+        }
+        SparseBinaryMatrix {
+            ncols: matrix.ncols(),
+            rows,
+        }
+    }
+
+    pub fn to_matrix<T: Zero + One>(&self) -> Matrix<T> {
+        let columns = (0..self.ncols)
+            .map(|col| {
+                Vector::from_vec(
+                    (0..self.nrows())
+                        .map(|row| {
+                            if self.get(row, col) {
+                                T::one()
+                            } else {
+                                T::zero()
+                            }
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+        Matrix::from_vec(columns)
+    }
+
+    pub fn transpose(&self) -> SparseBinaryMatrix {
+        let mut rows = vec![Vec::new(); self.ncols];
+        for (i, row) in self.rows.iter().enumerate() {
+            for &col in row {
+                rows[col].push(i);
+            }
+        }
+        SparseBinaryMatrix {
+            ncols: self.nrows(),
+            rows,
+        }
+    }
+
+    /// Multiply the matrix by a column vector: `M v`, e.g. a syndrome `H e^T`
+    pub fn mul_vector(&self, v: &BitVector) -> BitVector {
+        assert_eq!(
+            self.ncols,
+            v.len(),
+            "The length of vector should match the number of matrix columns"
+        );
+        let mut result = BitVector::zero(self.nrows());
+        for (i, row) in self.rows.iter().enumerate() {
+            if row.iter().filter(|&&col| v.get(col)).count() % 2 == 1 {
+                result.set(i, true);
+            }
+        }
+        result
+    }
+
+    /// Multiply a row vector by the matrix: `v M`
+    pub fn vector_mul(&self, v: &BitVector) -> BitVector {
+        assert_eq!(
+            self.nrows(),
+            v.len(),
+            "The length of vector should match the number of matrix rows"
+        );
+        let mut result = BitVector::zero(self.ncols);
+        for i in v.support() {
+            for &col in &self.rows[i] {
+                result.flip(col);
+            }
+        }
+        result
+    }
+
+    /// Bring the matrix into reduced row echelon form while limiting fill-in.
+    ///
+    /// Pivots are chosen Markowitz-style: the column with the fewest ones in
+    /// the rows that are not yet reduced, and within it the sparsest row.
+    pub fn structured_elimination(&self) -> Elimination {
+        let mut rows = self.rows.clone();
+        // the unreduced rows that have a one in every column
+        let mut columns: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); self.ncols];
+        for (i, row) in rows.iter().enumerate() {
+            for &col in row {
+                columns[col].insert(i);
+            }
+        }
+
+        let mut pivots = Vec::new();
+        loop {
+            let pivot_col = (0..self.ncols)
+                .filter(|&col| !columns[col].is_empty())
+                .min_by_key(|&col| columns[col].len());
+            let pivot_col = match pivot_col {
+                Some(col) => col,
+                None => break,
+            };
+            let pivot_row = *columns[pivot_col]
+                .iter()
+                .min_by_key(|&&row| rows[row].len())
+                .unwrap();
+
+            for &col in &rows[pivot_row] {
+                columns[col].remove(&pivot_row);
+            }
+            let targets: Vec<usize> = columns[pivot_col].iter().cloned().collect();
+            for target in targets {
+                for &col in &rows[pivot_row] {
+                    if !columns[col].remove(&target) {
+                        columns[col].insert(target);
+                    }
+                }
+                rows[target] = add_rows(&rows[target], &rows[pivot_row]);
+            }
+            pivots.push((pivot_row, pivot_col));
+        }
+
+        // back substitution: clear every pivot column in the earlier pivot rows
+        for k in (0..pivots.len()).rev() {
+            let (pivot_row, pivot_col) = pivots[k];
+            for &(row, _) in &pivots[..k] {
+                if rows[row].binary_search(&pivot_col).is_ok() {
+                    let sum = add_rows(&rows[row], &rows[pivot_row]);
+                    rows[row] = sum;
+                }
+            }
+        }
+
+        Elimination {
+            matrix: SparseBinaryMatrix {
+                ncols: self.ncols,
+                rows,
+            },
+            pivots,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Matrix<i32> {
+        Matrix::from_vec(vec![
+            Vector::from_vec(vec![1, 0, 0]),
+            Vector::from_vec(vec![0, 0, 3]),
+            Vector::from_vec(vec![2, 0, 0]),
+            Vector::from_vec(vec![0, 4, 5]),
+        ])
+    }
+
+    #[test]
+    fn from_matrix_roundtrip() {
+        for &layout in &[Layout::RowMajor, Layout::ColumnMajor] {
+            let s = SparseMatrix::from_matrix(&example(), layout);
+            assert_eq!(s.nrows(), 3);
+            assert_eq!(s.ncols(), 4);
+            assert_eq!(s.nnz(), 5);
+            assert_eq!(s.layout(), layout);
+            assert_eq!(s.to_matrix(), example());
+        }
+    }
+
+    #[test]
+    fn get() {
+        let s = SparseMatrix::from_matrix(&example(), Layout::RowMajor);
+        assert_eq!(s.get(0, 2), Some(&2));
+        assert_eq!(s.get(2, 3), Some(&5));
+        assert_eq!(s.get(1, 1), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_out_of_bounds() {
+        let s = SparseMatrix::from_matrix(&example(), Layout::RowMajor);
+        s.get(3, 0);
+    }
+
+    #[test]
+    fn from_triplets_sums_duplicates_and_drops_zeros() {
+        let s = SparseMatrix::from_triplets(
+            2,
+            2,
+            Layout::RowMajor,
+            vec![(0, 0, 1), (1, 1, 2), (0, 0, 2), (1, 0, 1), (1, 0, -1)],
+        );
+        assert_eq!(s.nnz(), 2);
+        assert_eq!(s.get(0, 0), Some(&3));
+        assert_eq!(s.get(1, 0), None);
+        assert_eq!(s.get(1, 1), Some(&2));
+    }
+
+    #[test]
+    fn transpose() {
+        let s = SparseMatrix::from_matrix(&example(), Layout::RowMajor);
+        let t = s.transpose();
+        assert_eq!(t.layout(), Layout::ColumnMajor);
+        assert_eq!(t.to_matrix(), example().transpose());
+    }
+
+    #[test]
+    fn to_layout() {
+        let csr = SparseMatrix::from_matrix(&example(), Layout::RowMajor);
+        let csc = csr.to_layout(Layout::ColumnMajor);
+        assert_eq!(
+            csc,
+            SparseMatrix::from_matrix(&example(), Layout::ColumnMajor)
+        );
+        assert_eq!(csc.to_layout(Layout::RowMajor), csr);
+    }
+
+    #[test]
+    fn matrix_vector_products() {
+        let m = example();
+        let v = Vector::from_vec(vec![1, 2, 3]);
+        let w = Vector::from_vec(vec![1, 2, 3, 4]);
+        for &layout in &[Layout::RowMajor, Layout::ColumnMajor] {
+            let s = SparseMatrix::from_matrix(&m, layout);
+            assert_eq!(&v * &s, &v * &m);
+            assert_eq!(&s * &w, &w * &m.transpose());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn matrix_vector_wrong_length() {
+        let s = SparseMatrix::from_matrix(&example(), Layout::RowMajor);
+        let _ = &s * &Vector::from_vec(vec![1, 2, 3]);
+    }
+
+    fn binary_example() -> SparseBinaryMatrix {
+        SparseBinaryMatrix::from_rows(
+            6,
+            vec![
+                vec![0, 1, 3],
+                vec![1, 2, 4],
+                vec![0, 2, 5],
+                vec![0, 3, 1],
+                vec![3, 4, 5],
+            ],
+        )
+    }
+
+    #[test]
+    fn binary_basics() {
+        let m = binary_example();
+        assert_eq!(m.nrows(), 5);
+        assert_eq!(m.ncols(), 6);
+        assert_eq!(m.nnz(), 15);
+        assert_eq!(m.row(3), &[0, 1, 3]);
+        assert!(m.get(2, 5));
+        assert!(!m.get(2, 4));
+        assert_eq!(m.column_weights(), vec![3, 3, 2, 3, 2, 2]);
+    }
+
+    #[test]
+    fn binary_matrix_roundtrip() {
+        let m = binary_example();
+        let dense: Matrix<i32> = m.to_matrix();
+        assert_eq!(dense.nrows(), 5);
+        assert_eq!(dense[3][0], 1);
+        assert_eq!(SparseBinaryMatrix::from_matrix(&dense), m);
+        assert_eq!(m.transpose().transpose(), m);
+        assert_eq!(m.transpose().to_matrix::<i32>(), dense.transpose());
+    }
+
+    #[test]
+    fn binary_products() {
+        let m = binary_example();
+        let dense: Matrix<i32> = m.to_matrix();
+        let v = BitVector::from_support(6, &[0, 4]);
+        let dense_v = Vector::from_vec((0..6).map(|i| v.get(i) as i32).collect());
+        let expected = &dense_v * &dense.transpose();
+        let product = m.mul_vector(&v);
+        for i in 0..5 {
+            assert_eq!(product.get(i), expected[i] % 2 == 1);
+        }
+
+        let u = BitVector::from_support(5, &[0, 1, 4]);
+        assert_eq!(m.vector_mul(&u), BitVector::from_support(6, &[0, 2, 5]));
+    }
+
+    /// Reduce `row` by the pivots; it is in the row space iff this gives zero.
+    fn reduce(elimination: &Elimination, row: &[usize]) -> Vec<usize> {
+        let mut row = row.to_vec();
+        for &(pivot_row, pivot_col) in &elimination.pivots {
+            if row.binary_search(&pivot_col).is_ok() {
+                row = add_rows(&row, elimination.matrix.row(pivot_row));
+            }
+        }
+        row
+    }
+
+    #[test]
+    fn structured_elimination() {
+        let m = binary_example();
+        let elimination = m.structured_elimination();
+        // rows 0 + 1 + 2 = 0 1 3 + 1 2 4 + 0 2 5 = 3 4 5 and row 3 = row 0
+        assert_eq!(elimination.rank(), 3);
+
+        for &(pivot_row, pivot_col) in &elimination.pivots {
+            for row in 0..m.nrows() {
+                assert_eq!(elimination.matrix.get(row, pivot_col), row == pivot_row);
+            }
+        }
+        for row in 0..m.nrows() {
+            assert!(reduce(&elimination, m.row(row)).is_empty());
+        }
+        let pivot_rows: Vec<usize> = elimination.pivots.iter().map(|&(r, _)| r).collect();
+        for row in 0..m.nrows() {
+            if !pivot_rows.contains(&row) {
+                assert!(elimination.matrix.row(row).is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn structured_elimination_full_rank() {
+        let m = SparseBinaryMatrix::from_rows(3, vec![vec![0, 1, 2], vec![1, 2], vec![2]]);
+        let elimination = m.structured_elimination();
+        assert_eq!(elimination.rank(), 3);
+        assert_eq!(elimination.matrix.nnz(), 3);
+    }
+}
@@ -0,0 +1,357 @@
+extern crate bit_vector;
+extern crate zero_one;
+use self::bit_vector::BitVector;
+use self::zero_one::{One, Zero};
+use bit_matrix::BitMatrix;
+use error::{unwrap, MatrixError};
+use inverse::Inverse;
+use matrix::Matrix;
+use permutation::Permutation;
+use row_matrix::RowMatrix;
+use std::ops;
+use vector::Vector;
+
+/// A matrix brought in systematic form by row operations and a permutation
+/// of its columns.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Systematic<M> {
+    /// The matrix in systematic form, `[I | A]` or `[A | I]`
+    pub matrix: M,
+    /// Permuting the columns of the original matrix by this, and then
+    /// reducing the rows, gives `matrix`. It is the identity when the block
+    /// that became `I` was invertible to begin with.
+    pub permutation: Permutation,
+}
+
+/// The order in which to try the columns as pivots, so that an invertible
+/// block where the identity should go is used as is
+fn preference(ncols: usize, nrows: usize, left: bool) -> Permutation {
+    if left || nrows > ncols {
+        Permutation::identity(ncols)
+    } else {
+        let start = ncols - nrows;
+        Permutation::from_vec((start..ncols).chain(0..start).collect())
+    }
+}
+
+/// Move the pivot columns together, to the left or the right of the others
+fn arrangement(pivots: &[usize], ncols: usize, left: bool) -> Permutation {
+    let mut is_pivot = vec![false; ncols];
+    for &col in pivots {
+        is_pivot[col] = true;
+    }
+    let free = (0..ncols).filter(|&col| !is_pivot[col]);
+    if left {
+        Permutation::from_vec(pivots.iter().cloned().chain(free).collect())
+    } else {
+        Permutation::from_vec(free.chain(pivots.iter().cloned()).collect())
+    }
+}
+
+fn check_rank(pivots: &[usize], rows: usize) -> Result<(), MatrixError> {
+    if pivots.len() == rows {
+        Ok(())
+    } else {
+        Err(MatrixError::Rank {
+            rank: pivots.len(),
+            rows,
+        })
+    }
+}
+
+fn unit<T: Zero + One>(len: usize, idx: usize) -> Vector<T> {
+    (0..len)
+        .map(|i| if i == idx { T::one() } else { T::zero() })
+        .collect()
+}
+
+impl<T> Matrix<T>
+where
+    T: Clone + Zero + PartialEq + Inverse + ops::Sub<Output = T> + ops::Mul<Output = T>,
+{
+    /// Bring the matrix in the form `[I | A]`, permuting the columns if the
+    /// leading block is singular, or give an error if the rows are dependent
+    pub fn to_systematic(&self) -> Result<Systematic<Matrix<T>>, MatrixError> {
+        self.systematic(true)
+    }
+
+    /// Bring the matrix in the form `[A | I]`, permuting the columns if the
+    /// trailing block is singular, or give an error if the rows are
+    /// dependent
+    pub fn to_systematic_right(&self) -> Result<Systematic<Matrix<T>>, MatrixError> {
+        self.systematic(false)
+    }
+
+    fn systematic(&self, left: bool) -> Result<Systematic<Matrix<T>>, MatrixError> {
+        let order = preference(self.ncols(), self.nrows(), left);
+        let mut matrix = self.clone();
+        matrix.permute_columns(&order);
+        let mut rows = RowMatrix::from(matrix);
+        let pivots = rows.reduce_row_echelon();
+        check_rank(&pivots, self.nrows())?;
+        let arrangement = arrangement(&pivots, self.ncols(), left);
+        let mut matrix = Matrix::from(rows);
+        matrix.permute_columns(&arrangement);
+        Ok(Systematic {
+            matrix,
+            permutation: order.compose(&arrangement),
+        })
+    }
+}
+
+impl<T: Clone + Zero + One + PartialEq + ops::Sub<Output = T>> Matrix<T> {
+    /// The parity-check matrix `[-A^T | I]` of a generator matrix `[I | A]`
+    pub fn to_parity_check(&self) -> Matrix<T> {
+        unwrap(self.try_to_parity_check())
+    }
+
+    /// The parity-check matrix `[-A^T | I]` of a generator matrix `[I | A]`,
+    /// or an error if the matrix does not start with an identity block
+    pub fn try_to_parity_check(&self) -> Result<Matrix<T>, MatrixError> {
+        if !self.has_identity(0) {
+            return Err(MatrixError::NotSystematic);
+        }
+        let (k, n) = (self.nrows(), self.ncols());
+        Ok(Matrix::from_vec(
+            (0..n)
+                .map(|col| {
+                    if col < k {
+                        (0..n - k)
+                            .map(|i| T::zero() - self[k + i][col].clone())
+                            .collect()
+                    } else {
+                        unit(n - k, col - k)
+                    }
+                })
+                .collect(),
+        ))
+    }
+
+    /// The generator matrix `[I | -B^T]` of a parity-check matrix `[B | I]`
+    pub fn to_generator(&self) -> Matrix<T> {
+        unwrap(self.try_to_generator())
+    }
+
+    /// The generator matrix `[I | -B^T]` of a parity-check matrix `[B | I]`,
+    /// or an error if the matrix does not end with an identity block
+    pub fn try_to_generator(&self) -> Result<Matrix<T>, MatrixError> {
+        let (r, n) = (self.nrows(), self.ncols());
+        if r > n || !self.has_identity(n - r) {
+            return Err(MatrixError::NotSystematic);
+        }
+        let k = n - r;
+        Ok(Matrix::from_vec(
+            (0..n)
+                .map(|col| {
+                    if col < k {
+                        unit(k, col)
+                    } else {
+                        (0..k)
+                            .map(|row| T::zero() - self[row][col - k].clone())
+                            .collect()
+                    }
+                })
+                .collect(),
+        ))
+    }
+
+    /// Whether the columns from `start` on form an identity block
+    fn has_identity(&self, start: usize) -> bool {
+        let size = self.nrows();
+        start + size <= self.ncols() && (0..size).all(|col| self[start + col] == unit(size, col))
+    }
+}
+
+impl BitMatrix {
+    /// Bring the matrix in the form `[I | A]`, permuting the columns if the
+    /// leading block is singular, or give an error if the rows are dependent
+    pub fn to_systematic(&self) -> Result<Systematic<BitMatrix>, MatrixError> {
+        self.systematic(true)
+    }
+
+    /// Bring the matrix in the form `[A | I]`, permuting the columns if the
+    /// trailing block is singular, or give an error if the rows are
+    /// dependent
+    pub fn to_systematic_right(&self) -> Result<Systematic<BitMatrix>, MatrixError> {
+        self.systematic(false)
+    }
+
+    fn systematic(&self, left: bool) -> Result<Systematic<BitMatrix>, MatrixError> {
+        let order = preference(self.ncols(), self.nrows(), left);
+        let mut matrix = self.clone();
+        matrix.permute_columns(&order);
+        let pivots = matrix.reduce_row_echelon();
+        check_rank(&pivots, self.nrows())?;
+        let arrangement = arrangement(&pivots, self.ncols(), left);
+        matrix.permute_columns(&arrangement);
+        Ok(Systematic {
+            matrix,
+            permutation: order.compose(&arrangement),
+        })
+    }
+
+    /// The parity-check matrix `[A^T | I]` of a generator matrix `[I | A]`
+    pub fn to_parity_check(&self) -> BitMatrix {
+        unwrap(self.try_to_parity_check())
+    }
+
+    /// The parity-check matrix `[A^T | I]` of a generator matrix `[I | A]`,
+    /// or an error if the matrix does not start with an identity block
+    pub fn try_to_parity_check(&self) -> Result<BitMatrix, MatrixError> {
+        if !self.has_identity(0) {
+            return Err(MatrixError::NotSystematic);
+        }
+        let (k, n) = (self.nrows(), self.ncols());
+        let rows = self
+            .columns(k, n - k)
+            .transpose()
+            .rows()
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let mut row = row.clone();
+                row.extend_from(&BitVector::from_support(n - k, &[i]));
+                row
+            })
+            .collect();
+        Ok(BitMatrix::from_rows(n, rows))
+    }
+
+    /// The generator matrix `[I | B^T]` of a parity-check matrix `[B | I]`
+    pub fn to_generator(&self) -> BitMatrix {
+        unwrap(self.try_to_generator())
+    }
+
+    /// The generator matrix `[I | B^T]` of a parity-check matrix `[B | I]`,
+    /// or an error if the matrix does not end with an identity block
+    pub fn try_to_generator(&self) -> Result<BitMatrix, MatrixError> {
+        let (r, n) = (self.nrows(), self.ncols());
+        if r > n || !self.has_identity(n - r) {
+            return Err(MatrixError::NotSystematic);
+        }
+        let k = n - r;
+        let rows = self
+            .columns(0, k)
+            .transpose()
+            .rows()
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let mut row = BitVector::from_support(k, &[i]);
+                row.extend_from(column);
+                row
+            })
+            .collect();
+        Ok(BitMatrix::from_rows(n, rows))
+    }
+
+    /// Whether the columns from `start` on form an identity block
+    fn has_identity(&self, start: usize) -> bool {
+        let size = self.nrows();
+        start + size <= self.ncols() && self.columns(start, size) == BitMatrix::identity(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(rows: &[&[f64]]) -> Matrix<f64> {
+        let vectors = rows
+            .iter()
+            .map(|row| Vector::from_vec(row.to_vec()))
+            .collect();
+        RowMatrix::from_rows(rows[0].len(), vectors).to_matrix()
+    }
+
+    fn bits(ncols: usize, supports: &[&[usize]]) -> BitMatrix {
+        BitMatrix::from_rows(
+            ncols,
+            supports
+                .iter()
+                .map(|support| BitVector::from_support(ncols, support))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn systematic() {
+        // the leading 2x2 block is singular
+        let g = rows(&[&[1.0, 2.0, 3.0], &[2.0, 4.0, 7.0]]);
+        let left = g.to_systematic().unwrap();
+        assert_eq!(left.matrix, rows(&[&[1.0, 0.0, 2.0], &[0.0, 1.0, 0.0]]));
+        assert_eq!(left.permutation, Permutation::from_vec(vec![0, 2, 1]));
+
+        // the trailing block is invertible, so no columns move
+        let right = g.to_systematic_right().unwrap();
+        assert_eq!(right.matrix, rows(&[&[0.5, 1.0, 0.0], &[0.0, 0.0, 1.0]]));
+        assert!(right.permutation.is_identity());
+
+        let dependent = rows(&[&[1.0, 2.0], &[2.0, 4.0]]);
+        assert_eq!(
+            dependent.to_systematic(),
+            Err(MatrixError::Rank { rank: 1, rows: 2 })
+        );
+        assert_eq!(
+            dependent.to_systematic_right().unwrap_err().to_string(),
+            "the 2 rows only have rank 1"
+        );
+    }
+
+    #[test]
+    fn standard_form() {
+        let g: Matrix<i32> = Matrix::from_vec(vec![
+            Vector::from_vec(vec![1, 0]),
+            Vector::from_vec(vec![0, 1]),
+            Vector::from_vec(vec![1, 3]),
+            Vector::from_vec(vec![2, 4]),
+        ]);
+        let h = g.to_parity_check();
+        assert_eq!(
+            h,
+            Matrix::from_vec(vec![
+                Vector::from_vec(vec![-1, -2]),
+                Vector::from_vec(vec![-3, -4]),
+                Vector::from_vec(vec![1, 0]),
+                Vector::from_vec(vec![0, 1]),
+            ])
+        );
+        assert_eq!(&g * &h.transpose(), Matrix::zero(2, 2));
+        assert_eq!(h.to_generator(), g);
+        assert_eq!(g.try_to_generator(), Err(MatrixError::NotSystematic));
+        assert_eq!(h.try_to_parity_check(), Err(MatrixError::NotSystematic));
+        assert_eq!(Matrix::<i32>::identity(3).to_parity_check().shape().cols, 3);
+    }
+
+    #[test]
+    fn binary() {
+        // a [7, 4] code whose first two columns are equal
+        let g = bits(7, &[&[0, 1, 4], &[0, 1, 5], &[2, 6], &[3, 4, 5, 6]]);
+        let systematic = g.to_systematic().unwrap();
+        assert_eq!(systematic.matrix.columns(0, 4), BitMatrix::identity(4));
+        assert!(!systematic.permutation.is_identity());
+        let mut permuted = g.clone();
+        permuted.permute_columns(&systematic.permutation);
+        let h = systematic.matrix.to_parity_check();
+        assert_eq!(h.nrows(), 3);
+        for row in permuted.rows() {
+            assert!(h.mul_vector(row).is_zero());
+        }
+        assert_eq!(h.to_generator(), systematic.matrix);
+
+        let right = h.to_systematic_right().unwrap();
+        assert_eq!(right.matrix, h);
+        assert!(right.permutation.is_identity());
+
+        // the trailing block of [I | I] is the identity too
+        let twice = bits(4, &[&[0, 2], &[1, 3]]);
+        let right = twice.to_systematic_right().unwrap();
+        assert_eq!(right.matrix, twice);
+        assert!(right.permutation.is_identity());
+        assert_eq!(
+            bits(3, &[&[0, 1], &[0, 1]]).to_systematic(),
+            Err(MatrixError::Rank { rank: 1, rows: 2 })
+        );
+        assert_eq!(g.try_to_generator(), Err(MatrixError::NotSystematic));
+    }
+}
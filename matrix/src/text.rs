@@ -0,0 +1,601 @@
+extern crate bit_vector;
+use self::bit_vector::BitVector;
+use bit_matrix::BitMatrix;
+use matrix::Matrix;
+use std::error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::mem;
+use std::str::FromStr;
+use vector::Vector;
+
+/// The entries a matrix in text form is over, as given in its header
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    /// `GF(q)`, with entries `0..q`
+    Finite(u64),
+    /// `Z`, with arbitrary entries
+    Integers,
+}
+
+impl Field {
+    fn is_binary(self) -> bool {
+        self == Field::Finite(2)
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Field::Finite(q) => write!(f, "GF({})", q),
+            Field::Integers => write!(f, "Z"),
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Field, String> {
+        if s == "Z" {
+            return Ok(Field::Integers);
+        }
+        let order = s
+            .strip_prefix("GF(")
+            .and_then(|s| s.strip_suffix(')'))
+            .and_then(|q| q.parse().ok())
+            .ok_or_else(|| format!("unknown field {:?}, expected GF(q) or Z", s))?;
+        if !is_prime_power(order) {
+            return Err(format!("GF({}) is not a field", order));
+        }
+        Ok(Field::Finite(order))
+    }
+}
+
+/// Whether `n` is prime, by the Miller-Rabin test to the first twelve prime
+/// bases, which is deterministic for all 64-bit `n`
+fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+    if n < 2 {
+        return false;
+    }
+    if let Some(&p) = BASES.iter().find(|&&p| n.is_multiple_of(p)) {
+        return n == p;
+    }
+    let mul = |a: u64, b: u64| (u128::from(a) * u128::from(b) % u128::from(n)) as u64;
+    let pow = |mut a: u64, mut e: u64| {
+        let mut result = 1;
+        while e > 0 {
+            if e & 1 == 1 {
+                result = mul(result, a);
+            }
+            a = mul(a, a);
+            e >>= 1;
+        }
+        result
+    };
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    BASES.iter().all(|&a| {
+        let mut x = pow(a, d);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mul(x, x);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// Whether `q = p^k` for a prime `p` and `k >= 1`, the orders of finite fields
+fn is_prime_power(q: u64) -> bool {
+    if is_prime(q) {
+        return true;
+    }
+    (2..64).any(|k| {
+        // the root is below 2^32, where the floating-point estimate is off
+        // by at most one
+        let estimate = (q as f64).powf(1.0 / f64::from(k)).round() as u64;
+        (estimate.saturating_sub(1)..=estimate + 1)
+            .any(|root| root.checked_pow(k) == Some(q) && is_prime(root))
+    })
+}
+
+/// An error while reading a matrix in text form
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    /// Malformed input, at the given (one-based) line
+    Parse {
+        line: usize,
+        message: String,
+    },
+}
+
+impl ReadError {
+    pub(crate) fn parse<S: Into<String>>(line: usize, message: S) -> ReadError {
+        ReadError::Parse {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReadError::Io(ref e) => write!(f, "{}", e),
+            ReadError::Parse { line, ref message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl error::Error for ReadError {}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> ReadError {
+        ReadError::Io(e)
+    }
+}
+
+/// Read the rows of a matrix in text form, converting every entry with
+/// `entry`, and return them with the number of columns.
+///
+/// The format is an optional header line `matrix <rows> <cols> [<field>]`
+/// followed by one line per row. A row is either a string of `0`/`1`
+/// characters, for binary matrices, or whitespace-separated entries. Empty
+/// lines and lines starting with `#` are skipped.
+fn read_rows<T, R, F>(reader: R, mut entry: F) -> Result<(usize, Vec<Vec<T>>), ReadError>
+where
+    R: BufRead,
+    F: FnMut(&str) -> Result<T, String>,
+{
+    let mut header: Option<(usize, usize, usize, Option<Field>)> = None;
+    let mut rows: Vec<Vec<T>> = Vec::new();
+    let mut ncols = None;
+    let mut last_line = 0;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let number = i + 1;
+        last_line = number;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens[0] == "matrix" {
+            if header.is_some() || !rows.is_empty() {
+                return Err(ReadError::parse(
+                    number,
+                    "the header should come before the rows",
+                ));
+            }
+            if tokens.len() < 3 || tokens.len() > 4 {
+                return Err(ReadError::parse(
+                    number,
+                    "expected a header `matrix <rows> <cols> [<field>]`",
+                ));
+            }
+            let dimension = |token: &str| {
+                token
+                    .parse()
+                    .map_err(|_| ReadError::parse(number, format!("invalid dimension {:?}", token)))
+            };
+            let field = match tokens.get(3) {
+                Some(token) => Some(token.parse().map_err(|e| ReadError::parse(number, e))?),
+                None => None,
+            };
+            header = Some((number, dimension(tokens[1])?, dimension(tokens[2])?, field));
+            ncols = header.map(|(_, _, cols, _)| cols);
+            continue;
+        }
+
+        let field = header.and_then(|(_, _, _, field)| field);
+        let bit_string = tokens.len() == 1
+            && field.is_none_or(Field::is_binary)
+            && line.chars().all(|c| c == '0' || c == '1');
+        let texts: Vec<String> = if bit_string {
+            line.chars().map(|c| c.to_string()).collect()
+        } else {
+            tokens.iter().map(|t| t.to_string()).collect()
+        };
+        let mut row = Vec::with_capacity(texts.len());
+        for text in &texts {
+            if let Some(Field::Finite(q)) = field {
+                match text.parse::<u64>() {
+                    Ok(value) if value < q => (),
+                    _ => {
+                        return Err(ReadError::parse(
+                            number,
+                            format!("entry {:?} is not an element of GF({})", text, q),
+                        ))
+                    }
+                }
+            }
+            row.push(entry(text).map_err(|e| ReadError::parse(number, e))?);
+        }
+        match ncols {
+            Some(n) if n != row.len() => {
+                return Err(ReadError::parse(
+                    number,
+                    format!("expected {} entries, found {}", n, row.len()),
+                ))
+            }
+            _ => ncols = Some(row.len()),
+        }
+        rows.push(row);
+    }
+    if let Some((_, nrows, _, _)) = header {
+        if nrows != rows.len() {
+            return Err(ReadError::parse(
+                last_line,
+                format!("expected {} rows, found {}", nrows, rows.len()),
+            ));
+        }
+    }
+    Ok((ncols.unwrap_or(0), rows))
+}
+
+/// Read the rows of a matrix as printed by Sage and MAGMA, converting
+/// every entry with `entry`, and return them with the number of columns.
+///
+/// Every row is in square brackets, with entries separated by whitespace or
+/// commas. A row may be wrapped over several lines, as MAGMA does for wide
+/// matrices. The subdivisions Sage prints, `|` between entries and rows of
+/// `-` and `+`, are ignored. Empty lines and lines starting with `#` are
+/// skipped.
+fn read_bracketed_rows<T, R, F>(reader: R, mut entry: F) -> Result<(usize, Vec<Vec<T>>), ReadError>
+where
+    R: BufRead,
+    F: FnMut(&str) -> Result<T, String>,
+{
+    let mut rows: Vec<Vec<T>> = Vec::new();
+    let mut ncols = None;
+    // the entries of the row being read, with the lines they are on
+    let mut current: Option<Vec<(usize, String)>> = None;
+    let mut last_line = 0;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let number = i + 1;
+        last_line = number;
+        let trimmed = line.trim();
+        if current.is_none() && (trimmed.is_empty() || trimmed.starts_with('#')) {
+            continue;
+        }
+        let mut token = String::new();
+        for c in trimmed.chars().chain(Some(' ')) {
+            let separator = c.is_whitespace() || c == ',' || c == '|' || c == '[' || c == ']';
+            if separator && !token.is_empty() {
+                if let Some(ref mut tokens) = current {
+                    tokens.push((number, mem::take(&mut token)));
+                }
+            }
+            match c {
+                '[' if current.is_some() => {
+                    return Err(ReadError::parse(number, "unexpected `[` inside a row"))
+                }
+                '[' => current = Some(Vec::new()),
+                ']' => {
+                    let tokens = current
+                        .take()
+                        .ok_or_else(|| ReadError::parse(number, "unexpected `]`"))?;
+                    let subdivision = !tokens.is_empty()
+                        && tokens
+                            .iter()
+                            .all(|(_, t)| t.chars().all(|c| c == '-' || c == '+'));
+                    if subdivision {
+                        continue;
+                    }
+                    let mut row = Vec::with_capacity(tokens.len());
+                    for (line, text) in tokens {
+                        row.push(entry(&text).map_err(|e| ReadError::parse(line, e))?);
+                    }
+                    match ncols {
+                        Some(n) if n != row.len() => {
+                            return Err(ReadError::parse(
+                                number,
+                                format!("expected {} entries, found {}", n, row.len()),
+                            ))
+                        }
+                        _ => ncols = Some(row.len()),
+                    }
+                    rows.push(row);
+                }
+                _ if separator => (),
+                _ if current.is_none() => {
+                    return Err(ReadError::parse(
+                        number,
+                        "expected a row in square brackets",
+                    ))
+                }
+                _ => token.push(c),
+            }
+        }
+    }
+    if current.is_some() {
+        return Err(ReadError::parse(last_line, "expected `]` to close the row"));
+    }
+    Ok((ncols.unwrap_or(0), rows))
+}
+
+/// Build a matrix from its rows
+fn from_rows<T>(ncols: usize, rows: Vec<Vec<T>>) -> Matrix<T> {
+    let mut columns: Vec<Vec<T>> = (0..ncols).map(|_| Vec::with_capacity(rows.len())).collect();
+    for row in rows {
+        for (column, value) in columns.iter_mut().zip(row) {
+            column.push(value);
+        }
+    }
+    Matrix::from_vec(columns.into_iter().map(Vector::from_vec).collect())
+}
+
+/// Parse an entry of a matrix
+fn parse_entry<T: FromStr>(text: &str) -> Result<T, String> {
+    text.parse::<T>()
+        .map_err(|_| format!("invalid entry {:?}", text))
+}
+
+/// Parse an entry of a binary matrix
+fn parse_bit(text: &str) -> Result<bool, String> {
+    match text {
+        "0" => Ok(false),
+        "1" => Ok(true),
+        _ => Err(format!("entry {:?} is not binary", text)),
+    }
+}
+
+fn bit_matrix(ncols: usize, rows: Vec<Vec<bool>>) -> BitMatrix {
+    BitMatrix::from_rows(
+        ncols,
+        rows.iter().map(|row| BitVector::from_bools(row)).collect(),
+    )
+}
+
+fn write_header<W: Write>(
+    writer: &mut W,
+    nrows: usize,
+    ncols: usize,
+    field: Field,
+) -> io::Result<()> {
+    writeln!(writer, "matrix {} {} {}", nrows, ncols, field)
+}
+
+impl<T: FromStr> Matrix<T> {
+    /// Read a matrix in text form, parsing every entry with `FromStr`
+    pub fn read_text<R: BufRead>(reader: R) -> Result<Matrix<T>, ReadError> {
+        let (ncols, rows) = read_rows(reader, parse_entry)?;
+        Ok(from_rows(ncols, rows))
+    }
+
+    pub fn from_text(text: &str) -> Result<Matrix<T>, ReadError> {
+        Matrix::read_text(text.as_bytes())
+    }
+
+    /// Read a matrix as printed by Sage or MAGMA, with one row per line in
+    /// square brackets
+    pub fn read_bracketed<R: BufRead>(reader: R) -> Result<Matrix<T>, ReadError> {
+        let (ncols, rows) = read_bracketed_rows(reader, parse_entry)?;
+        Ok(from_rows(ncols, rows))
+    }
+
+    pub fn from_bracketed(text: &str) -> Result<Matrix<T>, ReadError> {
+        Matrix::read_bracketed(text.as_bytes())
+    }
+}
+
+impl<T: fmt::Display> Matrix<T> {
+    /// Write the matrix in text form, with a header. Matrices over GF(2)
+    /// get one bit string per row.
+    pub fn write_text<W: Write>(&self, field: Field, writer: &mut W) -> io::Result<()> {
+        write_header(writer, self.nrows(), self.ncols(), field)?;
+        let separator = if field.is_binary() { "" } else { " " };
+        for i in 0..self.nrows() {
+            let entries: Vec<String> = (0..self.ncols()).map(|j| self[j][i].to_string()).collect();
+            writeln!(writer, "{}", entries.join(separator))?;
+        }
+        Ok(())
+    }
+
+    pub fn to_text(&self, field: Field) -> String {
+        let mut text = Vec::new();
+        self.write_text(field, &mut text).unwrap();
+        String::from_utf8(text).unwrap()
+    }
+}
+
+impl BitMatrix {
+    /// Read a binary matrix in text form; entries should be `0` or `1`
+    pub fn read_text<R: BufRead>(reader: R) -> Result<BitMatrix, ReadError> {
+        let (ncols, rows) = read_rows(reader, parse_bit)?;
+        Ok(bit_matrix(ncols, rows))
+    }
+
+    pub fn from_text(text: &str) -> Result<BitMatrix, ReadError> {
+        BitMatrix::read_text(text.as_bytes())
+    }
+
+    /// Read a binary matrix as printed by Sage or MAGMA
+    pub fn read_bracketed<R: BufRead>(reader: R) -> Result<BitMatrix, ReadError> {
+        let (ncols, rows) = read_bracketed_rows(reader, parse_bit)?;
+        Ok(bit_matrix(ncols, rows))
+    }
+
+    pub fn from_bracketed(text: &str) -> Result<BitMatrix, ReadError> {
+        BitMatrix::read_bracketed(text.as_bytes())
+    }
+
+    /// Write the matrix in text form, with a header and one bit string per row
+    pub fn write_text<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_header(writer, self.nrows(), self.ncols(), Field::Finite(2))?;
+        for row in self.rows() {
+            let bits: String = row.iter().map(|bit| if bit { '1' } else { '0' }).collect();
+            writeln!(writer, "{}", bits)?;
+        }
+        Ok(())
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = Vec::new();
+        self.write_text(&mut text).unwrap();
+        String::from_utf8(text).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error<T: fmt::Debug>(result: Result<T, ReadError>) -> (usize, String) {
+        match result {
+            Err(ReadError::Parse { line, message }) => (line, message),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn binary_rows() {
+        let m: Matrix<i32> = Matrix::from_text("# a comment\n101\n\n011\n").unwrap();
+        assert_eq!(m.nrows(), 2);
+        assert_eq!(m.ncols(), 3);
+        assert_eq!(m[0][0], 1);
+        assert_eq!(m[0][1], 0);
+        assert_eq!(m[2][1], 1);
+    }
+
+    #[test]
+    fn integer_rows_with_header() {
+        let text = "matrix 2 3 GF(7)\n1 6 0\n3 2 5\n";
+        let m: Matrix<i64> = Matrix::from_text(text).unwrap();
+        assert_eq!(m[1][0], 6);
+        assert_eq!(m[2][1], 5);
+        assert_eq!(m.to_text(Field::Finite(7)), text);
+        // a row of a single q-ary entry is not read as bits
+        let m: Matrix<i64> = Matrix::from_text("matrix 1 1 Z\n10").unwrap();
+        assert_eq!(m[0][0], 10);
+        let m: Matrix<f64> = Matrix::from_text("0.5 -1\n2 3").unwrap();
+        assert_eq!(m[1][0], -1.0);
+    }
+
+    #[test]
+    fn binary_roundtrip() {
+        let text = "matrix 3 4 GF(2)\n1010\n0110\n0001\n";
+        let m: Matrix<i32> = Matrix::from_text(text).unwrap();
+        assert_eq!(m.to_text(Field::Finite(2)), text);
+        let b = BitMatrix::from_text(text).unwrap();
+        assert_eq!(b, BitMatrix::from_matrix(&m));
+        assert_eq!(b.to_text(), text);
+        // separated bits are fine as well
+        assert_eq!(
+            BitMatrix::from_text("1 0 1 0\n0110\n0 0 0 1")
+                .unwrap()
+                .nrows(),
+            3
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let m: Matrix<i32> = Matrix::from_text("").unwrap();
+        assert_eq!((m.nrows(), m.ncols()), (0, 0));
+        let b = BitMatrix::from_text("matrix 0 5\n").unwrap();
+        assert_eq!((b.nrows(), b.ncols()), (0, 5));
+    }
+
+    #[test]
+    fn errors() {
+        let (line, message) = parse_error(Matrix::<i32>::from_text("101\n\n10\n"));
+        assert_eq!(line, 3);
+        assert_eq!(message, "expected 3 entries, found 2");
+        let (line, _) = parse_error(Matrix::<i32>::from_text("1 2\n3 x\n"));
+        assert_eq!(line, 2);
+        let (line, message) = parse_error(Matrix::<i32>::from_text("matrix 1 2 GF(3)\n1 3"));
+        assert_eq!(line, 2);
+        assert_eq!(message, "entry \"3\" is not an element of GF(3)");
+        let (line, _) = parse_error(Matrix::<i32>::from_text("matrix 3 2\n11\n01\n"));
+        assert_eq!(line, 3);
+        let (line, _) = parse_error(Matrix::<i32>::from_text("# field\nmatrix 1 2 GF(1)\n11"));
+        assert_eq!(line, 2);
+        let (line, _) = parse_error(Matrix::<i32>::from_text("11\nmatrix 1 2\n"));
+        assert_eq!(line, 2);
+        let (line, message) = parse_error(BitMatrix::from_text("1 2"));
+        assert_eq!(line, 1);
+        assert_eq!(message, "entry \"2\" is not binary");
+    }
+
+    #[test]
+    fn bracketed() {
+        // as printed by Sage, with a subdivision
+        let sage = "[1 0 0|1 1]\n[0 1 0|1 0]\n[-----+---]\n[0 0 1|0 1]\n";
+        let m: Matrix<i32> = Matrix::from_bracketed(sage).unwrap();
+        assert_eq!((m.nrows(), m.ncols()), (3, 5));
+        assert_eq!(m[3][1], 1);
+        assert_eq!(m[4][1], 0);
+        // as printed by MAGMA, with a wrapped row, or with commas
+        let magma = "[1 0 0 1 1]\n[0 1 0\n    1 0]\n[0 0 1 0 1]";
+        let b = BitMatrix::from_bracketed(magma).unwrap();
+        assert_eq!(b, BitMatrix::from_matrix(&m));
+        let list = "[1, 0, 0, 1, 1],\n[0, 1, 0, 1, 0],\n[0, 0, 1, 0, 1]";
+        assert_eq!(BitMatrix::from_bracketed(list).unwrap(), b);
+        let m: Matrix<i64> = Matrix::from_bracketed("[ 10  -3]\n[  2 100]").unwrap();
+        assert_eq!(m[1][0], -3);
+        assert_eq!(Matrix::<i32>::from_bracketed("").unwrap().nrows(), 0);
+    }
+
+    #[test]
+    fn bracketed_errors() {
+        let (line, message) = parse_error(Matrix::<i32>::from_bracketed("[1 0]\n\n[1 1 1]"));
+        assert_eq!(line, 3);
+        assert_eq!(message, "expected 2 entries, found 3");
+        let (line, message) = parse_error(BitMatrix::from_bracketed("[1 0\n 1 2]"));
+        assert_eq!(line, 2);
+        assert_eq!(message, "entry \"2\" is not binary");
+        let (line, message) = parse_error(BitMatrix::from_bracketed("[1 0]\n1 1"));
+        assert_eq!(line, 2);
+        assert_eq!(message, "expected a row in square brackets");
+        let (line, message) = parse_error(BitMatrix::from_bracketed("[1 0\n[1 1]"));
+        assert_eq!(line, 2);
+        assert_eq!(message, "unexpected `[` inside a row");
+        let (line, _) = parse_error(BitMatrix::from_bracketed("[1 0]]"));
+        assert_eq!(line, 1);
+        let (line, message) = parse_error(BitMatrix::from_bracketed("[1 0]\n[1 1\n"));
+        assert_eq!(line, 2);
+        assert_eq!(message, "expected `]` to close the row");
+    }
+
+    #[test]
+    fn prime_powers() {
+        let small: Vec<u64> = (0..30).filter(|&q| is_prime_power(q)).collect();
+        assert_eq!(
+            small,
+            [2, 3, 4, 5, 7, 8, 9, 11, 13, 16, 17, 19, 23, 25, 27, 29]
+        );
+        // the largest primes below 2^64 and 2^32
+        assert!(is_prime_power(18_446_744_073_709_551_557));
+        assert!(is_prime_power(4_294_967_291 * 4_294_967_291));
+        assert!(!is_prime_power(4_294_967_291 * 4_294_967_279));
+        assert!(is_prime_power(1 << 63));
+        assert!(!is_prime_power(u64::MAX));
+    }
+
+    #[test]
+    fn field_names() {
+        assert_eq!("GF(2)".parse(), Ok(Field::Finite(2)));
+        assert_eq!("Z".parse(), Ok(Field::Integers));
+        assert!("GF(x)".parse::<Field>().is_err());
+        assert_eq!("GF(256)".parse(), Ok(Field::Finite(256)));
+        assert_eq!("GF(6561)".parse(), Ok(Field::Finite(6561)));
+        for q in &["GF(0)", "GF(1)", "GF(6)", "GF(10)", "GF(12)", "GF(1000)"] {
+            assert_eq!(
+                q.parse::<Field>(),
+                Err(format!("{} is not a field", q)),
+                "{}",
+                q
+            );
+        }
+        assert_eq!(Field::Finite(13).to_string(), "GF(13)");
+    }
+}
@@ -1,24 +1,26 @@
+use std::iter;
 use std::ops;
+use std::slice;
 use std::vec;
-use std::iter;
-use std::clone::Clone;
-use std::rc::Rc;
+use error::{unwrap, MatrixError};
 
-#[derive(Debug, PartialEq)]
+/// A vector of elements of `T`, stored contiguously
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Vector<T> {
-    elements: Vec<Rc<T>>,
+    elements: Vec<T>,
 }
 
 impl<T> Vector<T> {
     pub fn from_vec(elements: Vec<T>) -> Vector<T> {
-        let elements = elements.into_iter().map(Rc::new).collect();
-        Vector { elements: elements }
+        Vector { elements }
     }
 
-    pub fn repeat(n: usize, element: T) -> Vector<T> {
-        let element = Rc::new(element);
+    pub fn repeat(n: usize, element: T) -> Vector<T>
+    where
+        T: Clone,
+    {
         Vector {
-            elements: ((0..n).map(|_| element.clone()).collect()),
+            elements: vec![element; n],
         }
     }
 
@@ -34,61 +36,116 @@ impl<T> Vector<T> {
         Vector { elements: vec![] }
     }
 
-    pub fn from_rc_vec(elements: Vec<Rc<T>>) -> Vector<T> {
-        Vector { elements }
+    pub fn as_slice(&self) -> &[T] {
+        &self.elements
     }
-}
 
-impl<T> Clone for Vector<T> {
-    fn clone(&self) -> Self {
-        Vector {
-            elements: self.elements.to_vec(),
-        }
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.elements
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.elements
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.elements.iter()
     }
 
-    fn clone_from(&mut self, other: &Self) {
-        self.elements = other.elements.clone()
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+        self.elements.iter_mut()
     }
 }
 
 impl<T> iter::IntoIterator for Vector<T> {
-    type Item = Rc<T>;
-    type IntoIter = vec::IntoIter<Rc<T>>;
+    type Item = T;
+    type IntoIter = vec::IntoIter<T>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.elements.into_iter()
     }
 }
 
+impl<'a, T> iter::IntoIterator for &'a Vector<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter()
+    }
+}
+
+impl<T> iter::FromIterator<T> for Vector<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Vector<T> {
+        Vector::from_vec(iter.into_iter().collect())
+    }
+}
+
 impl<T> ops::Index<usize> for Vector<T> {
     type Output = T;
 
     fn index(&self, idx: usize) -> &T {
-        &*self.elements[idx]
+        &self.elements[idx]
     }
 }
 
-impl<'a, T> ops::Index<ops::Range<usize>> for Vector<T> {
-    type Output = [Rc<T>];
+impl<T> ops::Index<ops::Range<usize>> for Vector<T> {
+    type Output = [T];
     fn index(&self, idxs: ops::Range<usize>) -> &Self::Output {
         &self.elements[idxs]
     }
 }
 
-impl<T: Clone> ops::IndexMut<usize> for Vector<T> {
+impl<T> ops::IndexMut<usize> for Vector<T> {
     fn index_mut(&mut self, idx: usize) -> &mut T {
-        Rc::make_mut(&mut self.elements[idx])
+        &mut self.elements[idx]
     }
 }
 
-impl<T> iter::Extend<Rc<T>> for Vector<T> {
-    fn extend<A: IntoIterator<Item = Rc<T>>>(&mut self, iter: A) {
+impl<T> iter::Extend<T> for Vector<T> {
+    fn extend<A: IntoIterator<Item = T>>(&mut self, iter: A) {
         self.elements.extend(iter);
     }
 }
 
+/// Regroup vectors of length `len` by position, so that the `i`th result
+/// holds the `i`th entries. The entries are moved, not cloned.
+pub(crate) fn transpose<T>(vectors: Vec<Vector<T>>, len: usize) -> Vec<Vector<T>> {
+    let mut entries: Vec<_> = vectors.into_iter().map(Vector::into_iter).collect();
+    (0..len)
+        .map(|_| {
+            entries
+                .iter_mut()
+                .map(|column| column.next().expect("vectors should have equal lengths"))
+                .collect()
+        })
+        .collect()
+}
+
+/// Check that two vectors can be combined elementwise
+fn check_lengths<T>(a: &Vector<T>, b: &Vector<T>) -> Result<(), MatrixError> {
+    if a.len() == b.len() {
+        Ok(())
+    } else {
+        Err(MatrixError::Length {
+            left: a.len(),
+            right: b.len(),
+        })
+    }
+}
+
 macro_rules! binary_operator {
-    ($type:ident, $funcname:ident, $operator:tt) => {
+    ($type:ident, $funcname:ident, $tryname:ident, $operator:tt) => {
+        impl<T> Vector<T> {
+            #[doc = concat!("`self ", stringify!($operator), " other`, or an error if the lengths differ")]
+            pub fn $tryname<'a>(&'a self, other: &'a Vector<T>) -> Result<Vector<T>, MatrixError>
+                where &'a T: ops::$type<Output = T>
+            {
+                check_lengths(self, other)?;
+                Ok(self.iter().zip(other).map(|(x, y)| x $operator y).collect())
+            }
+        }
+
         impl<'a, T: ops::$type<Output = T>> ops::$type<&'a Vector<T>> for &'a Vector<T>
             where &'a T: ops::$type<Output = T>
         {
@@ -96,25 +153,7 @@ macro_rules! binary_operator {
 
             #[inline]
             fn $funcname(self, other: &'a Vector<T>) -> Vector<T> {
-                assert_eq!(
-                    self.elements.len(),
-                    other.elements.len(),
-                    "Vectors should be of equal length"
-                );
-
-                let elements: Vec<Rc<T>> = self.elements
-                    .iter()
-                    .zip(other.elements.iter())
-                    .map(|(x, y)| {
-                        let x: &T = &*x;
-                        let y: &T = &*y;
-                        Rc::new(x $operator y)
-                    })
-                    .collect();
-
-                Vector {
-                    elements: elements
-                }
+                unwrap(self.$tryname(other))
             }
         }
 
@@ -127,26 +166,27 @@ macro_rules! binary_operator {
 
             #[inline]
             fn $funcname(self, other: Vector<T>) -> Vector<T> {
-                assert_eq!(
-                    self.elements.len(),
-                    other.elements.len(),
-                    "Vectors should be of equal length"
-                );
-
-                Vector {
-                    elements: self.elements
-                        .into_iter()
-                        .zip(other.elements)
-                        .map(|(x, y)| Rc::new(&*x $operator &*y))
-                        .collect()
-                }
+                unwrap(check_lengths(&self, &other));
+
+                self.iter().zip(&other).map(|(x, y)| x $operator y).collect()
             }
         }
     }
 }
 
-binary_operator!(Add, add, +);
-binary_operator!(Sub, sub, -);
+binary_operator!(Add, add, try_add, +);
+binary_operator!(Sub, sub, try_sub, -);
+
+impl<T: iter::Sum<T>> Vector<T> {
+    /// The inner product, or an error if the lengths differ
+    pub fn try_dot<'a>(&'a self, other: &'a Vector<T>) -> Result<T, MatrixError>
+    where
+        &'a T: ops::Mul<Output = T>,
+    {
+        check_lengths(self, other)?;
+        Ok(self.iter().zip(other).map(|(x, y)| x * y).sum())
+    }
+}
 
 impl<'a, T: ops::Mul<Output = T> + iter::Sum<T>> ops::Mul<&'a Vector<T>> for &'a Vector<T>
 where
@@ -156,40 +196,18 @@ where
 
     #[inline]
     fn mul(self, other: &'a Vector<T>) -> T {
-        assert_eq!(
-            self.elements.len(),
-            other.elements.len(),
-            "Vectors should be of equal length"
-        );
-
-        self.elements
-            .iter()
-            .zip(other.elements.iter())
-            .map(|(x, y)| {
-                let x: &T = &*x;
-                let y: &T = &*y;
-                x * y
-            })
-            .sum()
+        unwrap(self.try_dot(other))
     }
 }
 
-impl<T: ops::Mul<Output = T> + iter::Sum<T> + Copy> ops::Mul<Vector<T>> for Vector<T> {
+impl<T: ops::Mul<Output = T> + iter::Sum<T>> ops::Mul<Vector<T>> for Vector<T> {
     type Output = T;
 
     #[inline]
     fn mul(self, other: Vector<T>) -> T {
-        assert_eq!(
-            self.elements.len(),
-            other.elements.len(),
-            "Vectors should be of equal length"
-        );
+        unwrap(check_lengths(&self, &other));
 
-        self.elements
-            .into_iter()
-            .zip(other.elements)
-            .map(|(x, y)| *x * *y)
-            .sum()
+        self.into_iter().zip(other).map(|(x, y)| x * y).sum()
     }
 }
 
@@ -210,10 +228,10 @@ mod tests {
         let v1: Vector<i32> = Vector::from_vec(vec![0, 1, 2]);
         let v2: Vector<i32> = Vector::from_vec(vec![0, 1, 2]);
         let v3 = &v1 + &v2;
-        let els: Vec<i32> = v3.elements.iter().map(|x| **x).collect();
+        let els: Vec<i32> = v3.into_vec();
         assert_eq!(els, [0, 2, 4]);
         let v3 = v1 + v2;
-        let els: Vec<i32> = v3.elements.iter().map(|x| **x).collect();
+        let els: Vec<i32> = v3.into_vec();
         assert_eq!(els, [0, 2, 4]);
     }
 
@@ -222,7 +240,7 @@ mod tests {
         let v1: Vector<i32> = Vector::from_vec(vec![0, 1, 2]);
         let v2: Vector<i32> = Vector::from_vec(vec![0, 1, 2]);
         let v3 = &v1 - &v2;
-        let els: Vec<i32> = v3.elements.iter().map(|x| **x).collect();
+        let els: Vec<i32> = v3.into_vec();
         assert_eq!(els, [0, 0, 0]);
     }
 
@@ -237,13 +255,27 @@ mod tests {
     #[test]
     #[should_panic]
     fn add_diff_sized() {
-        &Vector::from_vec(vec![0]) + &Vector::from_vec(vec![0, 1]);
+        let _ = &Vector::from_vec(vec![0]) + &Vector::from_vec(vec![0, 1]);
     }
 
     #[test]
     #[should_panic]
     fn sub_diff_sized() {
-        &Vector::from_vec(vec![0]) - &Vector::from_vec(vec![0, 1]);
+        let _ = &Vector::from_vec(vec![0]) - &Vector::from_vec(vec![0, 1]);
+    }
+
+    #[test]
+    fn try_operators() {
+        let v1: Vector<i32> = Vector::from_vec(vec![1, 3, -5]);
+        let v2: Vector<i32> = Vector::from_vec(vec![4, -2, -1]);
+        assert_eq!(v1.try_add(&v2), Ok(Vector::from_vec(vec![5, 1, -6])));
+        assert_eq!(v1.try_sub(&v2), Ok(Vector::from_vec(vec![-3, 5, -4])));
+        assert_eq!(v1.try_dot(&v2), Ok(3));
+        let short = Vector::from_vec(vec![1, 2]);
+        let error = MatrixError::Length { left: 3, right: 2 };
+        assert_eq!(v1.try_add(&short), Err(error));
+        assert_eq!(v1.try_sub(&short), Err(error));
+        assert_eq!(v1.try_dot(&short), Err(error));
     }
 
     #[test]
@@ -257,7 +289,7 @@ mod tests {
     #[test]
     #[should_panic]
     fn get_index_out_of_bounds() {
-        &Vector::from_vec(vec![1])[100];
+        let _ = Vector::from_vec(vec![1])[100];
     }
 
     #[test]
@@ -270,21 +302,27 @@ mod tests {
     #[test]
     fn test_repeat() {
         let vec = Vector::repeat(3, 0);
-        let els: Vec<i32> = vec.elements.iter().map(|x| **x).collect();
-        assert_eq!(els, [0, 0, 0]);
-        assert_eq!(Rc::strong_count(&vec.elements[1]), 3);
-        assert!(Rc::ptr_eq(&vec.elements[0], &vec.elements[1]));
-        assert!(Rc::ptr_eq(&vec.elements[1], &vec.elements[2]));
+        assert_eq!(vec.as_slice(), [0, 0, 0]);
     }
 
     #[test]
-    fn test_assign_respects_references() {
+    fn test_assign_leaves_other_elements() {
         let mut vec = Vector::repeat(3, 0);
+        let copy = vec.clone();
         vec[1] = 4;
-        let els: Vec<i32> = vec.elements.iter().map(|x| **x).collect();
-        assert_eq!(els, [0, 4, 0]);
-        assert!(Rc::ptr_eq(&vec.elements[0], &vec.elements[2]));
-        assert!(!Rc::ptr_eq(&vec.elements[1], &vec.elements[2]));
+        assert_eq!(vec.as_slice(), [0, 4, 0]);
+        assert_eq!(copy.as_slice(), [0, 0, 0]);
     }
 
+    #[test]
+    fn iterators() {
+        let mut vec: Vector<i32> = (1..4).collect();
+        assert_eq!(vec.iter().sum::<i32>(), 6);
+        for x in vec.iter_mut() {
+            *x *= 2;
+        }
+        assert_eq!((&vec).into_iter().max(), Some(&6));
+        vec.extend(vec![8]);
+        assert_eq!(vec.into_iter().collect::<Vec<_>>(), [2, 4, 6, 8]);
+    }
 }
@@ -0,0 +1,789 @@
+extern crate zero_one;
+use self::zero_one::Zero;
+use error::{unwrap, MatrixError};
+use matrix::Matrix;
+use shape::Shape;
+use std::ops;
+use std::rc::Rc;
+use vector::Vector;
+
+/// Which entries of a vector, or which rows or columns of a matrix, a view
+/// sees, in order
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Selection {
+    /// `len` entries `stride` apart, from `start` on
+    Strided {
+        start: usize,
+        stride: usize,
+        len: usize,
+    },
+    /// The listed entries
+    Indices(Rc<[usize]>),
+}
+
+impl Selection {
+    fn all(len: usize) -> Selection {
+        Selection::Strided {
+            start: 0,
+            stride: 1,
+            len,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match *self {
+            Selection::Strided { len, .. } => len,
+            Selection::Indices(ref indices) => indices.len(),
+        }
+    }
+
+    /// The position of the `i`th selected entry
+    fn get(&self, i: usize) -> usize {
+        match *self {
+            Selection::Strided { start, stride, len } => {
+                assert!(i < len, "index {} out of range for a view of {}", i, len);
+                start + i * stride
+            }
+            Selection::Indices(ref indices) => indices[i],
+        }
+    }
+
+    /// The selected entries `start..start + len`, which should be in range
+    fn range(&self, start: usize, len: usize) -> Selection {
+        match *self {
+            Selection::Strided {
+                start: first,
+                stride,
+                ..
+            } => Selection::Strided {
+                start: first + start * stride,
+                stride,
+                len,
+            },
+            Selection::Indices(ref indices) => {
+                Selection::Indices(indices[start..start + len].into())
+            }
+        }
+    }
+
+    /// Every `step`th selected entry, from the first on
+    fn step(&self, step: usize) -> Selection {
+        assert!(step > 0, "a view can not step by zero");
+        match *self {
+            Selection::Strided { start, stride, len } => Selection::Strided {
+                start,
+                stride: stride * step,
+                len: len.div_ceil(step),
+            },
+            Selection::Indices(ref indices) => {
+                Selection::Indices(indices.iter().step_by(step).cloned().collect())
+            }
+        }
+    }
+
+    /// The selected entries at the listed positions
+    fn select(&self, indices: &[usize]) -> Result<Selection, MatrixError> {
+        let len = self.len();
+        if let Some(&index) = indices.iter().find(|&&index| index >= len) {
+            return Err(MatrixError::Selection { index, len });
+        }
+        Ok(Selection::Indices(
+            indices.iter().map(|&i| self.get(i)).collect(),
+        ))
+    }
+}
+
+/// The length of `range`, if it lies within `0..len`
+fn check_range(range: &ops::Range<usize>, len: usize) -> Result<usize, MatrixError> {
+    let size = range.end.saturating_sub(range.start);
+    Shape::row(len).check_segment(0, range.start, Shape::row(size))?;
+    Ok(size)
+}
+
+/// A borrowed, read-only selection of the entries of a vector: a range,
+/// every few entries or the entries at a list of positions.
+///
+/// Narrowing a view down never copies entries, so selecting the same few
+/// positions over and over is cheap.
+#[derive(Clone, Debug)]
+pub struct VectorView<'a, T: 'a> {
+    entries: &'a [T],
+    selection: Selection,
+}
+
+impl<'a, T> VectorView<'a, T> {
+    pub fn len(&self) -> usize {
+        self.selection.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&'a T> {
+        if idx < self.len() {
+            Some(&self.entries[self.selection.get(idx)])
+        } else {
+            None
+        }
+    }
+
+    pub fn iter<'b>(&'b self) -> impl Iterator<Item = &'a T> + 'b {
+        let entries = self.entries;
+        (0..self.len()).map(move |i| &entries[self.selection.get(i)])
+    }
+
+    /// The entries in `range`
+    pub fn slice(self, range: ops::Range<usize>) -> VectorView<'a, T> {
+        unwrap(self.try_slice(range))
+    }
+
+    /// The entries in `range`, or an error if it does not fit
+    pub fn try_slice(self, range: ops::Range<usize>) -> Result<VectorView<'a, T>, MatrixError> {
+        let len = check_range(&range, self.len())?;
+        Ok(VectorView {
+            entries: self.entries,
+            selection: self.selection.range(range.start, len),
+        })
+    }
+
+    /// Every `step`th entry, from the first on
+    pub fn step(self, step: usize) -> VectorView<'a, T> {
+        VectorView {
+            entries: self.entries,
+            selection: self.selection.step(step),
+        }
+    }
+
+    /// The entries at the listed positions, in that order
+    pub fn select(self, indices: &[usize]) -> VectorView<'a, T> {
+        unwrap(self.try_select(indices))
+    }
+
+    /// The entries at the listed positions, or an error if one is out of
+    /// range
+    pub fn try_select(self, indices: &[usize]) -> Result<VectorView<'a, T>, MatrixError> {
+        Ok(VectorView {
+            entries: self.entries,
+            selection: self.selection.select(indices)?,
+        })
+    }
+
+    /// Copy the entries into a vector of their own
+    pub fn to_vector(&self) -> Vector<T>
+    where
+        T: Clone,
+    {
+        self.iter().cloned().collect()
+    }
+
+    /// The number of nonzero entries
+    pub fn weight(&self) -> usize
+    where
+        T: Zero + PartialEq,
+    {
+        let zero = T::zero();
+        self.iter().filter(|&x| *x != zero).count()
+    }
+
+    pub fn dot<'b, V>(&self, other: V) -> T
+    where
+        V: Into<VectorView<'b, T>>,
+        T: 'b + Zero + ops::Add<Output = T>,
+        for<'c> &'c T: ops::Mul<Output = T>,
+    {
+        unwrap(self.try_dot(other))
+    }
+
+    /// The inner product, or an error if the lengths differ
+    pub fn try_dot<'b, V>(&self, other: V) -> Result<T, MatrixError>
+    where
+        V: Into<VectorView<'b, T>>,
+        T: 'b + Zero + ops::Add<Output = T>,
+        for<'c> &'c T: ops::Mul<Output = T>,
+    {
+        let other = other.into();
+        if self.len() != other.len() {
+            return Err(MatrixError::Length {
+                left: self.len(),
+                right: other.len(),
+            });
+        }
+        Ok(self
+            .iter()
+            .zip(other.iter())
+            .fold(T::zero(), |sum, (x, y)| sum + x * y))
+    }
+}
+
+impl<'a, T> ops::Index<usize> for VectorView<'a, T> {
+    type Output = T;
+
+    fn index(&self, idx: usize) -> &T {
+        &self.entries[self.selection.get(idx)]
+    }
+}
+
+impl<'a, 'b, T: PartialEq> PartialEq<VectorView<'b, T>> for VectorView<'a, T> {
+    fn eq(&self, other: &VectorView<'b, T>) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<'a, T> From<&'a Vector<T>> for VectorView<'a, T> {
+    fn from(vector: &'a Vector<T>) -> VectorView<'a, T> {
+        VectorView {
+            entries: vector.as_slice(),
+            selection: Selection::all(vector.len()),
+        }
+    }
+}
+
+impl<T> Vector<T> {
+    /// A view of all entries, to narrow down further
+    pub fn view(&self) -> VectorView<'_, T> {
+        VectorView::from(self)
+    }
+
+    /// The number of nonzero entries
+    pub fn weight(&self) -> usize
+    where
+        T: Zero + PartialEq,
+    {
+        self.view().weight()
+    }
+}
+
+/// A borrowed, read-only selection of the rows and columns of a matrix.
+///
+/// The rows and the columns are each picked as a range, every few of them
+/// or a list of indices, such as an information set. No entries are copied;
+/// `to_matrix` does that when it is needed.
+#[derive(Clone, Debug)]
+pub struct MatrixView<'a, T: 'a> {
+    matrix: &'a Matrix<T>,
+    rows: Selection,
+    cols: Selection,
+}
+
+/// A borrowed selection of the rows and columns of a matrix, through which
+/// the entries can be changed
+#[derive(Debug)]
+pub struct MatrixViewMut<'a, T: 'a> {
+    matrix: &'a mut Matrix<T>,
+    rows: Selection,
+    cols: Selection,
+}
+
+macro_rules! narrowing {
+    ($view:ident) => {
+        impl<'a, T> $view<'a, T> {
+            pub fn nrows(&self) -> usize {
+                self.rows.len()
+            }
+
+            pub fn ncols(&self) -> usize {
+                self.cols.len()
+            }
+
+            pub fn shape(&self) -> Shape {
+                Shape::new(self.nrows(), self.ncols())
+            }
+
+            /// The rows in `range`
+            pub fn rows(self, range: ops::Range<usize>) -> $view<'a, T> {
+                unwrap(self.try_rows(range))
+            }
+
+            /// The rows in `range`, or an error if it does not fit
+            pub fn try_rows(
+                mut self,
+                range: ops::Range<usize>,
+            ) -> Result<$view<'a, T>, MatrixError> {
+                let len = range.end.saturating_sub(range.start);
+                self.shape()
+                    .check_segment(range.start, 0, Shape::new(len, self.ncols()))?;
+                self.rows = self.rows.range(range.start, len);
+                Ok(self)
+            }
+
+            /// The columns in `range`
+            pub fn columns(self, range: ops::Range<usize>) -> $view<'a, T> {
+                unwrap(self.try_columns(range))
+            }
+
+            /// The columns in `range`, or an error if it does not fit
+            pub fn try_columns(
+                mut self,
+                range: ops::Range<usize>,
+            ) -> Result<$view<'a, T>, MatrixError> {
+                let len = range.end.saturating_sub(range.start);
+                self.shape()
+                    .check_segment(0, range.start, Shape::new(self.nrows(), len))?;
+                self.cols = self.cols.range(range.start, len);
+                Ok(self)
+            }
+
+            /// The rows with the listed indices, in that order
+            pub fn select_rows(self, indices: &[usize]) -> $view<'a, T> {
+                unwrap(self.try_select_rows(indices))
+            }
+
+            /// The rows with the listed indices, or an error if one is out
+            /// of range
+            pub fn try_select_rows(
+                mut self,
+                indices: &[usize],
+            ) -> Result<$view<'a, T>, MatrixError> {
+                self.rows = self.rows.select(indices)?;
+                Ok(self)
+            }
+
+            /// The columns with the listed indices, in that order
+            pub fn select_columns(self, indices: &[usize]) -> $view<'a, T> {
+                unwrap(self.try_select_columns(indices))
+            }
+
+            /// The columns with the listed indices, or an error if one is
+            /// out of range
+            pub fn try_select_columns(
+                mut self,
+                indices: &[usize],
+            ) -> Result<$view<'a, T>, MatrixError> {
+                self.cols = self.cols.select(indices)?;
+                Ok(self)
+            }
+
+            /// Every `step`th row, from the first on
+            pub fn step_rows(mut self, step: usize) -> $view<'a, T> {
+                self.rows = self.rows.step(step);
+                self
+            }
+
+            /// Every `step`th column, from the first on
+            pub fn step_columns(mut self, step: usize) -> $view<'a, T> {
+                self.cols = self.cols.step(step);
+                self
+            }
+        }
+    };
+}
+
+narrowing!(MatrixView);
+narrowing!(MatrixViewMut);
+
+impl<'a, T> MatrixView<'a, T> {
+    pub fn get(&self, row: usize, col: usize) -> &'a T {
+        let matrix: &'a Matrix<T> = self.matrix;
+        &matrix[self.cols.get(col)][self.rows.get(row)]
+    }
+
+    pub fn column(&self, col: usize) -> VectorView<'a, T> {
+        let matrix: &'a Matrix<T> = self.matrix;
+        VectorView {
+            entries: matrix[self.cols.get(col)].as_slice(),
+            selection: self.rows.clone(),
+        }
+    }
+
+    /// Copy the entries into a matrix of their own
+    pub fn to_matrix(&self) -> Matrix<T>
+    where
+        T: Clone,
+    {
+        Matrix::from_vec(
+            (0..self.ncols())
+                .map(|col| self.column(col).to_vector())
+                .collect(),
+        )
+    }
+
+    /// The entry of `self v` at `row`
+    fn row_dot(&self, row: usize, v: &VectorView<T>) -> T
+    where
+        T: Zero + ops::Add<Output = T>,
+        for<'c> &'c T: ops::Mul<Output = T>,
+    {
+        (0..self.ncols()).fold(T::zero(), |sum, col| sum + self.get(row, col) * &v[col])
+    }
+
+    pub fn mul<'b, M>(&self, other: M) -> Matrix<T>
+    where
+        M: Into<MatrixView<'b, T>>,
+        T: 'b + Zero + ops::Add<Output = T>,
+        for<'c> &'c T: ops::Mul<Output = T>,
+    {
+        unwrap(self.try_mul(other))
+    }
+
+    /// The product `self other`, or an error if the number of columns of
+    /// `self` is not the number of rows of `other`
+    pub fn try_mul<'b, M>(&self, other: M) -> Result<Matrix<T>, MatrixError>
+    where
+        M: Into<MatrixView<'b, T>>,
+        T: 'b + Zero + ops::Add<Output = T>,
+        for<'c> &'c T: ops::Mul<Output = T>,
+    {
+        let other = other.into();
+        let shape = self.shape().check_product(other.shape())?;
+        Ok(Matrix::from_vec(
+            (0..shape.cols)
+                .map(|col| {
+                    let column = other.column(col);
+                    (0..shape.rows)
+                        .map(|row| self.row_dot(row, &column))
+                        .collect()
+                })
+                .collect(),
+        ))
+    }
+
+    pub fn mul_vector<'b, V>(&self, v: V) -> Vector<T>
+    where
+        V: Into<VectorView<'b, T>>,
+        T: 'b + Zero + ops::Add<Output = T>,
+        for<'c> &'c T: ops::Mul<Output = T>,
+    {
+        unwrap(self.try_mul_vector(v))
+    }
+
+    /// The product `self v` with a column vector, such as a syndrome, or an
+    /// error if the length of `v` is not the number of columns
+    pub fn try_mul_vector<'b, V>(&self, v: V) -> Result<Vector<T>, MatrixError>
+    where
+        V: Into<VectorView<'b, T>>,
+        T: 'b + Zero + ops::Add<Output = T>,
+        for<'c> &'c T: ops::Mul<Output = T>,
+    {
+        let v = v.into();
+        self.shape().check_product(Shape::column(v.len()))?;
+        Ok((0..self.nrows()).map(|row| self.row_dot(row, &v)).collect())
+    }
+
+    pub fn vector_mul<'b, V>(&self, v: V) -> Vector<T>
+    where
+        V: Into<VectorView<'b, T>>,
+        T: 'b + Zero + ops::Add<Output = T>,
+        for<'c> &'c T: ops::Mul<Output = T>,
+    {
+        unwrap(self.try_vector_mul(v))
+    }
+
+    /// The product `v self` of a row vector and the view, or an error if
+    /// the length of `v` is not the number of rows
+    pub fn try_vector_mul<'b, V>(&self, v: V) -> Result<Vector<T>, MatrixError>
+    where
+        V: Into<VectorView<'b, T>>,
+        T: 'b + Zero + ops::Add<Output = T>,
+        for<'c> &'c T: ops::Mul<Output = T>,
+    {
+        let v = v.into();
+        Shape::row(v.len()).check_product(self.shape())?;
+        (0..self.ncols())
+            .map(|col| v.try_dot(self.column(col)))
+            .collect()
+    }
+}
+
+impl<'a, 'b, T: PartialEq> PartialEq<MatrixView<'b, T>> for MatrixView<'a, T> {
+    fn eq(&self, other: &MatrixView<'b, T>) -> bool {
+        self.shape() == other.shape()
+            && (0..self.ncols()).all(|col| self.column(col) == other.column(col))
+    }
+}
+
+impl<'a, T> From<&'a Matrix<T>> for MatrixView<'a, T> {
+    fn from(matrix: &'a Matrix<T>) -> MatrixView<'a, T> {
+        MatrixView {
+            rows: Selection::all(matrix.nrows()),
+            cols: Selection::all(matrix.ncols()),
+            matrix,
+        }
+    }
+}
+
+impl<'a, T> MatrixViewMut<'a, T> {
+    /// A read-only view of the same entries
+    pub fn view(&self) -> MatrixView<'_, T> {
+        MatrixView {
+            matrix: self.matrix,
+            rows: self.rows.clone(),
+            cols: self.cols.clone(),
+        }
+    }
+
+    pub fn get_mut(&mut self, row: usize, col: usize) -> &mut T {
+        let (row, col) = (self.rows.get(row), self.cols.get(col));
+        &mut self.matrix[col][row]
+    }
+
+    /// Overwrite the entries with those of `other`
+    pub fn assign<'b, M>(&mut self, other: M)
+    where
+        M: Into<MatrixView<'b, T>>,
+        T: 'b + Clone,
+    {
+        unwrap(self.try_assign(other))
+    }
+
+    /// Overwrite the entries with those of `other`, or give an error if the
+    /// shapes differ
+    pub fn try_assign<'b, M>(&mut self, other: M) -> Result<(), MatrixError>
+    where
+        M: Into<MatrixView<'b, T>>,
+        T: 'b + Clone,
+    {
+        let other = other.into();
+        self.shape().check_same(other.shape(), "assign")?;
+        for col in 0..self.ncols() {
+            for row in 0..self.nrows() {
+                *self.get_mut(row, col) = other.get(row, col).clone();
+            }
+        }
+        Ok(())
+    }
+
+    /// Set every entry to `value`
+    pub fn fill(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        for col in 0..self.ncols() {
+            for row in 0..self.nrows() {
+                *self.get_mut(row, col) = value.clone();
+            }
+        }
+    }
+}
+
+impl<'a, T> From<&'a mut Matrix<T>> for MatrixViewMut<'a, T> {
+    fn from(matrix: &'a mut Matrix<T>) -> MatrixViewMut<'a, T> {
+        MatrixViewMut {
+            rows: Selection::all(matrix.nrows()),
+            cols: Selection::all(matrix.ncols()),
+            matrix,
+        }
+    }
+}
+
+impl<T> Matrix<T> {
+    /// A view of the whole matrix, to narrow down further
+    pub fn view(&self) -> MatrixView<'_, T> {
+        MatrixView::from(self)
+    }
+
+    pub fn view_mut(&mut self) -> MatrixViewMut<'_, T> {
+        MatrixViewMut::from(self)
+    }
+
+    pub fn view_segment(
+        &self,
+        row: usize,
+        col: usize,
+        rows: usize,
+        cols: usize,
+    ) -> MatrixView<'_, T> {
+        unwrap(self.try_view_segment(row, col, rows, cols))
+    }
+
+    /// View the `rows x cols` segment at `(row, col)`
+    pub fn try_view_segment(
+        &self,
+        row: usize,
+        col: usize,
+        rows: usize,
+        cols: usize,
+    ) -> Result<MatrixView<'_, T>, MatrixError> {
+        self.shape()
+            .check_segment(row, col, Shape::new(rows, cols))?;
+        Ok(MatrixView {
+            matrix: self,
+            rows: Selection::all(self.nrows()).range(row, rows),
+            cols: Selection::all(self.ncols()).range(col, cols),
+        })
+    }
+
+    pub fn view_segment_mut(
+        &mut self,
+        row: usize,
+        col: usize,
+        rows: usize,
+        cols: usize,
+    ) -> MatrixViewMut<'_, T> {
+        unwrap(self.try_view_segment_mut(row, col, rows, cols))
+    }
+
+    /// View the `rows x cols` segment at `(row, col)` to change it
+    pub fn try_view_segment_mut(
+        &mut self,
+        row: usize,
+        col: usize,
+        rows: usize,
+        cols: usize,
+    ) -> Result<MatrixViewMut<'_, T>, MatrixError> {
+        self.shape()
+            .check_segment(row, col, Shape::new(rows, cols))?;
+        Ok(MatrixViewMut {
+            rows: Selection::all(self.nrows()).range(row, rows),
+            cols: Selection::all(self.ncols()).range(col, cols),
+            matrix: self,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The 4x5 matrix with entry `10 * row + col`
+    fn example() -> Matrix<i32> {
+        Matrix::from_function(4, 5, |col, row| (10 * row + col) as i32)
+    }
+
+    #[test]
+    fn vector_views() {
+        let v: Vector<i32> = (0..10).collect();
+        let view = v.view();
+        assert_eq!(view.len(), 10);
+        assert_eq!(view[3], 3);
+        assert_eq!(view.get(10), None);
+        let odd = view.clone().slice(1..10).step(2);
+        assert_eq!(odd.to_vector(), Vector::from_vec(vec![1, 3, 5, 7, 9]));
+        assert_eq!(odd.clone().step(2).to_vector().as_slice(), [1, 5, 9]);
+        let picked = odd.clone().select(&[4, 0, 2]);
+        assert_eq!(picked.to_vector().as_slice(), [9, 1, 5]);
+        assert_eq!(picked.clone().slice(1..3).to_vector().as_slice(), [1, 5]);
+        assert_eq!(picked.clone().step(2).to_vector().as_slice(), [9, 5]);
+        assert_eq!(odd.clone().slice(0..0).len(), 0);
+        assert_eq!(view.clone().slice(10..10).len(), 0);
+        assert!(view.clone().try_slice(5..11).is_err());
+        assert_eq!(
+            odd.try_select(&[1, 5]).unwrap_err(),
+            MatrixError::Selection { index: 5, len: 5 }
+        );
+        assert_eq!(view.clone().select(&[1, 3]), view.slice(1..4).step(2));
+    }
+
+    #[test]
+    fn weight_and_dot() {
+        let v = Vector::from_vec(vec![0, 3, 0, -1, 2]);
+        assert_eq!(v.weight(), 3);
+        assert_eq!(v.view().select(&[0, 2]).weight(), 0);
+        let w = Vector::from_vec(vec![1, 1, 1, 1, 1]);
+        assert_eq!(v.view().dot(&w), 4);
+        assert_eq!(v.view().step(2).dot(w.view().slice(0..3)), 2);
+        assert_eq!(
+            v.view().try_dot(w.view().slice(0..4)),
+            Err(MatrixError::Length { left: 5, right: 4 })
+        );
+    }
+
+    #[test]
+    fn matrix_views() {
+        let m = example();
+        let view = m.view();
+        assert_eq!(view.shape(), Shape::new(4, 5));
+        assert_eq!(*view.get(2, 3), 23);
+        assert_eq!(view.to_matrix(), m);
+        assert_eq!(
+            m.view_segment(1, 2, 2, 3).to_matrix(),
+            m.get_segment(1, 2, 2, 3)
+        );
+        assert_eq!(
+            view.clone().rows(1..3).columns(2..5),
+            m.view_segment(1, 2, 2, 3)
+        );
+
+        let information_set = [4, 0, 2];
+        let selected = view.clone().select_columns(&information_set);
+        assert_eq!(selected.shape(), Shape::new(4, 3));
+        assert_eq!(*selected.get(1, 0), 14);
+        assert_eq!(selected.column(2).to_vector().as_slice(), [2, 12, 22, 32]);
+        let narrowed = selected.step_rows(2).columns(1..3);
+        assert_eq!(narrowed.shape(), Shape::new(2, 2));
+        assert_eq!(*narrowed.get(1, 0), 20);
+        assert_eq!(*narrowed.get(1, 1), 22);
+
+        let strided = view.clone().step_columns(2).select_rows(&[3, 3]);
+        assert_eq!(strided.shape(), Shape::new(2, 3));
+        assert_eq!(strided.column(1).to_vector().as_slice(), [32, 32]);
+    }
+
+    #[test]
+    fn matrix_view_errors() {
+        let m = example();
+        assert_eq!(
+            m.try_view_segment(3, 0, 2, 1).unwrap_err(),
+            MatrixError::OutOfBounds {
+                position: (3, 0),
+                size: Shape::new(2, 1),
+                shape: Shape::new(4, 5),
+            }
+        );
+        assert!(m.view().try_rows(2..5).is_err());
+        assert!(m.view().try_columns(0..6).is_err());
+        assert!(m.view().try_columns(5..5).is_ok());
+        assert_eq!(
+            m.view()
+                .try_select_columns(&[0, 5])
+                .unwrap_err()
+                .to_string(),
+            "can not select entry 5 of 5"
+        );
+        assert!(m.view().select_rows(&[1]).try_select_rows(&[1]).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn view_get_out_of_bounds() {
+        let m = example();
+        m.view().rows(0..2).get(2, 0);
+    }
+
+    #[test]
+    fn products() {
+        let m = example();
+        let other: Matrix<i32> = Matrix::from_function(3, 2, |col, row| (row + col) as i32);
+        let segment = m.get_segment(0, 1, 2, 3);
+        let view = m.view_segment(0, 1, 2, 3);
+        assert_eq!(view.mul(&other), &segment * &other);
+        assert_eq!(
+            view.try_mul(m.view()).unwrap_err().to_string(),
+            "can not multiply a 2x3 and a 4x5 matrix"
+        );
+
+        let e = Vector::from_vec(vec![1, 0, 0, 1, 1]);
+        let syndrome = m.view().mul_vector(&e);
+        assert_eq!(syndrome.as_slice(), [7, 37, 67, 97]);
+        let picked = m.view().select_columns(&[0, 3, 4]);
+        assert_eq!(picked.mul_vector(e.view().select(&[0, 3, 4])), syndrome);
+        assert!(picked.try_mul_vector(&e).is_err());
+
+        let v = Vector::from_vec(vec![1, 0, 1, 0]);
+        assert_eq!(m.view().vector_mul(&v), &v * &m);
+        assert_eq!(
+            view.try_vector_mul(&v),
+            Err(MatrixError::Dimensions {
+                operation: "multiply",
+                left: Shape::row(4),
+                right: Shape::new(2, 3),
+            })
+        );
+    }
+
+    #[test]
+    fn mutable_views() {
+        let mut m = example();
+        m.view_segment_mut(1, 1, 2, 2).fill(0);
+        assert_eq!(m[1].as_slice(), [1, 0, 0, 31]);
+        assert_eq!(m[3].as_slice(), [3, 13, 23, 33]);
+
+        let block = Matrix::from_vec(vec![Vector::from_vec(vec![7, 8])]);
+        let mut view = m.view_mut().select_columns(&[4]).step_rows(2);
+        assert_eq!(view.view().to_matrix().shape(), Shape::new(2, 1));
+        view.assign(&block);
+        *view.get_mut(0, 0) += 1;
+        assert!(view.try_assign(&example()).is_err());
+        assert_eq!(m[4].as_slice(), [8, 14, 8, 34]);
+    }
+}
@@ -0,0 +1,265 @@
+//! Channel models that corrupt codewords, for decoding experiments.
+//!
+//! The hard-decision channels give the error pattern as a packed
+//! `BitVector`, which is added to a codeword and decoded through its
+//! syndrome. The erasure channel marks the positions it loses instead, and
+//! the AWGN channel gives the log-likelihood ratios of BPSK symbols as a
+//! `matrix::Vector<f64>`.
+
+use bit_vector::BitVector;
+use matrix::Vector;
+use rand::distributions::{IndependentSample, Normal};
+use rand::{seq, Rng};
+
+/// A channel that flips bits
+pub trait ErrorChannel {
+    /// The positions this channel flips in a word of `length` bits
+    fn error<R: Rng>(&self, length: usize, rng: &mut R) -> BitVector;
+
+    /// Send a word through the channel
+    fn transmit<R: Rng>(&self, word: &BitVector, rng: &mut R) -> BitVector {
+        word ^ &self.error(word.len(), rng)
+    }
+}
+
+/// Errors of exactly `weight` uniformly random positions, as in McEliece
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FixedWeight {
+    pub weight: usize,
+}
+
+impl ErrorChannel for FixedWeight {
+    fn error<R: Rng>(&self, length: usize, rng: &mut R) -> BitVector {
+        assert!(self.weight <= length, "Weight can not exceed the length");
+        BitVector::from_support(length, &seq::sample_indices(rng, length, self.weight))
+    }
+}
+
+/// The binary symmetric channel: every bit flips independently with
+/// probability `crossover`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BinarySymmetric {
+    pub crossover: f64,
+}
+
+impl ErrorChannel for BinarySymmetric {
+    fn error<R: Rng>(&self, length: usize, rng: &mut R) -> BitVector {
+        assert!(
+            (0.0..=1.0).contains(&self.crossover),
+            "The crossover probability should be between 0 and 1"
+        );
+        let bits: Vec<bool> = (0..length)
+            .map(|_| rng.next_f64() < self.crossover)
+            .collect();
+        BitVector::from_bools(&bits)
+    }
+}
+
+/// A single burst of errors within `span` consecutive positions, starting
+/// anywhere: the first and last position of the span flip, and the ones in
+/// between each flip with probability one half
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Burst {
+    pub span: usize,
+}
+
+impl ErrorChannel for Burst {
+    fn error<R: Rng>(&self, length: usize, rng: &mut R) -> BitVector {
+        assert!(
+            0 < self.span && self.span <= length,
+            "The burst should fit in the word"
+        );
+        let start = rng.gen_range(0, length - self.span + 1);
+        let end = start + self.span - 1;
+        let mut error = BitVector::zero(length);
+        error.set(start, true);
+        error.set(end, true);
+        for i in start + 1..end {
+            error.set(i, rng.gen());
+        }
+        error
+    }
+}
+
+/// The binary erasure channel: every bit is lost independently with
+/// probability `probability`, and the receiver knows which ones
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BinaryErasure {
+    pub probability: f64,
+}
+
+impl BinaryErasure {
+    /// The positions lost from a word of `length` bits
+    pub fn erasures<R: Rng>(&self, length: usize, rng: &mut R) -> BitVector {
+        BinarySymmetric {
+            crossover: self.probability,
+        }
+        .error(length, rng)
+    }
+
+    /// Send a word through the channel: `None` marks an erasure
+    pub fn transmit<R: Rng>(&self, word: &BitVector, rng: &mut R) -> Vector<Option<bool>> {
+        let erasures = self.erasures(word.len(), rng);
+        (0..word.len())
+            .map(|i| {
+                if erasures.get(i) {
+                    None
+                } else {
+                    Some(word.get(i))
+                }
+            })
+            .collect()
+    }
+}
+
+/// BPSK over the additive white Gaussian noise channel: bit `b` is sent as
+/// `1 - 2b` and received with noise of standard deviation `sigma`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Awgn {
+    pub sigma: f64,
+}
+
+impl Awgn {
+    /// The channel at signal-to-noise ratio `Eb/N0`, in decibels, for a
+    /// code of the given rate
+    pub fn from_ebn0(ebn0_db: f64, rate: f64) -> Awgn {
+        let ebn0 = 10f64.powf(ebn0_db / 10.0);
+        Awgn {
+            sigma: (1.0 / (2.0 * rate * ebn0)).sqrt(),
+        }
+    }
+
+    /// The received symbols for a word
+    pub fn symbols<R: Rng>(&self, word: &BitVector, rng: &mut R) -> Vector<f64> {
+        let noise = Normal::new(0.0, self.sigma);
+        word.iter()
+            .map(|bit| if bit { -1.0 } else { 1.0 } + noise.ind_sample(rng))
+            .collect()
+    }
+
+    /// Send a word through the channel, giving the log-likelihood ratio
+    /// `log P(0 | y) / P(1 | y) = 2 y / sigma^2` of every received symbol
+    pub fn transmit<R: Rng>(&self, word: &BitVector, rng: &mut R) -> Vector<f64> {
+        let scale = 2.0 / (self.sigma * self.sigma);
+        self.symbols(word, rng)
+            .into_iter()
+            .map(|y| scale * y)
+            .collect()
+    }
+}
+
+/// Hard decisions on the AWGN channel: a bit is in error when the noise
+/// pushes its symbol across zero
+impl ErrorChannel for Awgn {
+    fn error<R: Rng>(&self, length: usize, rng: &mut R) -> BitVector {
+        hard_decision(&self.transmit(&BitVector::zero(length), rng))
+    }
+}
+
+/// The most likely bits for the given log-likelihood ratios
+pub fn hard_decision(llrs: &Vector<f64>) -> BitVector {
+    let bits: Vec<bool> = llrs.iter().map(|&llr| llr < 0.0).collect();
+    BitVector::from_bools(&bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linear_code::tests::hamming;
+    use linear_code::{Decoder, Exhaustive};
+    use rand::{SeedableRng, XorShiftRng};
+
+    fn rng() -> XorShiftRng {
+        XorShiftRng::from_seed([1, 2, 3, 4])
+    }
+
+    #[test]
+    fn fixed_weight() {
+        let channel = FixedWeight { weight: 5 };
+        let error = channel.error(40, &mut rng());
+        assert_eq!(error.len(), 40);
+        assert_eq!(error.weight(), 5);
+        assert_eq!(channel.error(40, &mut rng()), error);
+
+        // one error is corrected by a code of minimum distance 3
+        let code = hamming();
+        assert_eq!(code.minimum_distance(), Some(3));
+        let codeword = code.encode(&BitVector::from_support(4, &[0, 2]));
+        let received = FixedWeight { weight: 1 }.transmit(&codeword, &mut rng());
+        assert_eq!((&received ^ &codeword).weight(), 1);
+        let syndrome = code.syndrome(&received);
+        let decoded = Exhaustive { max_weight: 1 }.decode(&code, &syndrome, &mut rng());
+        assert!(code.is_codeword(&(&received ^ &decoded.unwrap())));
+    }
+
+    #[test]
+    fn binary_symmetric() {
+        let mut rng = rng();
+        assert!(BinarySymmetric { crossover: 0.0 }
+            .error(100, &mut rng)
+            .is_zero());
+        assert_eq!(
+            BinarySymmetric { crossover: 1.0 }
+                .error(100, &mut rng)
+                .weight(),
+            100
+        );
+        let weight = BinarySymmetric { crossover: 0.1 }
+            .error(10_000, &mut rng)
+            .weight();
+        assert!(900 < weight && weight < 1100, "weight {}", weight);
+    }
+
+    #[test]
+    #[should_panic]
+    fn crossover_out_of_range() {
+        BinarySymmetric { crossover: 1.5 }.error(10, &mut rng());
+    }
+
+    #[test]
+    fn burst() {
+        let mut rng = rng();
+        for _ in 0..100 {
+            let error = Burst { span: 6 }.error(20, &mut rng);
+            let support: Vec<usize> = error.support().collect();
+            assert_eq!(support.last().unwrap() - support[0], 5);
+        }
+        assert_eq!(Burst { span: 1 }.error(20, &mut rng).weight(), 1);
+        assert!(Burst { span: 20 }.error(20, &mut rng).get(19));
+    }
+
+    #[test]
+    fn erasures() {
+        let mut rng = rng();
+        let word = BitVector::from_support(1000, &[1, 3, 500]);
+        let received = BinaryErasure { probability: 0.2 }.transmit(&word, &mut rng);
+        let erased = received.iter().filter(|bit| bit.is_none()).count();
+        assert!(150 < erased && erased < 250, "erased {}", erased);
+        for (i, bit) in received.iter().enumerate() {
+            assert!(bit.is_none_or(|bit| bit == word.get(i)));
+        }
+        assert!(BinaryErasure { probability: 0.0 }
+            .erasures(100, &mut rng)
+            .is_zero());
+    }
+
+    #[test]
+    fn awgn() {
+        let channel = Awgn::from_ebn0(3.0, 0.5);
+        assert!((channel.sigma - 0.708).abs() < 1e-3);
+
+        let mut rng = rng();
+        let word = BitVector::from_support(8, &[0, 5, 6]);
+        let quiet = Awgn { sigma: 0.01 };
+        let llrs = quiet.transmit(&word, &mut rng);
+        assert_eq!(hard_decision(&llrs), word);
+        assert!((llrs[1] - 2.0 / 0.0001).abs() < 1000.0);
+
+        // with sigma = 1 a symbol is flipped with probability Q(1) ~ 0.159
+        let weight = Awgn { sigma: 1.0 }.error(10_000, &mut rng).weight();
+        assert!(1450 < weight && weight < 1730, "weight {}", weight);
+        let symbols = Awgn { sigma: 1.0 }.symbols(&BitVector::zero(10_000), &mut rng);
+        let mean = symbols.iter().sum::<f64>() / 10_000.0;
+        assert!((mean - 1.0).abs() < 0.05);
+    }
+}
@@ -0,0 +1,206 @@
+//! Constant-time arithmetic for handling secret data.
+//!
+//! `Bit<T>`, `Vector<T>`, `Matrix<T>` and the table-based `gf2m::Field` make
+//! no timing guarantees. The functions in this module avoid branches and
+//! memory accesses that depend on their (secret) inputs: conditions are
+//! turned into masks that are either all zeros or all ones. Loop bounds and
+//! indices only depend on public sizes.
+
+use gf2m::{Element, Field};
+use std::hint::black_box;
+
+/// All ones if `bit` is one, all zeros if it is zero.
+///
+/// The mask passes through an optimisation barrier: without it the compiler
+/// is free to turn `x & mask` back into a branch on `bit`, and does.
+#[inline]
+pub fn mask(bit: u16) -> u16 {
+    black_box(0u16.wrapping_sub(bit & 1))
+}
+
+#[inline]
+fn mask32(bit: u32) -> u32 {
+    black_box(0u32.wrapping_sub(bit & 1))
+}
+
+/// All ones if `a` is zero
+#[inline]
+pub fn zero_mask(a: u16) -> u16 {
+    // a - 1 wraps around, setting the top bit, only if a == 0
+    mask(((u32::from(a).wrapping_sub(1)) >> 31) as u16)
+}
+
+/// All ones if `a >= b`, for `a, b < 2^31`
+#[inline]
+pub fn ge_mask(a: usize, b: usize) -> u16 {
+    mask(((a as u32).wrapping_sub(b as u32) >> 31) as u16 ^ 1)
+}
+
+/// Pick `a` if `mask` is all ones and `b` if it is all zeros
+#[inline]
+pub fn select(mask: u16, a: u16, b: u16) -> u16 {
+    (a & mask) | (b & !mask)
+}
+
+/// Arithmetic in GF(2^m) by shifts and masks instead of table lookups
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConstantTimeField {
+    degree: u32,
+    modulus: u32,
+}
+
+impl ConstantTimeField {
+    pub fn new(field: &Field) -> ConstantTimeField {
+        ConstantTimeField {
+            degree: field.degree(),
+            modulus: field.modulus(),
+        }
+    }
+
+    pub fn mul(&self, a: Element, b: Element) -> Element {
+        let a = u32::from(a);
+        let b = u32::from(b);
+        let mut result = 0u32;
+        for i in 0..self.degree {
+            result ^= (a << i) & mask32(b >> i);
+        }
+        for i in (self.degree..2 * self.degree - 1).rev() {
+            result ^= (self.modulus << (i - self.degree)) & mask32(result >> i);
+        }
+        result as Element
+    }
+
+    #[inline]
+    pub fn square(&self, a: Element) -> Element {
+        self.mul(a, a)
+    }
+
+    /// `a^(2^m - 2)`, which is the inverse of `a` for non-zero `a`, and zero
+    /// for zero
+    pub fn inverse(&self, a: Element) -> Element {
+        let mut result = a;
+        for _ in 1..self.degree - 1 {
+            result = self.mul(self.square(result), a);
+        }
+        self.square(result)
+    }
+
+    /// Evaluate a polynomial, given by all its coefficients, at `x`
+    pub fn eval(&self, coefficients: &[Element], x: Element) -> Element {
+        coefficients
+            .iter()
+            .rev()
+            .fold(0, |acc, &c| self.mul(acc, x) ^ c)
+    }
+
+    /// Berlekamp-Massey with a fixed number of operations.
+    ///
+    /// Returns the `t + 1` coefficients of `x^t C(1/x)`, where `C` is the
+    /// connection polynomial of the shortest register generating the `2t`
+    /// values in `sequence`. Its roots are the error locations, plus zero
+    /// if the register is shorter than `t`.
+    pub fn berlekamp_massey(&self, sequence: &[Element]) -> Vec<Element> {
+        let t = sequence.len() / 2;
+        assert!(t > 0, "There should be at least two values");
+        let mut connection = vec![0; t + 1];
+        let mut previous = vec![0; t + 1];
+        connection[0] = 1;
+        previous[1] = 1;
+        let mut length = 0u16;
+        let mut previous_discrepancy = 1;
+        for n in 0..2 * t {
+            let mut discrepancy = 0;
+            for i in 0..=n.min(t) {
+                discrepancy ^= self.mul(connection[i], sequence[n - i]);
+            }
+            let nonzero = !zero_mask(discrepancy);
+            let lengthen = nonzero & ge_mask(n, 2 * length as usize);
+
+            let factor = self.mul(discrepancy, self.inverse(previous_discrepancy));
+            let old = connection.clone();
+            for i in 0..=t {
+                connection[i] ^= self.mul(factor, previous[i]) & nonzero;
+            }
+            length = select(lengthen, (n as u16 + 1).wrapping_sub(length), length);
+            for i in 0..=t {
+                previous[i] = select(lengthen, old[i], previous[i]);
+            }
+            previous_discrepancy = select(lengthen, discrepancy, previous_discrepancy);
+            // multiply the previous polynomial by x
+            for i in (1..=t).rev() {
+                previous[i] = previous[i - 1];
+            }
+            previous[0] = 0;
+        }
+        connection.reverse();
+        connection
+    }
+
+    /// Evaluate the locator at every support element: entry `j` of the
+    /// result is one if `support[j]` is a root and zero otherwise
+    pub fn roots(&self, locator: &[Element], support: &[Element]) -> Vec<u16> {
+        support
+            .iter()
+            .map(|&alpha| zero_mask(self.eval(locator, alpha)) & 1)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks() {
+        assert_eq!(mask(1), 0xffff);
+        assert_eq!(mask(0), 0);
+        assert_eq!(zero_mask(0), 0xffff);
+        assert_eq!(zero_mask(1), 0);
+        assert_eq!(zero_mask(0x7fff), 0);
+        assert_eq!(ge_mask(3, 3), 0xffff);
+        assert_eq!(ge_mask(4, 3), 0xffff);
+        assert_eq!(ge_mask(2, 3), 0);
+        assert_eq!(select(0xffff, 5, 7), 5);
+        assert_eq!(select(0, 5, 7), 7);
+    }
+
+    #[test]
+    fn matches_table_arithmetic() {
+        let field = Field::new(8, 0x11B);
+        let ct = ConstantTimeField::new(&field);
+        for a in field.elements() {
+            for b in field.elements().step_by(3) {
+                assert_eq!(ct.mul(a, b), field.mul(a, b));
+            }
+            if a != 0 {
+                assert_eq!(ct.inverse(a), field.inverse(a));
+            }
+        }
+        assert_eq!(ct.inverse(0), 0);
+
+        let field = Field::new(13, 0x201B);
+        let ct = ConstantTimeField::new(&field);
+        for a in field.elements().step_by(97).skip(1) {
+            assert_eq!(ct.mul(a, 0x1234), field.mul(a, 0x1234));
+            assert_eq!(ct.inverse(a), field.inverse(a));
+        }
+    }
+
+    #[test]
+    fn locate_errors() {
+        let field = Field::new(4, 0x13);
+        let ct = ConstantTimeField::new(&field);
+        // power sums of the error locations 3 and 6 with unit values
+        let locations = [3, 6];
+        let sequence: Vec<Element> = (0..4)
+            .map(|i| locations.iter().fold(0, |acc, &x| acc ^ field.pow(x, i)))
+            .collect();
+        let locator = ct.berlekamp_massey(&sequence);
+        assert_eq!(locator.len(), 3);
+        let support: Vec<Element> = (0..16).collect();
+        let roots = ct.roots(&locator, &support);
+        for (j, &root) in roots.iter().enumerate() {
+            assert_eq!(root == 1, j == 3 || j == 6);
+        }
+    }
+}
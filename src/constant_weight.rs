@@ -0,0 +1,287 @@
+//! Encoding messages as constant-weight words.
+//!
+//! The weight-`t` words of length `n` are numbered by the combinatorial
+//! number system: the word with support `c_1 < ... < c_t` has rank
+//! `C(c_1, 1) + C(c_2, 2) + ... + C(c_t, t)`. A message of
+//! `floor(log2 C(n, t))` bits is read as a rank and mapped to its word.
+
+use bit_vector::BitVector;
+use std::cmp::Ordering;
+
+/// A bijection between messages and part of the words of weight `t`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConstantWeight {
+    length: usize,
+    weight: usize,
+    capacity: usize,
+}
+
+impl ConstantWeight {
+    pub fn new(length: usize, weight: usize) -> ConstantWeight {
+        assert!(weight <= length, "Weight can not exceed the length");
+        let capacity = binomial(length, weight).bits() - 1;
+        ConstantWeight {
+            length,
+            weight,
+            capacity,
+        }
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    /// The number of message bits that fit in a word
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Map a message of `capacity` bits to a word of weight `t`
+    pub fn encode(&self, message: &BitVector) -> BitVector {
+        assert_eq!(
+            message.len(),
+            self.capacity,
+            "Message should have the capacity as length"
+        );
+        let mut word = BitVector::zero(self.length);
+        if self.weight == 0 {
+            return word;
+        }
+        let mut rank = Natural::from_words(message.words());
+        let mut k = self.weight;
+        let mut c = self.length - 1;
+        // invariant: current = C(c, k)
+        let mut current = binomial(self.length, k)
+            .mul_small((self.length - k) as u64)
+            .div_small(self.length as u64);
+        loop {
+            if current <= rank {
+                word.set(c, true);
+                rank.sub_assign(&current);
+                if k == 1 {
+                    return word;
+                }
+                current = current.mul_small(k as u64).div_small(c as u64);
+                k -= 1;
+            } else {
+                current = current.mul_small((c - k) as u64).div_small(c as u64);
+            }
+            c -= 1;
+        }
+    }
+
+    /// Map a word of weight `t` back to its message.
+    ///
+    /// Returns `None` for words that are not the encoding of any message.
+    pub fn decode(&self, word: &BitVector) -> Option<BitVector> {
+        assert_eq!(
+            word.len(),
+            self.length,
+            "Word should match the encoder length"
+        );
+        if word.weight() != self.weight {
+            return None;
+        }
+        let mut rank = Natural::zero();
+        for (i, c) in word.support().enumerate() {
+            rank = rank.add(&binomial(c, i + 1));
+        }
+        if rank.bits() > self.capacity {
+            return None;
+        }
+        let mut message = BitVector::zero(self.capacity);
+        for (i, &w) in rank.words.iter().enumerate() {
+            for bit in 0..64 {
+                if (w >> bit) & 1 == 1 {
+                    message.set(64 * i + bit, true);
+                }
+            }
+        }
+        Some(message)
+    }
+}
+
+fn binomial(n: usize, k: usize) -> Natural {
+    if k > n {
+        return Natural::zero();
+    }
+    (0..k).fold(Natural::from_words(&[1]), |acc, i| {
+        acc.mul_small((n - i) as u64).div_small((i + 1) as u64)
+    })
+}
+
+/// Just enough arbitrary-precision arithmetic for ranking: little-endian
+/// words without trailing zeros
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Natural {
+    words: Vec<u64>,
+}
+
+impl Natural {
+    fn zero() -> Natural {
+        Natural { words: Vec::new() }
+    }
+
+    fn from_words(words: &[u64]) -> Natural {
+        let mut words = words.to_vec();
+        while words.last() == Some(&0) {
+            words.pop();
+        }
+        Natural { words }
+    }
+
+    /// The number of bits needed to write this number
+    fn bits(&self) -> usize {
+        match self.words.last() {
+            None => 0,
+            Some(w) => 64 * self.words.len() - w.leading_zeros() as usize,
+        }
+    }
+
+    fn add(&self, other: &Natural) -> Natural {
+        let len = self.words.len().max(other.words.len());
+        let mut words = Vec::with_capacity(len + 1);
+        let mut carry = 0u128;
+        for i in 0..len {
+            let sum = u128::from(self.words.get(i).cloned().unwrap_or(0))
+                + u128::from(other.words.get(i).cloned().unwrap_or(0))
+                + carry;
+            words.push(sum as u64);
+            carry = sum >> 64;
+        }
+        words.push(carry as u64);
+        Natural::from_words(&words)
+    }
+
+    /// Subtract a number that is at most `self`
+    fn sub_assign(&mut self, other: &Natural) {
+        assert!(*other <= *self, "Subtraction would underflow");
+        let mut borrow = 0u64;
+        for i in 0..self.words.len() {
+            let (a, b1) = self.words[i].overflowing_sub(other.words.get(i).cloned().unwrap_or(0));
+            let (a, b2) = a.overflowing_sub(borrow);
+            self.words[i] = a;
+            borrow = (b1 || b2) as u64;
+        }
+        *self = Natural::from_words(&self.words);
+    }
+
+    fn mul_small(&self, factor: u64) -> Natural {
+        let mut words = Vec::with_capacity(self.words.len() + 1);
+        let mut carry = 0u128;
+        for &w in &self.words {
+            let product = u128::from(w) * u128::from(factor) + carry;
+            words.push(product as u64);
+            carry = product >> 64;
+        }
+        words.push(carry as u64);
+        Natural::from_words(&words)
+    }
+
+    /// Divide, rounding down
+    fn div_small(&self, divisor: u64) -> Natural {
+        let mut words = vec![0; self.words.len()];
+        let mut remainder = 0u128;
+        for i in (0..self.words.len()).rev() {
+            let current = (remainder << 64) | u128::from(self.words[i]);
+            words[i] = (current / u128::from(divisor)) as u64;
+            remainder = current % u128::from(divisor);
+        }
+        Natural::from_words(&words)
+    }
+}
+
+impl PartialOrd for Natural {
+    fn partial_cmp(&self, other: &Natural) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Natural {
+    fn cmp(&self, other: &Natural) -> Ordering {
+        self.words
+            .len()
+            .cmp(&other.words.len())
+            .then_with(|| self.words.iter().rev().cmp(other.words.iter().rev()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng, XorShiftRng};
+
+    #[test]
+    fn binomials() {
+        assert_eq!(binomial(5, 2), Natural::from_words(&[10]));
+        assert_eq!(binomial(3, 5), Natural::zero());
+        assert_eq!(
+            binomial(64, 32),
+            Natural::from_words(&[1_832_624_140_942_590_534])
+        );
+        // C(100, 50) is about 2^96.3
+        assert_eq!(binomial(100, 50).bits(), 97);
+    }
+
+    #[test]
+    fn natural_arithmetic() {
+        let a = Natural::from_words(&[u64::MAX, 1]);
+        let b = a.mul_small(3).div_small(3);
+        assert_eq!(a, b);
+        let mut c = a.add(&Natural::from_words(&[1]));
+        assert_eq!(c, Natural::from_words(&[0, 2]));
+        c.sub_assign(&Natural::from_words(&[1]));
+        assert_eq!(c, a);
+        assert!(Natural::from_words(&[5]) < a);
+    }
+
+    #[test]
+    fn capacity() {
+        assert_eq!(ConstantWeight::new(5, 2).capacity(), 3);
+        assert_eq!(ConstantWeight::new(5, 0).capacity(), 0);
+        // log2 C(3488, 64) is about 456.3
+        assert_eq!(ConstantWeight::new(3488, 64).capacity(), 456);
+    }
+
+    #[test]
+    fn exhaustive_small() {
+        let encoder = ConstantWeight::new(6, 3);
+        assert_eq!(encoder.capacity(), 4);
+        let mut words = Vec::new();
+        for m in 0..16 {
+            let message = BitVector::from_bools(&[m & 1 == 1, m & 2 == 2, m & 4 == 4, m & 8 == 8]);
+            let word = encoder.encode(&message);
+            assert_eq!(word.weight(), 3);
+            assert_eq!(encoder.decode(&word), Some(message));
+            words.push(word);
+        }
+        words.sort_by_key(|w| w.words().to_vec());
+        words.dedup();
+        assert_eq!(words.len(), 16);
+        // rank C(3, 1) + C(4, 2) + C(5, 3) = 19 does not fit in four bits
+        assert_eq!(
+            encoder.decode(&BitVector::from_support(6, &[3, 4, 5])),
+            None
+        );
+        assert_eq!(encoder.decode(&BitVector::from_support(6, &[0, 1])), None);
+    }
+
+    #[test]
+    fn roundtrip_large() {
+        let encoder = ConstantWeight::new(3488, 64);
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        for _ in 0..5 {
+            let bits: Vec<bool> = (0..encoder.capacity()).map(|_| rng.gen()).collect();
+            let message = BitVector::from_bools(&bits);
+            let word = encoder.encode(&message);
+            assert_eq!(word.weight(), 64);
+            assert_eq!(encoder.decode(&word), Some(message));
+        }
+        let ones = BitVector::from_bools(&vec![true; encoder.capacity()]);
+        assert_eq!(encoder.decode(&encoder.encode(&ones)), Some(ones));
+    }
+}
@@ -0,0 +1,215 @@
+//! New codes from old ones.
+//!
+//! Each construction builds a generator matrix from those of the codes it
+//! starts from, and leaves the parity-check matrix to `LinearCode`. The
+//! dual code is `LinearCode::dual`.
+
+use bit_vector::BitVector;
+use linear_code::LinearCode;
+use matrix::BitMatrix;
+
+impl LinearCode {
+    /// Delete the coordinates at `positions` from every codeword.
+    ///
+    /// The dimension drops when a non-zero codeword lies within the
+    /// positions, which it does not if there are fewer than `d`.
+    pub fn puncture(&self, positions: &[usize]) -> LinearCode {
+        let kept = complement(self.length(), positions);
+        spanned_by(self.generator_matrix().select_columns(&kept))
+    }
+
+    /// Keep the codewords that are zero at `positions`, and delete those
+    /// coordinates: the dual of puncturing the dual
+    pub fn shorten(&self, positions: &[usize]) -> LinearCode {
+        let kept = complement(self.length(), positions);
+        LinearCode::from_parity_check(self.parity_check_matrix().select_columns(&kept))
+    }
+
+    /// Append a parity bit to every codeword, which makes all weights even
+    /// and an odd minimum distance one larger
+    pub fn extend(&self) -> LinearCode {
+        let g = self.generator_matrix();
+        let parity = g
+            .rows()
+            .iter()
+            .map(|row| BitVector::from_bools(&[row.weight() % 2 == 1]))
+            .collect();
+        let mut extended = g.clone();
+        extended.augment(BitMatrix::from_rows(1, parity));
+        LinearCode::from_generator(extended)
+    }
+
+    /// Plotkin's `(u | u + v)` construction, for `u` in this code and `v` in
+    /// `other` of the same length, with minimum distance `min(2 d_u, d_v)`
+    pub fn plotkin(&self, other: &LinearCode) -> LinearCode {
+        assert_eq!(
+            self.length(),
+            other.length(),
+            "The codes should have the same length"
+        );
+        let (g_u, g_v) = (self.generator_matrix(), other.generator_matrix());
+        LinearCode::from_generator(BitMatrix::from_blocks(vec![
+            vec![g_u.clone(), g_u.clone()],
+            vec![
+                BitMatrix::zero(other.dimension(), self.length()),
+                g_v.clone(),
+            ],
+        ]))
+    }
+
+    /// The codewords `(a | b)` for `a` in this code and `b` in `other`
+    pub fn direct_sum(&self, other: &LinearCode) -> LinearCode {
+        LinearCode::from_generator(BitMatrix::block_diagonal(vec![
+            self.generator_matrix().clone(),
+            other.generator_matrix().clone(),
+        ]))
+    }
+
+    /// The product code, of `n x n'` arrays read row by row whose columns
+    /// are in this code and whose rows are in `other`. Its generator matrix
+    /// is the tensor product of theirs, and its minimum distance `d d'`.
+    pub fn product(&self, other: &LinearCode) -> LinearCode {
+        LinearCode::from_generator(self.generator_matrix().kronecker(other.generator_matrix()))
+    }
+}
+
+/// The coordinates below `n` that are not in `positions`
+fn complement(n: usize, positions: &[usize]) -> Vec<usize> {
+    let mut removed = vec![false; n];
+    for &position in positions {
+        assert!(position < n, "Position {} is out of range", position);
+        removed[position] = true;
+    }
+    (0..n).filter(|&i| !removed[i]).collect()
+}
+
+/// The code spanned by the rows of `g`, which may be dependent
+fn spanned_by(mut g: BitMatrix) -> LinearCode {
+    let rank = g.reduce_row_echelon().len();
+    let rows = g.rows()[..rank].to_vec();
+    LinearCode::from_generator(BitMatrix::from_rows(g.ncols(), rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linear_code::tests::hamming;
+    use rand::{SeedableRng, XorShiftRng};
+
+    fn rng() -> XorShiftRng {
+        XorShiftRng::from_seed([1, 2, 3, 4])
+    }
+
+    fn repetition(n: usize) -> LinearCode {
+        let ones: Vec<usize> = (0..n).collect();
+        LinearCode::from_generator(BitMatrix::from_rows(
+            n,
+            vec![BitVector::from_support(n, &ones)],
+        ))
+    }
+
+    #[test]
+    fn dual() {
+        let code = hamming();
+        let simplex = code.dual();
+        assert_eq!(simplex.dimension(), 3);
+        assert_eq!(simplex.weight_distribution(), [1, 0, 0, 0, 7, 0, 0, 0]);
+        assert_eq!(simplex.dual(), code);
+
+        // dependent parity checks are no generator matrix
+        let mut h = code.parity_check_matrix().clone();
+        h.stack(BitMatrix::zero(1, 7));
+        let code = LinearCode::from_parity_check(h);
+        assert_eq!(code.dual().dimension(), 3);
+        for row in code.dual().generator_matrix().rows() {
+            assert!(code.generator_matrix().mul_vector(row).is_zero());
+        }
+    }
+
+    #[test]
+    fn puncture_and_shorten() {
+        let code = hamming();
+        let punctured = code.puncture(&[0]);
+        assert_eq!((punctured.length(), punctured.dimension()), (6, 4));
+        assert_eq!(punctured.minimum_distance(), Some(2));
+        let shortened = code.shorten(&[0]);
+        assert_eq!((shortened.length(), shortened.dimension()), (6, 3));
+        assert_eq!(shortened.minimum_distance(), Some(3));
+        for row in shortened.generator_matrix().rows() {
+            let mut word = BitVector::zero(1);
+            word.extend_from(row);
+            assert!(code.is_codeword(&word));
+        }
+
+        // columns 1, 2 and 3 of H add up to zero, so there is a codeword
+        // with support {0, 1, 2}
+        assert_eq!(code.puncture(&[0, 1, 2]).dimension(), 3);
+        assert_eq!(code.shorten(&[2, 0, 1]).dimension(), 1);
+
+        let code = LinearCode::random(14, 6, &mut rng());
+        let positions = [3, 7, 11];
+        assert_eq!(
+            code.shorten(&positions).weight_distribution(),
+            code.dual()
+                .puncture(&positions)
+                .dual()
+                .weight_distribution()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn puncture_out_of_range() {
+        hamming().puncture(&[7]);
+    }
+
+    #[test]
+    fn extend() {
+        let extended = hamming().extend();
+        assert_eq!((extended.length(), extended.dimension()), (8, 4));
+        assert_eq!(extended.weight_distribution(), [1, 0, 0, 0, 14, 0, 0, 0, 1]);
+        // the extended Hamming code is self-dual
+        assert_eq!(
+            extended.dual().weight_distribution(),
+            extended.weight_distribution()
+        );
+    }
+
+    #[test]
+    fn plotkin() {
+        // the first-order Reed-Muller code RM(1, 3)
+        let even = repetition(4).dual();
+        let code = even.plotkin(&repetition(4));
+        assert_eq!((code.length(), code.dimension()), (8, 4));
+        assert_eq!(code.weight_distribution(), [1, 0, 0, 0, 14, 0, 0, 0, 1]);
+        assert_eq!(repetition(4).plotkin(&even).minimum_distance(), Some(2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn plotkin_lengths() {
+        hamming().plotkin(&repetition(4));
+    }
+
+    #[test]
+    fn direct_sum() {
+        let code = hamming().direct_sum(&repetition(3));
+        assert_eq!((code.length(), code.dimension()), (10, 5));
+        assert_eq!(code.minimum_distance(), Some(3));
+        let word = BitVector::from_support(10, &[0, 1, 2, 7, 8, 9]);
+        assert!(code.is_codeword(&word));
+    }
+
+    #[test]
+    fn product() {
+        let even = repetition(3).dual();
+        let code = even.product(&hamming());
+        assert_eq!((code.length(), code.dimension()), (21, 8));
+        assert_eq!(code.minimum_distance(), Some(6));
+        // the array with a weight-3 Hamming codeword in rows 0 and 2
+        let word = BitVector::from_support(21, &[0, 1, 2, 14, 15, 16]);
+        assert!(code.is_codeword(&word));
+        let word = BitVector::from_support(21, &[0, 1, 2]);
+        assert!(!code.is_codeword(&word));
+    }
+}
@@ -0,0 +1,245 @@
+//! The minimum distance of binary linear codes.
+//!
+//! The exact distance is computed with the Brouwer–Zimmermann algorithm:
+//! the generator matrix is brought in systematic form on several disjoint
+//! information sets, and the sums of `w` rows of each form are enumerated
+//! for `w = 1, 2, ...`. A codeword that is not among them has weight more
+//! than `w` on each information set, which gives a lower bound that meets
+//! the lowest weight found long before all `2^k` codewords are seen.
+//!
+//! For codes too large for that, Stern's algorithm searches for low-weight
+//! codewords, which bounds the distance from above.
+
+use bit_vector::BitVector;
+use linear_code::{next_combination, LinearCode};
+use matrix::{BitMatrix, Permutation};
+use rand::Rng;
+use std::collections::HashMap;
+
+/// The minimum distance of the code spanned by the rows of `generator`,
+/// which should be independent, or `None` if there are none
+pub(crate) fn brouwer_zimmermann(generator: &BitMatrix) -> Option<usize> {
+    let (k, n) = (generator.nrows(), generator.ncols());
+    if k == 0 {
+        return None;
+    }
+
+    // reduced generator matrices, with the rank of each on its information set
+    let mut matrices: Vec<(BitMatrix, usize)> = Vec::new();
+    let mut remaining: Vec<usize> = (0..n).collect();
+    while !remaining.is_empty() {
+        let mut is_remaining = vec![false; n];
+        for &col in &remaining {
+            is_remaining[col] = true;
+        }
+        let order: Vec<usize> = remaining
+            .iter()
+            .cloned()
+            .chain((0..n).filter(|&col| !is_remaining[col]))
+            .collect();
+        let mut g = generator.clone();
+        g.permute_columns(&Permutation::from_vec(order.clone()));
+        let pivots = g.reduce_row_echelon();
+        let used: Vec<usize> = pivots
+            .iter()
+            .filter(|&&col| col < remaining.len())
+            .map(|&col| order[col])
+            .collect();
+        if used.is_empty() {
+            break;
+        }
+        remaining.retain(|col| !used.contains(col));
+        matrices.push((g, used.len()));
+    }
+
+    let mut best = n;
+    for w in 1..=k {
+        for (j, (g, _)) in matrices.iter().enumerate() {
+            let mut rows: Vec<usize> = (0..w).collect();
+            loop {
+                let mut sum = BitVector::zero(n);
+                for &row in &rows {
+                    sum ^= g.row(row);
+                }
+                best = best.min(sum.weight());
+                if !next_combination(&mut rows, k) {
+                    break;
+                }
+            }
+
+            // a codeword not seen yet comes from at least w + 1 rows of the
+            // matrices enumerated so far, and at least w of the others
+            let bound: usize = matrices
+                .iter()
+                .enumerate()
+                .map(|(i, &(_, rank))| {
+                    let rows = if i <= j { w + 1 } else { w };
+                    rows.saturating_sub(k - rank)
+                })
+                .sum();
+            if best <= bound {
+                return Some(best);
+            }
+        }
+    }
+    Some(best)
+}
+
+/// Stern's search for low-weight codewords.
+///
+/// Every iteration brings the generator matrix in systematic form on a
+/// random information set and splits its rows in two halves. The sums of
+/// `p` rows of either half that agree on `l` of the other positions are
+/// added together, which finds the codewords with weight `p` on both halves
+/// of the information set and weight zero on those `l` positions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stern {
+    pub iterations: usize,
+    pub p: usize,
+    pub l: usize,
+}
+
+impl Stern {
+    /// The codeword of lowest weight found, or `None` for the zero code
+    pub fn search<R: Rng>(&self, code: &LinearCode, rng: &mut R) -> Option<BitVector> {
+        assert!(self.p > 0, "Stern's algorithm adds at least one row");
+        assert!(self.l <= 64, "At most 64 positions fit in a collision key");
+        let (k, n) = (code.dimension(), code.length());
+        let mut best: Option<BitVector> = None;
+        {
+            let mut keep = |candidate: BitVector, permutation: &Permutation| {
+                if best
+                    .as_ref()
+                    .is_none_or(|b| candidate.weight() < b.weight())
+                {
+                    best = Some(permutation.inverse().permute_bits(&candidate));
+                }
+            };
+            for _ in 0..self.iterations.max(1) {
+                let permutation = Permutation::random(n, rng);
+                let mut g = code.generator_matrix().clone();
+                g.permute_columns(&permutation);
+                let pivots = g.reduce_row_echelon();
+                // the rows of the systematic form are codewords themselves
+                for row in g.rows() {
+                    keep(row.clone(), &permutation);
+                }
+
+                let mut is_pivot = vec![false; n];
+                for &col in &pivots {
+                    is_pivot[col] = true;
+                }
+                let window: Vec<usize> =
+                    (0..n).filter(|&col| !is_pivot[col]).take(self.l).collect();
+                let key = |sum: &BitVector| {
+                    window
+                        .iter()
+                        .enumerate()
+                        .filter(|&(_, &col)| sum.get(col))
+                        .fold(0u64, |key, (i, _)| key | 1 << i)
+                };
+
+                let half = k / 2;
+                let mut sums: HashMap<u64, Vec<BitVector>> = HashMap::new();
+                for_each_sum(&g, 0, half, self.p, |sum| {
+                    sums.entry(key(&sum)).or_default().push(sum);
+                });
+                for_each_sum(&g, half, k, self.p, |sum| {
+                    if let Some(matches) = sums.get(&key(&sum)) {
+                        for other in matches {
+                            keep(&sum ^ other, &permutation);
+                        }
+                    }
+                });
+            }
+        }
+        best
+    }
+}
+
+/// Call `f` with the sum of every `p` of the rows `start..end`
+fn for_each_sum<F: FnMut(BitVector)>(g: &BitMatrix, start: usize, end: usize, p: usize, mut f: F) {
+    if p > end - start {
+        return;
+    }
+    let mut rows: Vec<usize> = (0..p).collect();
+    loop {
+        let mut sum = BitVector::zero(g.ncols());
+        for &row in &rows {
+            sum ^= g.row(start + row);
+        }
+        f(sum);
+        if !next_combination(&mut rows, end - start) {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linear_code::tests::golay;
+    use rand::{SeedableRng, XorShiftRng};
+
+    fn rng() -> XorShiftRng {
+        XorShiftRng::from_seed([1, 2, 3, 4])
+    }
+
+    /// The lowest weight of a non-zero codeword, from all of them
+    fn brute_force(code: &LinearCode) -> usize {
+        let k = code.dimension();
+        (1..1u32 << k)
+            .map(|m| {
+                let message: Vec<bool> = (0..k).map(|i| (m >> i) & 1 == 1).collect();
+                code.encode(&BitVector::from_bools(&message)).weight()
+            })
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn exact_distance() {
+        assert_eq!(brouwer_zimmermann(golay().generator_matrix()), Some(7));
+        let mut rng = rng();
+        for &(n, k) in &[(10, 3), (15, 7), (20, 10), (16, 12), (12, 1), (8, 8)] {
+            let code = LinearCode::random(n, k, &mut rng);
+            assert_eq!(
+                brouwer_zimmermann(code.generator_matrix()),
+                Some(brute_force(&code)),
+                "[{}, {}]",
+                n,
+                k
+            );
+        }
+        assert_eq!(brouwer_zimmermann(&BitMatrix::zero(0, 5)), None);
+    }
+
+    #[test]
+    fn stern_search() {
+        let code = golay();
+        let stern = Stern {
+            iterations: 20,
+            p: 1,
+            l: 4,
+        };
+        let found = stern.search(&code, &mut rng()).unwrap();
+        assert!(code.is_codeword(&found));
+        assert_eq!(found.weight(), 7);
+
+        let code = LinearCode::random(60, 30, &mut rng());
+        let found = Stern {
+            iterations: 10,
+            p: 2,
+            l: 8,
+        }
+        .search(&code, &mut rng())
+        .unwrap();
+        assert!(code.is_codeword(&found));
+        assert!(!found.is_zero());
+        assert!(found.weight() <= 12, "weight {}", found.weight());
+        assert_eq!(
+            stern.search(&LinearCode::random(5, 0, &mut rng()), &mut rng()),
+            None
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! A dudect-style test for timing leaks.
+//!
+//! An operation is timed on two classes of inputs, typically a fixed input
+//! and random ones, in random order. If the running time depends on the
+//! input, Welch's t-test eventually tells the two timing distributions
+//! apart. See Reparaz, Balasch and Verbauwhede, "Dude, is my code constant
+//! time?" (DATE 2017).
+//!
+//! The measurements are only meaningful in release builds on a quiet
+//! machine, so the tests that look for leaks are ignored by default:
+//! run them with `cargo test --release -- --ignored`.
+
+use rand::Rng;
+use std::hint::black_box;
+use std::time::Instant;
+
+/// A `|t|` above this value is taken as evidence of a leak
+pub const THRESHOLD: f64 = 4.5;
+
+/// Running means and variances of two classes, using Welford's method
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WelchTest {
+    count: [f64; 2],
+    mean: [f64; 2],
+    m2: [f64; 2],
+}
+
+impl WelchTest {
+    pub fn new() -> WelchTest {
+        WelchTest::default()
+    }
+
+    /// Add a measurement to class `0` or `1`
+    pub fn push(&mut self, class: usize, value: f64) {
+        self.count[class] += 1.0;
+        let delta = value - self.mean[class];
+        self.mean[class] += delta / self.count[class];
+        self.m2[class] += delta * (value - self.mean[class]);
+    }
+
+    pub fn samples(&self) -> usize {
+        (self.count[0] + self.count[1]) as usize
+    }
+
+    /// Welch's t statistic of the difference between the means
+    pub fn t(&self) -> f64 {
+        let variance = |c: usize| self.m2[c] / (self.count[c] - 1.0);
+        let error = (variance(0) / self.count[0] + variance(1) / self.count[1]).sqrt();
+        (self.mean[0] - self.mean[1]) / error
+    }
+
+    pub fn leaks(&self) -> bool {
+        self.t().abs() > THRESHOLD
+    }
+}
+
+/// Time `operation` on `samples` inputs made by `prepare` for randomly
+/// chosen classes.
+///
+/// All inputs are prepared before timing starts. Measurements above the
+/// 90th percentile, mostly caused by interrupts, are discarded. Panics if
+/// `samples` is zero.
+pub fn measure<R, T, O, P, F>(
+    samples: usize,
+    rng: &mut R,
+    mut prepare: P,
+    mut operation: F,
+) -> WelchTest
+where
+    R: Rng,
+    P: FnMut(usize, &mut R) -> T,
+    F: FnMut(&T) -> O,
+{
+    assert!(samples > 0, "A timing test needs samples");
+    let classes: Vec<usize> = (0..samples).map(|_| rng.gen_range(0, 2)).collect();
+    let inputs: Vec<T> = classes.iter().map(|&class| prepare(class, rng)).collect();
+    let timings: Vec<f64> = inputs
+        .iter()
+        .map(|input| {
+            let start = Instant::now();
+            black_box(operation(black_box(input)));
+            let elapsed = start.elapsed();
+            elapsed.as_secs() as f64 * 1e9 + f64::from(elapsed.subsec_nanos())
+        })
+        .collect();
+
+    let mut sorted = timings.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let cutoff = sorted[(sorted.len() * 9) / 10];
+    let mut test = WelchTest::new();
+    for (&class, &time) in classes.iter().zip(timings.iter()) {
+        if time <= cutoff {
+            test.push(class, time);
+        }
+    }
+    test
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bit_vector::BitVector;
+    use constant_time::ConstantTimeField;
+    use gf2m::Field;
+    use goppa::GoppaCode;
+    use rand::{seq, SeedableRng, XorShiftRng};
+
+    fn rng() -> XorShiftRng {
+        XorShiftRng::from_seed([1, 2, 3, 4])
+    }
+
+    #[test]
+    fn welch_statistic() {
+        let mut test = WelchTest::new();
+        for &x in &[1.0, 2.0, 3.0, 4.0] {
+            test.push(0, x);
+        }
+        for &x in &[2.0, 4.0, 6.0, 8.0] {
+            test.push(1, x);
+        }
+        assert_eq!(test.samples(), 8);
+        // means 2.5 and 5, variances 5/3 and 20/3
+        let expected = -2.5 / (25.0f64 / 12.0).sqrt();
+        assert!((test.t() - expected).abs() < 1e-12);
+        assert!(!test.leaks());
+    }
+
+    #[test]
+    fn measurements() {
+        // too few samples to tell anything apart, but enough to check the
+        // bookkeeping in a debug build
+        let test = measure(
+            300,
+            &mut rng(),
+            |class, _| class,
+            |&class| (0..10 + class as u64).fold(0u64, |acc, i| black_box(acc ^ i)),
+        );
+        // at least the fastest 90% are kept
+        assert!(test.samples() > 270 && test.samples() <= 300);
+        assert!(test.count[0] > 0.0 && test.count[1] > 0.0);
+        for class in 0..2 {
+            assert!(test.mean[class].is_finite() && test.mean[class] >= 0.0);
+            assert!(test.m2[class].is_finite() && test.m2[class] >= 0.0);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "A timing test needs samples")]
+    fn no_samples() {
+        measure(0, &mut rng(), |class, _| class, |&class| class);
+    }
+
+    #[test]
+    #[ignore]
+    fn detects_obvious_leak() {
+        let test = measure(
+            2000,
+            &mut rng(),
+            |class, _| class,
+            |&class| {
+                let rounds = if class == 0 { 10 } else { 2000 };
+                (0..rounds).fold(0u64, |acc, i| black_box(acc.wrapping_mul(31) ^ i))
+            },
+        );
+        assert!(test.leaks());
+    }
+
+    #[test]
+    #[ignore]
+    fn constant_time_multiplication() {
+        let ct = ConstantTimeField::new(&Field::new(13, 0x201B));
+        let test = measure(
+            1_000_000,
+            &mut rng(),
+            |class, rng| {
+                if class == 0 {
+                    (0, 0)
+                } else {
+                    (rng.gen_range(0, 1 << 13), rng.gen_range(0, 1 << 13))
+                }
+            },
+            |&(a, b)| ct.mul(a, b),
+        );
+        assert!(!test.leaks(), "t = {}", test.t());
+    }
+
+    #[test]
+    #[ignore]
+    fn constant_time_decoding() {
+        let mut rng = rng();
+        let code = GoppaCode::random(Field::new(12, 0x1009), 512, 20, &mut rng);
+        let fixed = BitVector::from_support(512, &(0..20).collect::<Vec<_>>());
+        let test = measure(
+            20_000,
+            &mut rng,
+            |class, rng| {
+                if class == 0 {
+                    fixed.clone()
+                } else {
+                    BitVector::from_support(512, &seq::sample_indices(rng, 512, 20))
+                }
+            },
+            |received| code.decode_constant_time(received),
+        );
+        assert!(!test.leaks(), "t = {}", test.t());
+    }
+}
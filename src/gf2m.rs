@@ -0,0 +1,457 @@
+//! Arithmetic in the binary extension fields GF(2^m) and in polynomial rings
+//! over them.
+//!
+//! Field elements are polynomials over GF(2) modulo an irreducible polynomial
+//! of degree `m`, stored as the bits of a `u16`. Multiplication goes through
+//! logarithm tables, so it is fast but *not* constant time.
+
+use rand::Rng;
+
+/// An element of GF(2^m), bit `i` holding the coefficient of `z^i`
+pub type Element = u16;
+
+/// The field GF(2^m) defined by an irreducible modulus
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    degree: u32,
+    modulus: u32,
+    exp: Vec<Element>,
+    log: Vec<usize>,
+}
+
+impl Field {
+    /// Construct the field `GF(2)[z] / modulus`.
+    ///
+    /// The modulus is given by its bits, e.g. `0x1009` for `z^12 + z^3 + 1`.
+    pub fn new(degree: u32, modulus: u32) -> Field {
+        assert!(
+            (2..=15).contains(&degree),
+            "Only fields GF(2^m) with 2 <= m <= 15 are supported"
+        );
+        assert_eq!(
+            32 - modulus.leading_zeros() - 1,
+            degree,
+            "The modulus should have the degree of the field"
+        );
+        Field::try_new(degree, modulus).expect("The modulus should be irreducible")
+    }
+
+    /// Like `new`, but returns `None` instead of panicking on an unsupported
+    /// degree or an invalid modulus
+    pub fn try_new(degree: u32, modulus: u32) -> Option<Field> {
+        if !(2..=15).contains(&degree) || modulus >> degree != 1 {
+            return None;
+        }
+        let order = 1usize << degree;
+        for generator in 2..order as u32 {
+            let mut exp = Vec::with_capacity(order - 1);
+            let mut log = vec![0; order];
+            let mut element = 1;
+            for i in 0..order - 1 {
+                if i > 0 && element == 1 {
+                    break;
+                }
+                log[element as usize] = i;
+                exp.push(element as Element);
+                element = carryless_mul_mod(element, generator, degree, modulus);
+            }
+            if exp.len() == order - 1 && element == 1 {
+                return Some(Field {
+                    degree,
+                    modulus,
+                    exp,
+                    log,
+                });
+            }
+        }
+        None
+    }
+
+    /// The `m` in GF(2^m)
+    pub fn degree(&self) -> u32 {
+        self.degree
+    }
+
+    pub fn modulus(&self) -> u32 {
+        self.modulus
+    }
+
+    /// The number of elements, `2^m`
+    pub fn order(&self) -> usize {
+        1 << self.degree
+    }
+
+    /// All field elements, starting from zero
+    pub fn elements(&self) -> impl Iterator<Item = Element> {
+        (0..self.order()).map(|a| a as Element)
+    }
+
+    /// Sample a uniformly random element
+    pub fn random<R: Rng>(&self, rng: &mut R) -> Element {
+        rng.gen_range(0, self.order()) as Element
+    }
+
+    #[inline]
+    pub fn add(&self, a: Element, b: Element) -> Element {
+        a ^ b
+    }
+
+    #[inline]
+    pub fn mul(&self, a: Element, b: Element) -> Element {
+        if a == 0 || b == 0 {
+            return 0;
+        }
+        let index = self.log[a as usize] + self.log[b as usize];
+        self.exp[index % self.exp.len()]
+    }
+
+    #[inline]
+    pub fn square(&self, a: Element) -> Element {
+        self.mul(a, a)
+    }
+
+    /// The multiplicative inverse of a non-zero element
+    pub fn inverse(&self, a: Element) -> Element {
+        assert_ne!(a, 0, "Zero has no inverse");
+        let size = self.exp.len();
+        self.exp[(size - self.log[a as usize]) % size]
+    }
+
+    #[inline]
+    pub fn div(&self, a: Element, b: Element) -> Element {
+        self.mul(a, self.inverse(b))
+    }
+
+    pub fn pow(&self, a: Element, exponent: usize) -> Element {
+        if exponent == 0 {
+            return 1;
+        }
+        if a == 0 {
+            return 0;
+        }
+        let size = self.exp.len();
+        self.exp[(self.log[a as usize] * (exponent % size)) % size]
+    }
+}
+
+/// Multiply two field elements bit by bit and reduce by the modulus
+fn carryless_mul_mod(a: u32, b: u32, degree: u32, modulus: u32) -> u32 {
+    let mut result = 0;
+    for i in 0..degree {
+        if (b >> i) & 1 == 1 {
+            result ^= a << i;
+        }
+    }
+    for i in (degree..2 * degree).rev() {
+        if (result >> i) & 1 == 1 {
+            result ^= modulus << (i - degree);
+        }
+    }
+    result
+}
+
+/// A polynomial over GF(2^m), with the coefficient of `x^i` at index `i`.
+///
+/// The coefficients never have trailing zeros, so the zero polynomial has no
+/// coefficients at all.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Polynomial {
+    coefficients: Vec<Element>,
+}
+
+impl Polynomial {
+    pub fn from_coefficients(mut coefficients: Vec<Element>) -> Polynomial {
+        while coefficients.last() == Some(&0) {
+            coefficients.pop();
+        }
+        Polynomial { coefficients }
+    }
+
+    pub fn zero() -> Polynomial {
+        Polynomial {
+            coefficients: Vec::new(),
+        }
+    }
+
+    pub fn one() -> Polynomial {
+        Polynomial::monomial(1, 0)
+    }
+
+    /// The polynomial `coefficient * x^degree`
+    pub fn monomial(coefficient: Element, degree: usize) -> Polynomial {
+        let mut coefficients = vec![0; degree + 1];
+        coefficients[degree] = coefficient;
+        Polynomial::from_coefficients(coefficients)
+    }
+
+    /// Sample a monic irreducible polynomial of the given degree
+    pub fn random_irreducible<R: Rng>(field: &Field, degree: usize, rng: &mut R) -> Polynomial {
+        assert!(degree > 0, "Constants are never irreducible");
+        loop {
+            let mut coefficients: Vec<Element> = (0..degree).map(|_| field.random(rng)).collect();
+            coefficients.push(1);
+            let candidate = Polynomial::from_coefficients(coefficients);
+            if candidate.is_irreducible(field) {
+                return candidate;
+            }
+        }
+    }
+
+    pub fn coefficients(&self) -> &[Element] {
+        &self.coefficients
+    }
+
+    /// The coefficient of `x^i`
+    pub fn coefficient(&self, i: usize) -> Element {
+        self.coefficients.get(i).cloned().unwrap_or(0)
+    }
+
+    /// The degree, or `None` for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    pub fn leading_coefficient(&self) -> Element {
+        self.coefficients.last().cloned().unwrap_or(0)
+    }
+
+    /// Evaluate at `x` using Horner's rule
+    pub fn eval(&self, x: Element, field: &Field) -> Element {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(0, |acc, &c| field.add(field.mul(acc, x), c))
+    }
+
+    pub fn add(&self, other: &Polynomial) -> Polynomial {
+        let len = self.coefficients.len().max(other.coefficients.len());
+        Polynomial::from_coefficients(
+            (0..len)
+                .map(|i| self.coefficient(i) ^ other.coefficient(i))
+                .collect(),
+        )
+    }
+
+    pub fn scale(&self, c: Element, field: &Field) -> Polynomial {
+        Polynomial::from_coefficients(self.coefficients.iter().map(|&a| field.mul(a, c)).collect())
+    }
+
+    pub fn mul(&self, other: &Polynomial, field: &Field) -> Polynomial {
+        if self.is_zero() || other.is_zero() {
+            return Polynomial::zero();
+        }
+        let mut coefficients = vec![0; self.coefficients.len() + other.coefficients.len() - 1];
+        for (i, &a) in self.coefficients.iter().enumerate() {
+            if a == 0 {
+                continue;
+            }
+            for (j, &b) in other.coefficients.iter().enumerate() {
+                coefficients[i + j] ^= field.mul(a, b);
+            }
+        }
+        Polynomial::from_coefficients(coefficients)
+    }
+
+    /// Square, using that squaring is linear in characteristic two
+    pub fn square(&self, field: &Field) -> Polynomial {
+        let mut coefficients = vec![0; 2 * self.coefficients.len()];
+        for (i, &a) in self.coefficients.iter().enumerate() {
+            coefficients[2 * i] = field.square(a);
+        }
+        Polynomial::from_coefficients(coefficients)
+    }
+
+    /// Long division: `(quotient, remainder)`
+    pub fn div_rem(&self, divisor: &Polynomial, field: &Field) -> (Polynomial, Polynomial) {
+        let divisor_degree = divisor.degree().expect("Division by the zero polynomial");
+        let lead_inverse = field.inverse(divisor.leading_coefficient());
+        let mut remainder = self.coefficients.clone();
+        if remainder.len() <= divisor_degree {
+            return (Polynomial::zero(), self.clone());
+        }
+        let mut quotient = vec![0; remainder.len() - divisor_degree];
+        for i in (divisor_degree..remainder.len()).rev() {
+            let factor = field.mul(remainder[i], lead_inverse);
+            if factor == 0 {
+                continue;
+            }
+            let shift = i - divisor_degree;
+            quotient[shift] = factor;
+            for (j, &d) in divisor.coefficients.iter().enumerate() {
+                remainder[shift + j] ^= field.mul(factor, d);
+            }
+        }
+        remainder.truncate(divisor_degree);
+        (
+            Polynomial::from_coefficients(quotient),
+            Polynomial::from_coefficients(remainder),
+        )
+    }
+
+    pub fn rem(&self, modulus: &Polynomial, field: &Field) -> Polynomial {
+        self.div_rem(modulus, field).1
+    }
+
+    /// Scale to leading coefficient one
+    pub fn monic(&self, field: &Field) -> Polynomial {
+        if self.is_zero() {
+            return Polynomial::zero();
+        }
+        self.scale(field.inverse(self.leading_coefficient()), field)
+    }
+
+    /// The monic greatest common divisor
+    pub fn gcd(&self, other: &Polynomial, field: &Field) -> Polynomial {
+        let mut a = self.clone();
+        let mut b = other.clone();
+        while !b.is_zero() {
+            let r = a.rem(&b, field);
+            a = b;
+            b = r;
+        }
+        a.monic(field)
+    }
+
+    /// Test irreducibility with Ben-Or's algorithm.
+    ///
+    /// A polynomial `f` of degree `t` over GF(q) is irreducible iff
+    /// `gcd(x^(q^i) - x, f) = 1` for all `1 <= i <= t / 2`.
+    pub fn is_irreducible(&self, field: &Field) -> bool {
+        let degree = match self.degree() {
+            None | Some(0) => return false,
+            Some(degree) => degree,
+        };
+        let x = Polynomial::monomial(1, 1);
+        let mut power = x.clone();
+        for _ in 0..degree / 2 {
+            for _ in 0..field.degree() {
+                power = power.square(field).rem(self, field);
+            }
+            if power.add(&x).gcd(self, field) != Polynomial::one() {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, XorShiftRng};
+
+    fn field() -> Field {
+        // z^8 + z^4 + z^3 + z + 1, for which z itself is not a generator
+        Field::new(8, 0x11B)
+    }
+
+    #[test]
+    fn field_axioms() {
+        let f = field();
+        for a in f.elements().skip(1) {
+            assert_eq!(f.mul(a, f.inverse(a)), 1);
+            assert_eq!(f.mul(a, 1), a);
+            assert_eq!(f.mul(a, 0), 0);
+        }
+        assert_eq!(f.add(0x53, 0x53), 0);
+    }
+
+    #[test]
+    fn matches_bitwise_multiplication() {
+        let f = field();
+        for a in (0..256).step_by(7) {
+            for b in (0..256).step_by(5) {
+                let expected = carryless_mul_mod(a, b, 8, 0x11B) as Element;
+                assert_eq!(f.mul(a as Element, b as Element), expected);
+            }
+        }
+        // the classic example from the AES specification
+        assert_eq!(f.mul(0x57, 0x83), 0xC1);
+    }
+
+    #[test]
+    fn powers() {
+        let f = Field::new(12, 0x1009);
+        let a = 0x123;
+        assert_eq!(f.pow(a, 0), 1);
+        assert_eq!(f.pow(a, 3), f.mul(a, f.square(a)));
+        assert_eq!(f.pow(a, f.order() - 1), 1);
+        assert_eq!(f.pow(0, 5), 0);
+        assert_eq!(f.div(f.mul(a, 7), 7), a);
+    }
+
+    #[test]
+    #[should_panic]
+    fn reducible_modulus() {
+        // z^4 + 1 = (z + 1)^4
+        Field::new(4, 0x11);
+    }
+
+    #[test]
+    fn try_new() {
+        assert!(Field::try_new(4, 0x11).is_none());
+        assert!(Field::try_new(4, 0x13).is_some());
+        assert!(Field::try_new(4, 0x23).is_none());
+        assert!(Field::try_new(16, 0x1002B).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_inverse() {
+        field().inverse(0);
+    }
+
+    #[test]
+    fn polynomial_basics() {
+        let f = field();
+        let p = Polynomial::from_coefficients(vec![1, 2, 0, 0]);
+        assert_eq!(p.degree(), Some(1));
+        assert_eq!(p.coefficient(5), 0);
+        assert_eq!(Polynomial::zero().degree(), None);
+        assert_eq!(p.eval(0, &f), 1);
+        assert_eq!(p.eval(3, &f), 1 ^ f.mul(2, 3));
+        assert!(p.add(&p).is_zero());
+    }
+
+    #[test]
+    fn polynomial_division() {
+        let f = field();
+        let a = Polynomial::from_coefficients(vec![5, 0, 7, 9, 1, 3]);
+        let b = Polynomial::from_coefficients(vec![2, 1, 4]);
+        let (q, r) = a.div_rem(&b, &f);
+        assert!(r.degree() < b.degree());
+        assert_eq!(q.mul(&b, &f).add(&r), a);
+        assert_eq!(a.square(&f), a.mul(&a, &f));
+    }
+
+    #[test]
+    fn polynomial_gcd() {
+        let f = field();
+        let a = Polynomial::from_coefficients(vec![3, 1]);
+        let b = Polynomial::from_coefficients(vec![5, 1]);
+        let c = Polynomial::from_coefficients(vec![9, 1]);
+        assert_eq!(a.mul(&b, &f).gcd(&a.mul(&c, &f), &f), a);
+        assert_eq!(b.gcd(&c, &f), Polynomial::one());
+    }
+
+    #[test]
+    fn irreducibility() {
+        let f = field();
+        let a = Polynomial::from_coefficients(vec![3, 1]);
+        let b = Polynomial::from_coefficients(vec![5, 7, 1]);
+        assert!(a.is_irreducible(&f));
+        assert!(!a.mul(&b, &f).is_irreducible(&f));
+        assert!(!Polynomial::one().is_irreducible(&f));
+
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        let g = Polynomial::random_irreducible(&f, 6, &mut rng);
+        assert_eq!(g.degree(), Some(6));
+        assert_eq!(g.leading_coefficient(), 1);
+        // an irreducible polynomial of degree > 1 has no roots
+        assert!(f.elements().all(|a| g.eval(a, &f) != 0));
+    }
+}
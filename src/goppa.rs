@@ -0,0 +1,428 @@
+//! Binary Goppa codes and their algebraic decoder.
+//!
+//! The code `Γ(L, g)` with support `L = (α_0, ..., α_{n-1})` in GF(2^m) and
+//! a square-free Goppa polynomial `g` of degree `t` consists of the binary
+//! words `c` with `Σ c_j / (x - α_j) ≡ 0 mod g`. Because `Γ(L, g) = Γ(L, g²)`
+//! for such `g`, decoding `t` errors reduces to Berlekamp-Massey on the `2t`
+//! syndromes with respect to `g²`.
+
+use bit_vector::BitVector;
+use constant_time::{mask, zero_mask, ConstantTimeField};
+use gf2m::{Element, Field, Polynomial};
+use matrix::{BinaryError, BinaryFormat, BinaryReader, BinaryWriter, BitMatrix};
+use rand::Rng;
+
+/// A binary Goppa code
+#[derive(Clone, Debug, PartialEq)]
+pub struct GoppaCode {
+    field: Field,
+    support: Vec<Element>,
+    goppa_polynomial: Polynomial,
+}
+
+impl GoppaCode {
+    pub fn new(field: Field, support: Vec<Element>, goppa_polynomial: Polynomial) -> GoppaCode {
+        if let Err(message) = check(&field, &support, &goppa_polynomial) {
+            panic!("{}", message);
+        }
+        GoppaCode {
+            field,
+            support,
+            goppa_polynomial,
+        }
+    }
+
+    /// Sample a code with a random support of `length` field elements and a
+    /// random irreducible Goppa polynomial of degree `t`
+    pub fn random<R: Rng>(field: Field, length: usize, t: usize, rng: &mut R) -> GoppaCode {
+        assert!(
+            length <= field.order(),
+            "The support can not be larger than the field"
+        );
+        let goppa_polynomial = Polynomial::random_irreducible(&field, t, rng);
+        let mut support: Vec<Element> = field.elements().collect();
+        rng.shuffle(&mut support);
+        support.truncate(length);
+        GoppaCode::new(field, support, goppa_polynomial)
+    }
+
+    pub fn field(&self) -> &Field {
+        &self.field
+    }
+
+    pub fn support(&self) -> &[Element] {
+        &self.support
+    }
+
+    pub fn goppa_polynomial(&self) -> &Polynomial {
+        &self.goppa_polynomial
+    }
+
+    pub fn length(&self) -> usize {
+        self.support.len()
+    }
+
+    /// The number of errors the decoder corrects, `t = deg g`
+    pub fn correction_capacity(&self) -> usize {
+        self.goppa_polynomial.degree().unwrap()
+    }
+
+    /// The binary parity-check matrix of size `mt x n`.
+    ///
+    /// Entry `(i, j)` of the matrix over GF(2^m) is `α_j^i / g(α_j)`; its
+    /// bit `b` ends up in binary row `i * m + b`.
+    pub fn parity_check_matrix(&self) -> BitMatrix {
+        let m = self.field.degree() as usize;
+        let t = self.correction_capacity();
+        let n = self.length();
+        let mut h = BitMatrix::zero(m * t, n);
+        for (j, &alpha) in self.support.iter().enumerate() {
+            let mut entry = self
+                .field
+                .inverse(self.goppa_polynomial.eval(alpha, &self.field));
+            for i in 0..t {
+                for b in 0..m {
+                    if (entry >> b) & 1 == 1 {
+                        h.set(i * m + b, j, true);
+                    }
+                }
+                entry = self.field.mul(entry, alpha);
+            }
+        }
+        h
+    }
+
+    /// The `2t` syndromes `Σ_j r_j α_j^i / g(α_j)²` of a received word
+    pub fn syndrome(&self, received: &BitVector) -> Vec<Element> {
+        assert_eq!(
+            received.len(),
+            self.length(),
+            "Vector should match the code length"
+        );
+        let mut syndrome = vec![0; 2 * self.correction_capacity()];
+        for j in received.support() {
+            let alpha = self.support[j];
+            let mut term = self.field.inverse(
+                self.field
+                    .square(self.goppa_polynomial.eval(alpha, &self.field)),
+            );
+            for s in syndrome.iter_mut() {
+                *s = self.field.add(*s, term);
+                term = self.field.mul(term, alpha);
+            }
+        }
+        syndrome
+    }
+
+    /// Find the error vector of weight at most `t` in a received word.
+    ///
+    /// Returns `None` if there is no such error.
+    pub fn decode(&self, received: &BitVector) -> Option<BitVector> {
+        let syndrome = self.syndrome(received);
+        let (connection, errors) = berlekamp_massey(&self.field, &syndrome);
+        if errors > self.correction_capacity() {
+            return None;
+        }
+        // the roots of x^L C(1/x) are the error locations themselves
+        let locator = Polynomial::from_coefficients(
+            (0..=errors)
+                .map(|k| connection.coefficient(errors - k))
+                .collect(),
+        );
+        let mut error = BitVector::zero(self.length());
+        for (j, &alpha) in self.support.iter().enumerate() {
+            if locator.eval(alpha, &self.field) == 0 {
+                error.set(j, true);
+            }
+        }
+        if error.weight() != errors || self.syndrome(&error) != syndrome {
+            return None;
+        }
+        Some(error)
+    }
+
+    /// Decode exactly `t` errors in constant time.
+    ///
+    /// The time taken does not depend on the received word or the secret
+    /// code, only on the parameters. Returns the error vector and whether
+    /// decoding succeeded; on failure the vector is meaningless.
+    pub fn decode_constant_time(&self, received: &BitVector) -> (BitVector, bool) {
+        let ct = ConstantTimeField::new(&self.field);
+        let syndrome = self.syndrome_constant_time(&ct, received);
+        let locator = ct.berlekamp_massey(&syndrome);
+        let roots = ct.roots(&locator, &self.support);
+        let mut bytes = vec![0u8; self.length().div_ceil(8)];
+        for (j, &root) in roots.iter().enumerate() {
+            bytes[j / 8] |= (root as u8) << (j % 8);
+        }
+        let error = BitVector::from_bytes(self.length(), &bytes).unwrap();
+
+        let check = self.syndrome_constant_time(&ct, &error);
+        let difference = syndrome
+            .iter()
+            .zip(check.iter())
+            .fold(0, |acc, (a, b)| acc | (a ^ b));
+        let weight = (error.weight() ^ self.correction_capacity()) as u16;
+        let valid = zero_mask(difference) & zero_mask(weight);
+        (error, valid & 1 == 1)
+    }
+
+    /// The same as `syndrome`, but visiting every position of the word
+    fn syndrome_constant_time(&self, ct: &ConstantTimeField, received: &BitVector) -> Vec<Element> {
+        assert_eq!(
+            received.len(),
+            self.length(),
+            "Vector should match the code length"
+        );
+        let goppa: Vec<Element> = (0..=self.correction_capacity())
+            .map(|i| self.goppa_polynomial.coefficient(i))
+            .collect();
+        let words = received.words();
+        let mut syndrome = vec![0; 2 * self.correction_capacity()];
+        for (j, &alpha) in self.support.iter().enumerate() {
+            let bit = mask((words[j / 64] >> (j % 64)) as u16);
+            let mut term = ct.inverse(ct.square(ct.eval(&goppa, alpha)));
+            for s in syndrome.iter_mut() {
+                *s ^= term & bit;
+                term = ct.mul(term, alpha);
+            }
+        }
+        syndrome
+    }
+}
+
+/// The field, support and Goppa polynomial
+impl BinaryFormat for GoppaCode {
+    const KIND: u16 = 17;
+
+    fn write_payload(&self, writer: &mut BinaryWriter) {
+        writer.put_u32(self.field.degree());
+        writer.put_u32(self.field.modulus());
+        write_elements(writer, &self.support);
+        write_elements(writer, self.goppa_polynomial.coefficients());
+    }
+
+    fn read_payload(reader: &mut BinaryReader) -> Result<GoppaCode, BinaryError> {
+        let degree = reader.get_u32()?;
+        let modulus = reader.get_u32()?;
+        let field = Field::try_new(degree, modulus)
+            .ok_or_else(|| BinaryError::Invalid(format!("invalid field modulus {:#x}", modulus)))?;
+        let support = read_elements(reader)?;
+        let goppa_polynomial = Polynomial::from_coefficients(read_elements(reader)?);
+        check(&field, &support, &goppa_polynomial)
+            .map_err(|message| BinaryError::Invalid(message.to_string()))?;
+        Ok(GoppaCode {
+            field,
+            support,
+            goppa_polynomial,
+        })
+    }
+}
+
+fn write_elements(writer: &mut BinaryWriter, elements: &[Element]) {
+    writer.put_usize(elements.len());
+    for &e in elements {
+        writer.put_u16(e);
+    }
+}
+
+fn read_elements(reader: &mut BinaryReader) -> Result<Vec<Element>, BinaryError> {
+    let len = reader.get_len(1)?;
+    (0..len).map(|_| reader.get_u16()).collect()
+}
+
+/// Check the requirements on the parameters of a Goppa code
+fn check(
+    field: &Field,
+    support: &[Element],
+    goppa_polynomial: &Polynomial,
+) -> Result<(), &'static str> {
+    if goppa_polynomial.degree().is_none_or(|t| t == 0) {
+        return Err("The Goppa polynomial should not be constant");
+    }
+    if goppa_polynomial
+        .coefficients()
+        .iter()
+        .any(|&c| c as usize >= field.order())
+    {
+        return Err("The Goppa polynomial should have coefficients in the field");
+    }
+    let mut seen = vec![false; field.order()];
+    for &alpha in support {
+        if alpha as usize >= field.order() {
+            return Err("The support should consist of field elements");
+        }
+        if seen[alpha as usize] {
+            return Err("The support should consist of distinct elements");
+        }
+        seen[alpha as usize] = true;
+        if goppa_polynomial.eval(alpha, field) == 0 {
+            return Err("The support should not contain roots of the Goppa polynomial");
+        }
+    }
+    Ok(())
+}
+
+/// Find the shortest linear feedback shift register generating `sequence`.
+///
+/// Returns the connection polynomial `C` with `C(0) = 1` and the register
+/// length `L`.
+fn berlekamp_massey(field: &Field, sequence: &[Element]) -> (Polynomial, usize) {
+    let mut connection = Polynomial::one();
+    let mut previous = Polynomial::one();
+    let mut length = 0;
+    let mut shift = 1;
+    let mut previous_discrepancy = 1;
+    for n in 0..sequence.len() {
+        let discrepancy = (1..=length).fold(sequence[n], |acc, i| {
+            field.add(acc, field.mul(connection.coefficient(i), sequence[n - i]))
+        });
+        if discrepancy == 0 {
+            shift += 1;
+            continue;
+        }
+        let factor = field.div(discrepancy, previous_discrepancy);
+        let update = connection.add(&previous.mul(&Polynomial::monomial(factor, shift), field));
+        if 2 * length <= n {
+            previous = connection;
+            length = n + 1 - length;
+            previous_discrepancy = discrepancy;
+            shift = 1;
+        } else {
+            shift += 1;
+        }
+        connection = update;
+    }
+    (connection, length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{seq, SeedableRng, XorShiftRng};
+
+    fn rng() -> XorShiftRng {
+        XorShiftRng::from_seed([1, 2, 3, 4])
+    }
+
+    fn code() -> GoppaCode {
+        GoppaCode::random(Field::new(6, 0x43), 60, 5, &mut rng())
+    }
+
+    #[test]
+    fn random_code() {
+        let code = code();
+        assert_eq!(code.length(), 60);
+        assert_eq!(code.correction_capacity(), 5);
+        assert!(code.goppa_polynomial().is_irreducible(code.field()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn repeated_support() {
+        let field = Field::new(4, 0x13);
+        let g = Polynomial::from_coefficients(vec![2, 1, 1]);
+        GoppaCode::new(field, vec![1, 2, 1], g);
+    }
+
+    #[test]
+    fn berlekamp_massey_fibonacci() {
+        // s_n = s_{n-1} + s_{n-2} has connection polynomial 1 + x + x^2
+        let field = Field::new(4, 0x13);
+        let sequence = [1, 1, 0, 1, 1, 0];
+        let (c, l) = berlekamp_massey(&field, &sequence);
+        assert_eq!(l, 2);
+        assert_eq!(c, Polynomial::from_coefficients(vec![1, 1, 1]));
+    }
+
+    #[test]
+    fn codewords_have_zero_syndrome() {
+        // in systematic form [I | T], column j of T gives the codeword (T_j, e_j)
+        let mut rng = rng();
+        let (code, h) = loop {
+            let code = GoppaCode::random(Field::new(6, 0x43), 60, 5, &mut rng);
+            let mut h = code.parity_check_matrix();
+            if h.reduce_systematic() {
+                break (code, h);
+            }
+        };
+        assert_eq!(h.nrows(), 30);
+        for j in h.nrows()..code.length() {
+            let mut codeword = BitVector::zero(code.length());
+            codeword.set(j, true);
+            for i in 0..h.nrows() {
+                codeword.set(i, h.get(i, j));
+            }
+            assert_eq!(code.syndrome(&codeword), vec![0; 10]);
+        }
+    }
+
+    #[test]
+    fn decode_errors() {
+        let code = code();
+        let mut rng = rng();
+        for weight in 0..=5 {
+            let support = seq::sample_indices(&mut rng, code.length(), weight);
+            let error = BitVector::from_support(code.length(), &support);
+            assert_eq!(code.decode(&error), Some(error));
+        }
+    }
+
+    #[test]
+    fn decode_support_containing_zero() {
+        let field = Field::new(4, 0x13);
+        let g = Polynomial::from_coefficients(vec![8, 1, 1]);
+        assert!(g.is_irreducible(&field));
+        let code = GoppaCode::new(field, (0..16).collect(), g);
+        let error = BitVector::from_support(16, &[0, 9]);
+        assert_eq!(code.decode(&error), Some(error));
+    }
+
+    #[test]
+    fn decode_constant_time() {
+        let code = code();
+        let mut rng = rng();
+        for _ in 0..5 {
+            let support = seq::sample_indices(&mut rng, code.length(), 5);
+            let error = BitVector::from_support(code.length(), &support);
+            assert_eq!(code.decode_constant_time(&error), (error.clone(), true));
+            assert_eq!(code.decode(&error), Some(error));
+        }
+        // only exactly t errors are accepted
+        let error = BitVector::from_support(code.length(), &[1, 2]);
+        assert!(!code.decode_constant_time(&error).1);
+        let error = BitVector::from_support(code.length(), &[1, 2, 3, 4, 5, 6, 7]);
+        assert!(!code.decode_constant_time(&error).1);
+    }
+
+    #[test]
+    fn binary_roundtrip() {
+        let code = code();
+        let bytes = code.to_binary();
+        assert_eq!(GoppaCode::from_binary(&bytes).unwrap(), code);
+        // a support element repeated: valid checksum, invalid code
+        let mut writer = BinaryWriter::new();
+        code.write_payload(&mut writer);
+        let mut payload = writer.into_bytes();
+        payload[16..18].copy_from_slice(&code.support()[1].to_le_bytes());
+        match GoppaCode::read_payload(&mut BinaryReader::new(&payload)) {
+            Err(BinaryError::Invalid(_)) => (),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn decode_too_many_errors() {
+        let code = code();
+        let mut rng = rng();
+        let mut failures = 0;
+        for _ in 0..20 {
+            let support = seq::sample_indices(&mut rng, code.length(), 8);
+            let error = BitVector::from_support(code.length(), &support);
+            if code.decode(&error) != Some(error) {
+                failures += 1;
+            }
+        }
+        assert_eq!(failures, 20);
+    }
+}
@@ -0,0 +1,287 @@
+//! Reading codes written by other tools.
+//!
+//! LDPC codes are usually distributed as a parity-check matrix in MacKay's
+//! alist format. Sage and MAGMA print a code as a description such as
+//! `[7, 4, 3] Linear Code over GF(2)`, followed by a matrix with every row in
+//! square brackets; MAGMA labels it `Generator matrix:` or
+//! `Parity check matrix:`. Errors point at the offending line.
+
+use linear_code::LinearCode;
+use matrix::{BitMatrix, ReadError, SparseBinaryMatrix};
+use std::io::BufRead;
+
+/// Read a code given by its parity-check matrix in alist format
+pub fn read_alist<R: BufRead>(reader: R) -> Result<LinearCode, ReadError> {
+    let parity_check = SparseBinaryMatrix::read_alist(reader)?;
+    Ok(LinearCode::from_parity_check(BitMatrix::from_sparse(
+        &parity_check,
+    )))
+}
+
+/// Read a binary code as printed by Sage or MAGMA.
+///
+/// The description line and the label are optional; a matrix without a
+/// label is read as a generator matrix. If there is a description, the
+/// length and dimension of the code should match it.
+pub fn read_printed<R: BufRead>(reader: R) -> Result<LinearCode, ReadError> {
+    // the matrix rows, with the other lines left blank to keep the numbering
+    let mut rows = String::new();
+    let mut description = None;
+    let mut parity_check = false;
+    let mut in_row = false;
+    let mut last_line = 0;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let number = i + 1;
+        let trimmed = line.trim();
+        if !trimmed.is_empty() {
+            last_line = number;
+        }
+        if !in_row {
+            if let Some(parameters) = parse_description(trimmed, number)? {
+                description = Some((number, parameters));
+                rows.push('\n');
+                continue;
+            }
+            if trimmed.ends_with(':') {
+                parity_check = parse_label(trimmed, number)?;
+                rows.push('\n');
+                continue;
+            }
+        }
+        if !trimmed.starts_with('#') {
+            for c in trimmed.chars() {
+                match c {
+                    '[' => in_row = true,
+                    ']' => in_row = false,
+                    _ => (),
+                }
+            }
+        }
+        rows.push_str(&line);
+        rows.push('\n');
+    }
+
+    let matrix = BitMatrix::from_bracketed(&rows)?;
+    if matrix.ncols() == 0 {
+        return Err(ReadError::Parse {
+            line: last_line,
+            message: "expected the rows of a matrix".to_string(),
+        });
+    }
+    let code = if parity_check {
+        LinearCode::from_parity_check(matrix)
+    } else if matrix.rank() != matrix.nrows() {
+        return Err(ReadError::Parse {
+            line: last_line,
+            message: "the rows of the generator matrix should be independent".to_string(),
+        });
+    } else {
+        LinearCode::from_generator(matrix)
+    };
+    if let Some((line, (length, dimension))) = description {
+        let mismatch = if code.length() != length {
+            Some(format!(
+                "the code has length {}, but the matrix has {} columns",
+                length,
+                code.length()
+            ))
+        } else if code.dimension() != dimension {
+            Some(format!(
+                "the code has dimension {}, but the matrix gives {}",
+                dimension,
+                code.dimension()
+            ))
+        } else {
+            None
+        };
+        if let Some(message) = mismatch {
+            return Err(ReadError::Parse { line, message });
+        }
+    }
+    Ok(code)
+}
+
+/// Parse a description `[n, k] ...` or `[n, k, d] ...` into the length and
+/// dimension; lines that are not descriptions give `None`.
+fn parse_description(line: &str, number: usize) -> Result<Option<(usize, usize)>, ReadError> {
+    let close = match line.find(']') {
+        Some(close) if line.starts_with('[') => close,
+        _ => return Ok(None),
+    };
+    let rest = line[close + 1..].trim();
+    if rest.is_empty() {
+        return Ok(None);
+    }
+    let error = |message: String| ReadError::Parse {
+        line: number,
+        message,
+    };
+    let parameters: Vec<usize> = line[1..close]
+        .split(',')
+        .map(|p| p.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| error("expected the parameters [n, k] or [n, k, d]".to_string()))?;
+    if parameters.len() < 2 || parameters.len() > 3 {
+        return Err(error(
+            "expected the parameters [n, k] or [n, k, d]".to_string(),
+        ));
+    }
+    // MAGMA writes GF(q), Sage "Finite Field of size q"
+    let order = if let Some(start) = rest.find("GF(") {
+        rest[start + 3..].split(')').next()
+    } else if let Some(start) = rest.find("of size ") {
+        rest[start + 8..].split_whitespace().next()
+    } else {
+        None
+    };
+    match order {
+        Some(q) if q != "2" => Err(error(format!(
+            "only binary codes are supported, not codes over GF({})",
+            q
+        ))),
+        _ => Ok(Some((parameters[0], parameters[1]))),
+    }
+}
+
+/// Parse the label of a matrix, and return whether it is a parity-check
+/// matrix
+fn parse_label(line: &str, number: usize) -> Result<bool, ReadError> {
+    match line.to_lowercase().replace('-', " ").as_str() {
+        "generator matrix:" => Ok(false),
+        "parity check matrix:" => Ok(true),
+        _ => Err(ReadError::Parse {
+            line: number,
+            message: format!(
+                "unknown label {:?}, expected `Generator matrix:` or `Parity check matrix:`",
+                line
+            ),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bit_vector::BitVector;
+
+    fn parse_error<T: ::std::fmt::Debug>(result: Result<T, ReadError>) -> (usize, String) {
+        match result {
+            Err(ReadError::Parse { line, message }) => (line, message),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    fn hamming() -> LinearCode {
+        read_printed(
+            "[1 0 0 0 0 1 1]\n[0 1 0 0 1 0 1]\n[0 0 1 0 1 1 0]\n[0 0 0 1 1 1 1]\n".as_bytes(),
+        )
+        .unwrap()
+    }
+
+    /// Whether two codes have the same codewords
+    fn same_code(a: &LinearCode, b: &LinearCode) -> bool {
+        a.length() == b.length()
+            && a.dimension() == b.dimension()
+            && a.generator_matrix().rows().iter().all(|r| b.is_codeword(r))
+    }
+
+    #[test]
+    fn alist() {
+        let text = "7 3\n3 4\n1 1 2 1 2 2 3\n4 4 4\n1 0 0\n2 0 0\n1 2 0\n3 0 0\n\
+                    1 3 0\n2 3 0\n1 2 3\n1 3 5 7\n2 3 6 7\n4 5 6 7\n";
+        let code = read_alist(text.as_bytes()).unwrap();
+        assert_eq!(code.dimension(), 4);
+        assert!(code.is_codeword(&BitVector::from_bools(&[
+            true, true, true, false, false, false, false
+        ])));
+        let (line, _) = parse_error(read_alist("7 3\n3 4\n1 1 2\n".as_bytes()));
+        assert_eq!(line, 3);
+    }
+
+    #[test]
+    fn magma() {
+        let text = "[7, 4, 3] Linear Code over GF(2)\n\
+                    Generator matrix:\n\
+                    [1 0 0 0 0 1 1]\n\
+                    [0 1 0 0 1 0 1]\n\
+                    [0 0 1 0 1 1 0]\n\
+                    [0 0 0 1 1 1 1]\n";
+        assert!(same_code(
+            &read_printed(text.as_bytes()).unwrap(),
+            &hamming()
+        ));
+        let text = "[7, 4, 3] \"Hamming code\" Linear Code over GF(2)\n\
+                    Parity check matrix:\n\
+                    [0 0 0 1 1 1\n 1]\n\
+                    [0 1 1 0 0 1 1]\n\
+                    [1 0 1 0 1 0 1]\n";
+        assert!(same_code(
+            &read_printed(text.as_bytes()).unwrap(),
+            &hamming()
+        ));
+    }
+
+    #[test]
+    fn sage() {
+        let text = "[7, 4] linear code over GF(2)\n\n\
+                    [1 0 0 0|0 1 1]\n\
+                    [0 1 0 0|1 0 1]\n\
+                    [0 0 1 0|1 1 0]\n\
+                    [0 0 0 1|1 1 1]\n";
+        assert!(same_code(
+            &read_printed(text.as_bytes()).unwrap(),
+            &hamming()
+        ));
+        let text = "[7, 4] Hamming Code over Finite Field of size 2\n\
+                    [1, 0, 0, 0, 0, 1, 1]\n\
+                    [0, 1, 0, 0, 1, 0, 1]\n\
+                    [0, 0, 1, 0, 1, 1, 0]\n\
+                    [0, 0, 0, 1, 1, 1, 1]\n";
+        assert!(same_code(
+            &read_printed(text.as_bytes()).unwrap(),
+            &hamming()
+        ));
+    }
+
+    #[test]
+    fn errors() {
+        let (line, message) = parse_error(read_printed(
+            "[3, 1] Linear Code over GF(2)\n[1 1 1 1]\n".as_bytes(),
+        ));
+        assert_eq!(line, 1);
+        assert_eq!(
+            message,
+            "the code has length 3, but the matrix has 4 columns"
+        );
+        let (line, message) = parse_error(read_printed(
+            "[4, 2] Linear Code over GF(2)\n[1 1 1 1]\n".as_bytes(),
+        ));
+        assert_eq!(line, 1);
+        assert_eq!(message, "the code has dimension 2, but the matrix gives 1");
+        let (line, message) = parse_error(read_printed(
+            "[5, 2, 4] Linear Code over GF(2^2)\n".as_bytes(),
+        ));
+        assert_eq!(line, 1);
+        assert_eq!(
+            message,
+            "only binary codes are supported, not codes over GF(2^2)"
+        );
+        let (line, message) = parse_error(read_printed("Check matrix:\n[1 1]".as_bytes()));
+        assert_eq!(line, 1);
+        assert!(message.starts_with("unknown label"));
+        let (line, message) = parse_error(read_printed("[1 1 0]\n\n[1 1 0]\n".as_bytes()));
+        assert_eq!(line, 3);
+        assert_eq!(
+            message,
+            "the rows of the generator matrix should be independent"
+        );
+        let (line, message) = parse_error(read_printed("[1 1 0]\n[1 0]\n".as_bytes()));
+        assert_eq!(line, 2);
+        assert_eq!(message, "expected 3 entries, found 2");
+        let (line, _) = parse_error(read_printed("[x, 4] Linear Code\n".as_bytes()));
+        assert_eq!(line, 1);
+        let (line, _) = parse_error(read_printed("\n[2, 1] Linear Code\n".as_bytes()));
+        assert_eq!(line, 2);
+    }
+}
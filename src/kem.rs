@@ -0,0 +1,70 @@
+//! Key encapsulation mechanisms.
+//!
+//! A KEM establishes a shared secret: `encaps` produces a ciphertext and a
+//! secret from a public key, and `decaps` recovers the same secret from the
+//! ciphertext with the secret key.
+
+use bit_vector::BitVector;
+use rand::Rng;
+use tiny_keccak::Keccak;
+
+/// The length of shared secrets in bytes
+pub const SHARED_SECRET_BYTES: usize = 32;
+
+pub type SharedSecret = [u8; SHARED_SECRET_BYTES];
+
+pub trait Kem {
+    type PublicKey;
+    type SecretKey;
+    type Ciphertext;
+
+    fn keygen<R: Rng>(&self, rng: &mut R) -> (Self::PublicKey, Self::SecretKey);
+
+    fn encaps<R: Rng>(
+        &self,
+        public: &Self::PublicKey,
+        rng: &mut R,
+    ) -> (Self::Ciphertext, SharedSecret);
+
+    /// Recover the shared secret.
+    ///
+    /// Invalid ciphertexts are rejected implicitly: they yield a secret that
+    /// is unrelated to the one of any valid ciphertext.
+    fn decaps(&self, secret: &Self::SecretKey, ciphertext: &Self::Ciphertext) -> SharedSecret;
+}
+
+/// Hash `prefix || e || c` with SHAKE256, with vectors packed as bytes
+pub fn derive_secret(prefix: u8, error: &BitVector, ciphertext: &BitVector) -> SharedSecret {
+    let mut shake = Keccak::new_shake256();
+    shake.update(&[prefix]);
+    shake.update(&error.to_bytes());
+    shake.update(&ciphertext.to_bytes());
+    let mut secret = [0; SHARED_SECRET_BYTES];
+    shake.finalize(&mut secret);
+    secret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derive_secret_separates_inputs() {
+        let e = BitVector::from_support(10, &[1, 4]);
+        let c = BitVector::from_support(6, &[0]);
+        let secret = derive_secret(1, &e, &c);
+        assert_eq!(secret, derive_secret(1, &e, &c));
+        assert_ne!(secret, derive_secret(0, &e, &c));
+        assert_ne!(secret, derive_secret(1, &c, &e));
+    }
+
+    #[test]
+    fn shake256_empty() {
+        // SHAKE256 of the empty string
+        let mut shake = Keccak::new_shake256();
+        shake.update(&[]);
+        let mut out = [0; 4];
+        shake.finalize(&mut out);
+        assert_eq!(out, [0x46, 0xb9, 0xdd, 0x2b]);
+    }
+}
@@ -0,0 +1,35 @@
+//! # decoder
+//! Codes and decoding algorithms, with a focus on code-based cryptography.
+
+extern crate bit_vector;
+extern crate matrix;
+extern crate memmap;
+extern crate rand;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_bytes;
+extern crate tiny_keccak;
+extern crate zero_one;
+
+pub mod channel;
+pub mod constant_time;
+pub mod constant_weight;
+pub mod construction;
+pub mod distance;
+pub mod dudect;
+pub mod gf2m;
+pub mod goppa;
+pub mod import;
+pub mod kem;
+pub mod linear_code;
+pub mod mceliece;
+pub mod mdpc;
+pub mod niederreiter;
+pub mod rng;
+#[cfg(feature = "serde")]
+mod serialize;
+pub mod simulation;
+pub mod storage;
+pub mod weight_enumerator;
@@ -0,0 +1,595 @@
+//! Binary linear codes given by a generator or parity-check matrix, and
+//! generic decoders for them.
+//!
+//! These decoders make no assumptions about the structure of the code, so
+//! they work for any matrix read from a file, at the cost of speed: use the
+//! algebraic decoders in `goppa` and `mdpc` where the structure is known.
+
+use bit_vector::BitVector;
+use distance;
+use matrix::{BinaryError, BinaryFormat, BinaryReader, BinaryWriter, BitMatrix, Permutation};
+use rand::Rng;
+use std::sync::OnceLock;
+
+/// A binary linear `[n, k]` code.
+///
+/// Both matrices are kept: the rows of the generator matrix are a basis of
+/// the code and the parity-check matrix is the one it was given by, or the
+/// dual basis. The minimum distance is computed when first asked for.
+#[derive(Clone, Debug)]
+pub struct LinearCode {
+    generator: BitMatrix,
+    parity_check: BitMatrix,
+    minimum_distance: OnceLock<Option<usize>>,
+}
+
+impl PartialEq for LinearCode {
+    fn eq(&self, other: &LinearCode) -> bool {
+        self.generator == other.generator && self.parity_check == other.parity_check
+    }
+}
+
+impl LinearCode {
+    /// The code of words `c` with `H c = 0`; the rows of `H` may be dependent
+    pub fn from_parity_check(parity_check: BitMatrix) -> LinearCode {
+        LinearCode {
+            generator: parity_check.null_space(),
+            parity_check,
+            minimum_distance: OnceLock::new(),
+        }
+    }
+
+    /// The code spanned by the rows of `G`, which should be independent
+    pub fn from_generator(generator: BitMatrix) -> LinearCode {
+        assert_eq!(
+            generator.rank(),
+            generator.nrows(),
+            "The generator matrix should have full rank"
+        );
+        LinearCode {
+            parity_check: generator.null_space(),
+            generator,
+            minimum_distance: OnceLock::new(),
+        }
+    }
+
+    /// A random code with systematic generator matrix `[I_k | A]`
+    pub fn random<R: Rng>(length: usize, dimension: usize, rng: &mut R) -> LinearCode {
+        assert!(dimension <= length, "Dimension can not exceed the length");
+        let rows = (0..dimension)
+            .map(|i| {
+                let mut row = BitVector::from_support(dimension, &[i]);
+                let bits: Vec<bool> = (dimension..length).map(|_| rng.gen()).collect();
+                row.extend_from(&BitVector::from_bools(&bits));
+                row
+            })
+            .collect();
+        LinearCode::from_generator(BitMatrix::from_rows(length, rows))
+    }
+
+    /// An equivalent code with generator matrix `[I | A]` and parity-check
+    /// matrix `[A^T | I]`, and the permutation of the positions that maps
+    /// the codewords of this code to its codewords
+    pub fn to_systematic(&self) -> (LinearCode, Permutation) {
+        let systematic = self
+            .generator
+            .to_systematic()
+            .expect("The generator matrix has full rank");
+        let code = LinearCode {
+            parity_check: systematic.matrix.to_parity_check(),
+            generator: systematic.matrix,
+            minimum_distance: self.minimum_distance.clone(),
+        };
+        (code, systematic.permutation)
+    }
+
+    /// The dual code, of the words orthogonal to every codeword: its
+    /// generator and parity-check matrices are those of this code swapped
+    pub fn dual(&self) -> LinearCode {
+        if self.parity_check.nrows() != self.redundancy() {
+            // the parity checks are dependent, so they are no basis
+            return LinearCode::from_parity_check(self.generator.clone());
+        }
+        LinearCode {
+            generator: self.parity_check.clone(),
+            parity_check: self.generator.clone(),
+            minimum_distance: OnceLock::new(),
+        }
+    }
+
+    pub fn generator_matrix(&self) -> &BitMatrix {
+        &self.generator
+    }
+
+    pub fn parity_check_matrix(&self) -> &BitMatrix {
+        &self.parity_check
+    }
+
+    /// The code length `n`
+    pub fn length(&self) -> usize {
+        self.generator.ncols()
+    }
+
+    /// The code dimension `k`
+    pub fn dimension(&self) -> usize {
+        self.generator.nrows()
+    }
+
+    /// The number of independent parity checks, `n - k`
+    pub fn redundancy(&self) -> usize {
+        self.length() - self.dimension()
+    }
+
+    /// The rate `k / n`, which is zero for the code of length zero
+    pub fn rate(&self) -> f64 {
+        if self.length() == 0 {
+            return 0.0;
+        }
+        self.dimension() as f64 / self.length() as f64
+    }
+
+    /// Map a message of `k` bits to the codeword `m G`
+    pub fn encode(&self, message: &BitVector) -> BitVector {
+        assert_eq!(
+            message.len(),
+            self.dimension(),
+            "Message should match the code dimension"
+        );
+        self.generator.vector_mul(message)
+    }
+
+    /// The syndrome `H r` of a received word
+    pub fn syndrome(&self, received: &BitVector) -> BitVector {
+        assert_eq!(
+            received.len(),
+            self.length(),
+            "Vector should match the code length"
+        );
+        self.parity_check.mul_vector(received)
+    }
+
+    pub fn is_codeword(&self, word: &BitVector) -> bool {
+        self.syndrome(word).is_zero()
+    }
+
+    /// The minimum distance `d`, or `None` for the zero code.
+    ///
+    /// It is computed exactly with the Brouwer–Zimmermann algorithm, which
+    /// is only feasible for small codes, and then kept with the code. For
+    /// larger codes bound it with `distance::Stern` instead.
+    pub fn minimum_distance(&self) -> Option<usize> {
+        *self
+            .minimum_distance
+            .get_or_init(|| distance::brouwer_zimmermann(&self.generator))
+    }
+
+    /// The number of errors `(d - 1) / 2` the code is guaranteed to correct
+    pub fn correction_radius(&self) -> Option<usize> {
+        self.minimum_distance().map(|d| d.saturating_sub(1) / 2)
+    }
+
+    /// An upper bound on the minimum distance: the lowest weight found by
+    /// `iterations` of Stern's search with `p = 1` and `l = 4`. Returns
+    /// `None` for the zero code.
+    pub fn estimate_minimum_distance<R: Rng>(
+        &self,
+        iterations: usize,
+        rng: &mut R,
+    ) -> Option<usize> {
+        let stern = distance::Stern {
+            iterations,
+            p: 1,
+            l: 4,
+        };
+        stern.search(self, rng).map(|codeword| codeword.weight())
+    }
+}
+
+/// The generator matrix followed by the parity-check matrix
+impl BinaryFormat for LinearCode {
+    const KIND: u16 = 16;
+
+    fn write_payload(&self, writer: &mut BinaryWriter) {
+        self.generator.write_payload(writer);
+        self.parity_check.write_payload(writer);
+    }
+
+    fn read_payload(reader: &mut BinaryReader) -> Result<LinearCode, BinaryError> {
+        let generator = BitMatrix::read_payload(reader)?;
+        let parity_check = BitMatrix::read_payload(reader)?;
+        if generator.ncols() != parity_check.ncols() {
+            return Err(BinaryError::Invalid(
+                "the generator and parity-check matrix should have the same length".to_string(),
+            ));
+        }
+        if generator.rank() != generator.nrows() {
+            return Err(BinaryError::Invalid(
+                "the generator matrix should have full rank".to_string(),
+            ));
+        }
+        if generator.rank() + parity_check.rank() != generator.ncols() {
+            return Err(BinaryError::Invalid(
+                "the parity checks should leave the dimension of the code".to_string(),
+            ));
+        }
+        if generator
+            .rows()
+            .iter()
+            .any(|row| !parity_check.mul_vector(row).is_zero())
+        {
+            return Err(BinaryError::Invalid(
+                "the generator matrix should satisfy the parity checks".to_string(),
+            ));
+        }
+        Ok(LinearCode {
+            generator,
+            parity_check,
+            minimum_distance: OnceLock::new(),
+        })
+    }
+}
+
+/// A decoder that finds a low-weight error from its syndrome
+pub trait Decoder {
+    /// Find an error with the given syndrome, or `None` on a decoding failure
+    fn decode<R: Rng>(
+        &self,
+        code: &LinearCode,
+        syndrome: &BitVector,
+        rng: &mut R,
+    ) -> Option<BitVector>;
+}
+
+/// Try all errors in order of increasing weight, which finds an error of
+/// minimum weight: maximum-likelihood decoding on the binary symmetric
+/// channel, feasible for small lengths and weights only.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Exhaustive {
+    pub max_weight: usize,
+}
+
+impl Decoder for Exhaustive {
+    fn decode<R: Rng>(
+        &self,
+        code: &LinearCode,
+        syndrome: &BitVector,
+        _rng: &mut R,
+    ) -> Option<BitVector> {
+        let h = code.parity_check_matrix();
+        let columns = h.transpose();
+        let n = code.length();
+        for weight in 0..=self.max_weight.min(n) {
+            // the positions of the error, in increasing order
+            let mut positions: Vec<usize> = (0..weight).collect();
+            loop {
+                let mut sum = BitVector::zero(h.nrows());
+                for &j in &positions {
+                    sum ^= columns.row(j);
+                }
+                if &sum == syndrome {
+                    return Some(BitVector::from_support(n, &positions));
+                }
+                if !next_combination(&mut positions, n) {
+                    break;
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Advance `positions`, increasing and below `n`, to the next combination
+/// in lexicographic order, or return `false` after the last one
+pub(crate) fn next_combination(positions: &mut [usize], n: usize) -> bool {
+    let weight = positions.len();
+    let mut i = weight;
+    while i > 0 && positions[i - 1] == n - weight + i - 1 {
+        i -= 1;
+    }
+    if i == 0 {
+        return false;
+    }
+    positions[i - 1] += 1;
+    for k in i..weight {
+        positions[k] = positions[k - 1] + 1;
+    }
+    true
+}
+
+/// Gallager's hard-decision bit-flipping decoder: flip the positions that
+/// are in the largest number of unsatisfied parity checks. Effective for
+/// codes with a sparse parity-check matrix.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BitFlipping {
+    pub iterations: usize,
+}
+
+impl Decoder for BitFlipping {
+    fn decode<R: Rng>(
+        &self,
+        code: &LinearCode,
+        syndrome: &BitVector,
+        _rng: &mut R,
+    ) -> Option<BitVector> {
+        let h = code.parity_check_matrix();
+        let columns = h.transpose();
+        let mut syndrome = syndrome.clone();
+        let mut error = BitVector::zero(code.length());
+        for _ in 0..self.iterations {
+            if syndrome.is_zero() {
+                break;
+            }
+            let counters: Vec<usize> = columns
+                .rows()
+                .iter()
+                .map(|column| {
+                    column
+                        .support()
+                        .filter(|&check| syndrome.get(check))
+                        .count()
+                })
+                .collect();
+            let max = counters.iter().cloned().max().unwrap_or(0);
+            if max == 0 {
+                break;
+            }
+            for (j, &counter) in counters.iter().enumerate() {
+                if counter == max {
+                    error.flip(j);
+                    syndrome ^= columns.row(j);
+                }
+            }
+        }
+        if syndrome.is_zero() {
+            Some(error)
+        } else {
+            None
+        }
+    }
+}
+
+/// Prange's information-set decoding: guess a set of `n - k` positions that
+/// contains the whole error, and solve for it by elimination.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InformationSet {
+    pub iterations: usize,
+    /// The largest error weight to accept
+    pub max_weight: usize,
+}
+
+impl Decoder for InformationSet {
+    fn decode<R: Rng>(
+        &self,
+        code: &LinearCode,
+        syndrome: &BitVector,
+        rng: &mut R,
+    ) -> Option<BitVector> {
+        let h = code.parity_check_matrix();
+        let columns = h.transpose();
+        let n = code.length();
+        let mut permutation: Vec<usize> = (0..n).collect();
+        for _ in 0..self.iterations {
+            rng.shuffle(&mut permutation);
+            // eliminate on [H P | s]
+            let mut augmented: Vec<BitVector> = permutation
+                .iter()
+                .map(|&j| columns.row(j).clone())
+                .collect();
+            augmented.push(syndrome.clone());
+            let mut augmented = BitMatrix::from_rows(h.nrows(), augmented).transpose();
+            let pivots = augmented.reduce_row_echelon();
+            if pivots.last() == Some(&n) {
+                // the syndrome is not in the column space of H
+                return None;
+            }
+            let mut error = BitVector::zero(n);
+            for (row, &col) in pivots.iter().enumerate() {
+                if augmented.get(row, n) {
+                    error.set(permutation[col], true);
+                }
+            }
+            if error.weight() <= self.max_weight {
+                return Some(error);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use rand::{seq, SeedableRng, XorShiftRng};
+
+    fn rng() -> XorShiftRng {
+        XorShiftRng::from_seed([1, 2, 3, 4])
+    }
+
+    /// The [7, 4, 3] Hamming code: column j of H is j + 1 in binary
+    pub(crate) fn hamming() -> LinearCode {
+        let rows = (0..3)
+            .map(|b| {
+                let support: Vec<usize> = (0..7).filter(|j| ((j + 1) >> b) & 1 == 1).collect();
+                BitVector::from_support(7, &support)
+            })
+            .collect();
+        LinearCode::from_parity_check(BitMatrix::from_rows(7, rows))
+    }
+
+    /// The [23, 12, 7] Golay code, generated by the shifts of
+    /// `x^11 + x^10 + x^6 + x^5 + x^4 + x^2 + 1`
+    pub(crate) fn golay() -> LinearCode {
+        let rows = (0..12)
+            .map(|shift| {
+                let support: Vec<usize> =
+                    [0, 2, 4, 5, 6, 10, 11].iter().map(|i| i + shift).collect();
+                BitVector::from_support(23, &support)
+            })
+            .collect();
+        LinearCode::from_generator(BitMatrix::from_rows(23, rows))
+    }
+
+    #[test]
+    fn hamming_parameters() {
+        let code = hamming();
+        assert_eq!(code.length(), 7);
+        assert_eq!(code.dimension(), 4);
+        assert_eq!(code.redundancy(), 3);
+        assert_eq!(code.rate(), 4.0 / 7.0);
+        assert_eq!(LinearCode::random(0, 0, &mut rng()).rate(), 0.0);
+        assert_eq!(code.estimate_minimum_distance(10, &mut rng()), Some(3));
+        assert_eq!(code.minimum_distance(), Some(3));
+        assert_eq!(code.correction_radius(), Some(1));
+        let (systematic, _) = code.to_systematic();
+        assert_eq!(systematic.minimum_distance(), Some(3));
+        assert_eq!(
+            LinearCode::random(4, 0, &mut rng()).minimum_distance(),
+            None
+        );
+    }
+
+    #[test]
+    fn generator_and_parity_check_agree() {
+        let code = LinearCode::random(20, 8, &mut rng());
+        assert_eq!(code.dimension(), 8);
+        assert_eq!(code.parity_check_matrix().nrows(), 12);
+        let dual = LinearCode::from_parity_check(code.generator_matrix().clone());
+        assert_eq!(dual.dimension(), 12);
+        let message = BitVector::from_support(8, &[0, 3, 7]);
+        let codeword = code.encode(&message);
+        assert_eq!(codeword.slice(0, 8), message);
+        assert!(code.is_codeword(&codeword));
+        for row in dual.generator_matrix().rows() {
+            assert!(!codeword.dot(row));
+        }
+    }
+
+    #[test]
+    fn systematic_form() {
+        let code = hamming();
+        let (systematic, permutation) = code.to_systematic();
+        assert_eq!(systematic.dimension(), 4);
+        let g = systematic.generator_matrix();
+        assert_eq!(g.columns(0, 4), BitMatrix::identity(4));
+        assert_eq!(systematic.parity_check_matrix().to_generator(), *g);
+        for row in code.generator_matrix().rows() {
+            assert!(systematic.is_codeword(&permutation.permute_bits(row)));
+        }
+        let message = BitVector::from_support(4, &[1, 2]);
+        assert_eq!(systematic.encode(&message).slice(0, 4), message);
+    }
+
+    #[test]
+    fn redundant_parity_checks() {
+        let h = hamming().parity_check_matrix().clone();
+        let mut rows = h.rows().to_vec();
+        rows.push(&rows[0] ^ &rows[1]);
+        let code = LinearCode::from_parity_check(BitMatrix::from_rows(7, rows));
+        assert_eq!(code.dimension(), 4);
+        assert_eq!(code.redundancy(), 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn dependent_generator() {
+        let row = BitVector::from_support(3, &[0, 1]);
+        LinearCode::from_generator(BitMatrix::from_rows(3, vec![row.clone(), row]));
+    }
+
+    #[test]
+    fn binary_roundtrip() {
+        let code = hamming();
+        assert_eq!(LinearCode::from_binary(&code.to_binary()).unwrap(), code);
+    }
+
+    #[test]
+    fn binary_rejects_inconsistent_matrices() {
+        let invalid = |generator: BitMatrix, parity_check: BitMatrix| {
+            let code = LinearCode {
+                generator,
+                parity_check,
+                minimum_distance: OnceLock::new(),
+            };
+            match LinearCode::from_binary(&code.to_binary()) {
+                Err(BinaryError::Invalid(message)) => message,
+                other => panic!("expected invalid data, got {:?}", other),
+            }
+        };
+        let code = hamming();
+        let (g, h) = (code.generator_matrix(), code.parity_check_matrix());
+
+        let mut dependent = g.clone();
+        dependent.stack(BitMatrix::from_rows(7, vec![g.row(0) ^ g.row(1)]));
+        assert_eq!(
+            invalid(dependent, h.clone()),
+            "the generator matrix should have full rank"
+        );
+
+        let mut flipped = g.clone();
+        flipped.set(0, 0, !flipped.get(0, 0));
+        assert_eq!(
+            invalid(flipped, h.clone()),
+            "the generator matrix should satisfy the parity checks"
+        );
+
+        let too_few = BitMatrix::from_rows(7, h.rows()[..2].to_vec());
+        assert_eq!(
+            invalid(g.clone(), too_few),
+            "the parity checks should leave the dimension of the code"
+        );
+    }
+
+    #[test]
+    fn exhaustive_decoding() {
+        let code = hamming();
+        let decoder = Exhaustive { max_weight: 1 };
+        let mut rng = rng();
+        for j in 0..7 {
+            let error = BitVector::from_support(7, &[j]);
+            let syndrome = code.syndrome(&error);
+            assert_eq!(decoder.decode(&code, &syndrome, &mut rng), Some(error));
+        }
+        let zero = BitVector::zero(3);
+        assert_eq!(
+            decoder.decode(&code, &zero, &mut rng),
+            Some(BitVector::zero(7))
+        );
+        let code = LinearCode::random(12, 2, &mut rng);
+        let error = BitVector::from_support(12, &[2, 5, 9]);
+        let found = Exhaustive { max_weight: 3 }
+            .decode(&code, &code.syndrome(&error), &mut rng)
+            .unwrap();
+        assert!(found.weight() <= 3);
+        assert_eq!(code.syndrome(&found), code.syndrome(&error));
+    }
+
+    #[test]
+    fn bit_flipping_decoding() {
+        let code = hamming();
+        let decoder = BitFlipping { iterations: 5 };
+        // the last column of H is in all three checks
+        let error = BitVector::from_support(7, &[6]);
+        assert_eq!(
+            decoder.decode(&code, &code.syndrome(&error), &mut rng()),
+            Some(error)
+        );
+    }
+
+    #[test]
+    fn information_set_decoding() {
+        let mut rng = rng();
+        let code = LinearCode::random(60, 20, &mut rng);
+        let decoder = InformationSet {
+            iterations: 1000,
+            max_weight: 3,
+        };
+        for _ in 0..5 {
+            let error = BitVector::from_support(60, &seq::sample_indices(&mut rng, 60, 3));
+            let found = decoder
+                .decode(&code, &code.syndrome(&error), &mut rng)
+                .unwrap();
+            assert!(found.weight() <= 3);
+            assert_eq!(code.syndrome(&found), code.syndrome(&error));
+        }
+    }
+}
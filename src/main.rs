@@ -1,3 +1,529 @@
+//! Command-line interface to the decoders.
+//!
+//! Matrices and lists of words are read from files in the text format of
+//! the `matrix` crate: an optional `matrix <rows> <cols>` header and one row
+//! or word of `0`/`1` characters per line. Matrices in the binary format
+//! are recognised by their header. The matrix of a code may also be given in
+//! alist format, or with its rows in brackets as printed by Sage and MAGMA.
+
+extern crate bit_vector;
+extern crate clap;
+extern crate decoder;
+extern crate matrix;
+extern crate rand;
+
+use bit_vector::BitVector;
+use clap::{App, AppSettings, Arg, ArgGroup, ArgMatches, SubCommand};
+use decoder::channel::{Awgn, BinarySymmetric, FixedWeight};
+use decoder::linear_code::{BitFlipping, Decoder, Exhaustive, InformationSet, LinearCode};
+use decoder::rng;
+use decoder::simulation::{self, Simulation};
+use decoder::storage;
+use matrix::{BinaryFormat, BitMatrix, SparseBinaryMatrix};
+use rand::XorShiftRng;
+use std::fs;
+use std::io;
+use std::process;
+use std::thread;
+use std::time::Instant;
+
 fn main() {
-    println!("Hello, world!");
+    let matches = app().get_matches();
+    let result = match matches.subcommand() {
+        ("encode", Some(args)) => encode(args),
+        ("decode", Some(args)) => decode(args),
+        ("simulate", Some(args)) => simulate(args),
+        ("syndrome", Some(args)) => syndrome(args),
+        ("info", Some(args)) => info(args),
+        ("random-code", Some(args)) => random_code(args),
+        ("convert", Some(args)) => convert(args),
+        _ => unreachable!("a subcommand is required"),
+    };
+    if let Err(message) = result {
+        eprintln!("error: {}", message);
+        process::exit(1);
+    }
+}
+
+fn app() -> App<'static, 'static> {
+    let code_args = || {
+        vec![
+            Arg::with_name("generator")
+                .short("g")
+                .long("generator")
+                .value_name("FILE")
+                .help("Read the code as a generator matrix"),
+            Arg::with_name("parity-check")
+                .short("H")
+                .long("parity-check")
+                .value_name("FILE")
+                .help("Read the code as a parity-check matrix"),
+            Arg::with_name("format")
+                .short("f")
+                .long("format")
+                .possible_values(&["text", "alist", "bracketed"])
+                .default_value("text")
+                .help("The format of the matrix of the code"),
+        ]
+    };
+    let code_group = || {
+        ArgGroup::with_name("code")
+            .args(&["generator", "parity-check"])
+            .required(true)
+    };
+    let seed = || {
+        Arg::with_name("seed")
+            .long("seed")
+            .value_name("SEED")
+            .default_value("1")
+            .help("Seed the random number generator; the same seed repeats a run exactly")
+    };
+    let decoder_args = || {
+        vec![
+            Arg::with_name("algorithm")
+                .short("a")
+                .long("algorithm")
+                .possible_values(&["information-set", "bit-flipping", "exhaustive"])
+                .default_value("information-set"),
+            Arg::with_name("max-weight")
+                .short("w")
+                .long("max-weight")
+                .value_name("WEIGHT")
+                .default_value("1")
+                .help("The largest error weight to search for"),
+            Arg::with_name("iterations")
+                .short("i")
+                .long("iterations")
+                .value_name("N")
+                .default_value("1000")
+                .help("The number of iterations of iterative decoders"),
+        ]
+    };
+    let words = |help| Arg::with_name("WORDS").required(true).help(help);
+
+    App::new("decoder")
+        .about("Encode, decode and inspect binary linear codes")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("encode")
+                .about("Encode messages of k bits")
+                .args(&code_args())
+                .group(code_group())
+                .arg(words("File with one message per line")),
+        )
+        .subcommand(
+            SubCommand::with_name("decode")
+                .about("Decode received words")
+                .args(&code_args())
+                .group(code_group())
+                .arg(words("File with one received word per line"))
+                .args(&decoder_args())
+                .arg(seed()),
+        )
+        .subcommand(
+            SubCommand::with_name("simulate")
+                .about("Estimate frame and bit error rates on a channel, as CSV")
+                .args(&code_args())
+                .group(code_group())
+                .args(&decoder_args())
+                .arg(
+                    Arg::with_name("channel")
+                        .short("c")
+                        .long("channel")
+                        .possible_values(&["bsc", "awgn", "fixed-weight"])
+                        .default_value("bsc")
+                        .help("The binary symmetric, hard-decision AWGN or fixed-weight channel"),
+                )
+                .arg(
+                    Arg::with_name("POINTS")
+                        .required(true)
+                        .multiple(true)
+                        .help("The crossover probabilities, Eb/N0 values in dB or error weights"),
+                )
+                .arg(
+                    Arg::with_name("target-errors")
+                        .short("e")
+                        .long("target-errors")
+                        .value_name("N")
+                        .default_value("100")
+                        .help("Stop at a point after this many frame errors"),
+                )
+                .arg(
+                    Arg::with_name("max-frames")
+                        .short("n")
+                        .long("max-frames")
+                        .value_name("N")
+                        .default_value("100000")
+                        .help("Stop at a point after this many frames"),
+                )
+                .arg(
+                    Arg::with_name("threads")
+                        .short("t")
+                        .long("threads")
+                        .value_name("N")
+                        .help("The number of worker threads [default: one per CPU]"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Write the CSV to a file instead of standard output"),
+                )
+                .arg(seed()),
+        )
+        .subcommand(
+            SubCommand::with_name("syndrome")
+                .about("Compute the syndromes of words")
+                .args(&code_args())
+                .group(code_group())
+                .arg(words("File with one word per line")),
+        )
+        .subcommand(
+            SubCommand::with_name("info")
+                .about("Show the parameters of a code")
+                .args(&code_args())
+                .group(code_group())
+                .arg(
+                    Arg::with_name("iterations")
+                        .short("i")
+                        .long("iterations")
+                        .value_name("N")
+                        .default_value("100")
+                        .help("The number of information sets for the distance estimate"),
+                )
+                .arg(
+                    Arg::with_name("exact")
+                        .short("x")
+                        .long("exact")
+                        .help("Compute the exact minimum distance, which is slow for large codes"),
+                )
+                .arg(seed()),
+        )
+        .subcommand(
+            SubCommand::with_name("random-code")
+                .about("Print the generator matrix of a random code")
+                .arg(Arg::with_name("LENGTH").required(true))
+                .arg(Arg::with_name("DIMENSION").required(true))
+                .arg(
+                    Arg::with_name("parity-check")
+                        .short("H")
+                        .long("parity-check")
+                        .help("Print a parity-check matrix instead"),
+                )
+                .arg(seed()),
+        )
+        .subcommand(
+            SubCommand::with_name("convert")
+                .about("Convert a matrix between the text and binary formats")
+                .arg(Arg::with_name("INPUT").required(true))
+                .arg(Arg::with_name("OUTPUT").required(true))
+                .arg(
+                    Arg::with_name("binary")
+                        .short("b")
+                        .long("binary")
+                        .help("Write the binary format instead of text"),
+                ),
+        )
+}
+
+fn encode(args: &ArgMatches) -> Result<(), String> {
+    let code = read_code(args)?;
+    let messages = read_words(args.value_of("WORDS").unwrap(), code.dimension())?;
+    for message in messages.rows() {
+        println!("{}", format_word(&code.encode(message)));
+    }
+    Ok(())
+}
+
+fn syndrome(args: &ArgMatches) -> Result<(), String> {
+    let code = read_code(args)?;
+    let words = read_words(args.value_of("WORDS").unwrap(), code.length())?;
+    for word in words.rows() {
+        println!("{}", format_word(&code.syndrome(word)));
+    }
+    Ok(())
+}
+
+fn decode(args: &ArgMatches) -> Result<(), String> {
+    let code = read_code(args)?;
+    let words = read_words(args.value_of("WORDS").unwrap(), code.length())?;
+    let max_weight = parse_number(args, "max-weight")?;
+    let iterations = parse_number(args, "iterations")?;
+    let mut rng = rng(args)?;
+    let algorithm = args.value_of("algorithm").unwrap();
+
+    let start = Instant::now();
+    let mut decoded = 0;
+    let mut total_weight = 0;
+    for received in words.rows() {
+        let syndrome = code.syndrome(received);
+        let error = match algorithm {
+            "exhaustive" => Exhaustive { max_weight }.decode(&code, &syndrome, &mut rng),
+            "bit-flipping" => BitFlipping { iterations }.decode(&code, &syndrome, &mut rng),
+            _ => InformationSet {
+                iterations,
+                max_weight,
+            }
+            .decode(&code, &syndrome, &mut rng),
+        };
+        match error {
+            Some(error) => {
+                decoded += 1;
+                total_weight += error.weight();
+                println!("codeword: {}", format_word(&(received ^ &error)));
+                println!("error:    {}", format_word(&error));
+            }
+            None => println!("failure"),
+        }
+    }
+    let elapsed = start.elapsed();
+
+    eprintln!("decoded {} of {} words", decoded, words.nrows());
+    if decoded > 0 {
+        eprintln!(
+            "average error weight {:.2}",
+            total_weight as f64 / decoded as f64
+        );
+    }
+    eprintln!(
+        "{:.3} s, {:.3} ms per word",
+        elapsed.as_secs_f64(),
+        1000.0 * elapsed.as_secs_f64() / words.nrows().max(1) as f64
+    );
+    Ok(())
+}
+
+fn simulate(args: &ArgMatches) -> Result<(), String> {
+    let code = read_code(args)?;
+    let max_weight = parse_number(args, "max-weight")?;
+    let iterations = parse_number(args, "iterations")?;
+    match args.value_of("algorithm").unwrap() {
+        "exhaustive" => sweep(args, &code, &Exhaustive { max_weight }),
+        "bit-flipping" => sweep(args, &code, &BitFlipping { iterations }),
+        _ => sweep(
+            args,
+            &code,
+            &InformationSet {
+                iterations,
+                max_weight,
+            },
+        ),
+    }
+}
+
+/// Simulate the decoder at every point on the channel of the arguments
+fn sweep<D: Decoder + Sync>(
+    args: &ArgMatches,
+    code: &LinearCode,
+    decoder: &D,
+) -> Result<(), String> {
+    let channel = args.value_of("channel").unwrap();
+    let mut points = Vec::new();
+    for value in args.values_of("POINTS").unwrap() {
+        let point: f64 = value
+            .parse()
+            .map_err(|_| format!("points should be numbers, not {:?}", value))?;
+        let valid = match channel {
+            "awgn" => point.is_finite(),
+            "fixed-weight" => point.fract() == 0.0 && (0.0..=code.length() as f64).contains(&point),
+            _ => (0.0..=1.0).contains(&point),
+        };
+        if !valid {
+            return Err(format!(
+                "{} is not a valid point for the {} channel",
+                value, channel
+            ));
+        }
+        points.push(point);
+    }
+    let threads = match args.value_of("threads") {
+        Some(_) => parse_number(args, "threads")?.max(1),
+        None => thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+    let simulation = Simulation {
+        target_errors: parse_number(args, "target-errors")?,
+        max_frames: parse_number(args, "max-frames")?,
+        threads,
+        ..Simulation::default()
+    };
+    let mut rng = rng(args)?;
+
+    let start = Instant::now();
+    let (parameter, results) = match channel {
+        "awgn" => (
+            "ebn0",
+            simulation.sweep(
+                code,
+                decoder,
+                &points,
+                |ebn0| Awgn::from_ebn0(ebn0, code.rate()),
+                &mut rng,
+            ),
+        ),
+        "fixed-weight" => (
+            "weight",
+            simulation.sweep(
+                code,
+                decoder,
+                &points,
+                |weight| FixedWeight {
+                    weight: weight as usize,
+                },
+                &mut rng,
+            ),
+        ),
+        _ => (
+            "crossover",
+            simulation.sweep(
+                code,
+                decoder,
+                &points,
+                |crossover| BinarySymmetric { crossover },
+                &mut rng,
+            ),
+        ),
+    };
+    let frames: usize = results.iter().map(|&(_, rates)| rates.frames).sum();
+    eprintln!(
+        "{} frames on {} threads in {:.3} s",
+        frames,
+        threads,
+        start.elapsed().as_secs_f64()
+    );
+
+    match args.value_of("output") {
+        Some(path) => fs::File::create(path)
+            .and_then(|mut file| simulation::write_csv(&mut file, parameter, &results))
+            .map_err(|e| format!("{}: {}", path, e)),
+        None => {
+            let stdout = io::stdout();
+            simulation::write_csv(&mut stdout.lock(), parameter, &results)
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+fn info(args: &ArgMatches) -> Result<(), String> {
+    let code = read_code(args)?;
+    let iterations = parse_number(args, "iterations")?;
+    let mut rng = rng(args)?;
+    println!("n = {}", code.length());
+    println!("k = {}", code.dimension());
+    println!("rate = {:.4}", code.rate());
+    let distance = if args.is_present("exact") {
+        code.minimum_distance()
+    } else {
+        code.estimate_minimum_distance(iterations, &mut rng)
+    };
+    match distance {
+        Some(d) if args.is_present("exact") => {
+            println!("d = {}", d);
+            if let Some(t) = code.correction_radius() {
+                println!("corrects {} errors", t);
+            }
+        }
+        Some(d) => println!("d <= {}", d),
+        None => println!("d undefined for the zero code"),
+    }
+    Ok(())
+}
+
+fn random_code(args: &ArgMatches) -> Result<(), String> {
+    let length = parse_number(args, "LENGTH")?;
+    let dimension = parse_number(args, "DIMENSION")?;
+    if dimension > length {
+        return Err("the dimension can not exceed the length".to_string());
+    }
+    let code = LinearCode::random(length, dimension, &mut rng(args)?);
+    let matrix = if args.is_present("parity-check") {
+        code.parity_check_matrix()
+    } else {
+        code.generator_matrix()
+    };
+    let stdout = io::stdout();
+    matrix
+        .write_text(&mut stdout.lock())
+        .map_err(|e| e.to_string())
+}
+
+fn convert(args: &ArgMatches) -> Result<(), String> {
+    let matrix = read_matrix(args.value_of("INPUT").unwrap())?;
+    let output = args.value_of("OUTPUT").unwrap();
+    let bytes = if args.is_present("binary") {
+        matrix.to_binary()
+    } else {
+        matrix.to_text().into_bytes()
+    };
+    fs::write(output, bytes).map_err(|e| format!("{}: {}", output, e))
+}
+
+fn read_code(args: &ArgMatches) -> Result<LinearCode, String> {
+    let format = args.value_of("format").unwrap();
+    if let Some(path) = args.value_of("parity-check") {
+        let h = read_code_matrix(path, format)?;
+        return Ok(LinearCode::from_parity_check(h));
+    }
+    let g = read_code_matrix(args.value_of("generator").unwrap(), format)?;
+    if g.rank() != g.nrows() {
+        return Err("the rows of the generator matrix should be independent".to_string());
+    }
+    Ok(LinearCode::from_generator(g))
+}
+
+fn read_matrix(path: &str) -> Result<BitMatrix, String> {
+    let bytes = storage::map(path).map_err(|e| format!("{}: {}", path, e))?;
+    if matrix::is_binary(&bytes) {
+        BitMatrix::from_binary(&bytes).map_err(|e| format!("{}: {}", path, e))
+    } else {
+        BitMatrix::read_text(&bytes[..]).map_err(|e| format!("{}: {}", path, e))
+    }
+}
+
+fn read_code_matrix(path: &str, format: &str) -> Result<BitMatrix, String> {
+    let result = match format {
+        "alist" => {
+            let bytes = storage::map(path).map_err(|e| format!("{}: {}", path, e))?;
+            SparseBinaryMatrix::read_alist(&bytes[..]).map(|h| BitMatrix::from_sparse(&h))
+        }
+        "bracketed" => {
+            let bytes = storage::map(path).map_err(|e| format!("{}: {}", path, e))?;
+            BitMatrix::read_bracketed(&bytes[..])
+        }
+        _ => return read_matrix(path),
+    };
+    result.map_err(|e| format!("{}: {}", path, e))
+}
+
+/// Read a list of words, one per row, that should have the given length
+fn read_words(path: &str, length: usize) -> Result<BitMatrix, String> {
+    let words = read_matrix(path)?;
+    if words.nrows() > 0 && words.ncols() != length {
+        return Err(format!(
+            "{}: expected words of {} bits, found {}",
+            path,
+            length,
+            words.ncols()
+        ));
+    }
+    Ok(words)
+}
+
+fn format_word(word: &BitVector) -> String {
+    word.iter().map(|bit| if bit { '1' } else { '0' }).collect()
+}
+
+fn parse_number(args: &ArgMatches, name: &str) -> Result<usize, String> {
+    let value = args.value_of(name).unwrap();
+    value
+        .parse()
+        .map_err(|_| format!("{} should be a number, not {:?}", name, value))
+}
+
+fn rng(args: &ArgMatches) -> Result<XorShiftRng, String> {
+    let value = args.value_of("seed").unwrap();
+    let seed: u64 = value
+        .parse()
+        .map_err(|_| format!("seed should be a number, not {:?}", value))?;
+    Ok(rng::from_seed(seed))
 }
@@ -0,0 +1,428 @@
+//! The Classic McEliece key encapsulation mechanism.
+//!
+//! The secret key is a random binary Goppa code; the public key is its
+//! parity-check matrix in systematic form `[I_{mt} | T]`, of which only `T`
+//! is stored. A ciphertext is the syndrome `[I | T] e` of a random error `e`
+//! of weight `t`, which the owner of the secret key recovers by padding the
+//! syndrome with zeros and running the algebraic decoder of the Goppa code.
+//!
+//! Keys and ciphertexts follow the structure of the Classic McEliece
+//! specification, but the randomness is not derived through SHAKE, so they
+//! are not interoperable with the reference implementation.
+
+use bit_vector::BitVector;
+use gf2m::Field;
+use goppa::GoppaCode;
+use matrix::{BinaryError, BinaryFormat, BinaryReader, BinaryWriter, BitMatrix};
+use rand::{seq, Rng};
+
+/// A Classic McEliece parameter set
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Parameters {
+    /// The extension degree of the field GF(2^m)
+    pub m: u32,
+    /// The code length
+    pub n: usize,
+    /// The number of errors, equal to the degree of the Goppa polynomial
+    pub t: usize,
+    /// The modulus defining GF(2^m), as bits
+    pub field_polynomial: u32,
+}
+
+impl Parameters {
+    /// The number of parity checks, `mt`
+    pub fn redundancy(&self) -> usize {
+        self.m as usize * self.t
+    }
+
+    /// The code dimension, `n - mt`
+    pub fn dimension(&self) -> usize {
+        self.n - self.redundancy()
+    }
+
+    pub fn field(&self) -> Field {
+        Field::new(self.m, self.field_polynomial)
+    }
+
+    fn write(&self, writer: &mut BinaryWriter) {
+        writer.put_u32(self.m);
+        writer.put_usize(self.n);
+        writer.put_usize(self.t);
+        writer.put_u32(self.field_polynomial);
+    }
+
+    fn read(reader: &mut BinaryReader) -> Result<Parameters, BinaryError> {
+        let m = reader.get_u32()?;
+        let n = reader.get_u64()? as usize;
+        let t = reader.get_u64()? as usize;
+        let field_polynomial = reader.get_u32()?;
+        let params = Parameters {
+            m,
+            n,
+            t,
+            field_polynomial,
+        };
+        let valid = Field::try_new(m, field_polynomial).is_some()
+            && t > 0
+            && t.checked_mul(m as usize).is_some_and(|r| r < n)
+            && n <= 1 << m;
+        if !valid {
+            return Err(BinaryError::Invalid(format!(
+                "invalid parameter set {:?}",
+                params
+            )));
+        }
+        Ok(params)
+    }
+}
+
+/// `mceliece348864`
+pub const MCELIECE_348864: Parameters = Parameters {
+    m: 12,
+    n: 3488,
+    t: 64,
+    field_polynomial: 0x1009,
+};
+
+/// `mceliece460896`
+pub const MCELIECE_460896: Parameters = Parameters {
+    m: 13,
+    n: 4608,
+    t: 96,
+    field_polynomial: 0x201B,
+};
+
+/// `mceliece6688128`
+pub const MCELIECE_6688128: Parameters = Parameters {
+    m: 13,
+    n: 6688,
+    t: 128,
+    field_polynomial: 0x201B,
+};
+
+/// `mceliece6960119`
+pub const MCELIECE_6960119: Parameters = Parameters {
+    m: 13,
+    n: 6960,
+    t: 119,
+    field_polynomial: 0x201B,
+};
+
+/// `mceliece8192128`
+pub const MCELIECE_8192128: Parameters = Parameters {
+    m: 13,
+    n: 8192,
+    t: 128,
+    field_polynomial: 0x201B,
+};
+
+/// A small parameter set for experiments and tests; offers no security
+pub const TOY: Parameters = Parameters {
+    m: 8,
+    n: 128,
+    t: 8,
+    field_polynomial: 0x11B,
+};
+
+/// The non-identity part `T` of the systematic parity-check matrix
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicKey {
+    params: Parameters,
+    matrix: BitMatrix,
+}
+
+impl PublicKey {
+    pub fn parameters(&self) -> Parameters {
+        self.params
+    }
+
+    /// The `mt x (n - mt)` matrix `T`
+    pub fn matrix(&self) -> &BitMatrix {
+        &self.matrix
+    }
+
+    /// The full parity-check matrix `[I | T]`
+    pub fn parity_check_matrix(&self) -> BitMatrix {
+        let redundancy = self.params.redundancy();
+        let rows = (0..redundancy)
+            .map(|i| {
+                let mut row = BitVector::from_support(redundancy, &[i]);
+                row.extend_from(self.matrix.row(i));
+                row
+            })
+            .collect();
+        BitMatrix::from_rows(self.params.n, rows)
+    }
+
+    /// Compute the syndrome `[I | T] e` of an error vector
+    pub fn encrypt(&self, error: &BitVector) -> BitVector {
+        assert_eq!(
+            error.len(),
+            self.params.n,
+            "Vector should match the code length"
+        );
+        let redundancy = self.params.redundancy();
+        let mut syndrome = self
+            .matrix
+            .mul_vector(&error.slice(redundancy, self.params.dimension()));
+        syndrome ^= &error.slice(0, redundancy);
+        syndrome
+    }
+}
+
+/// The parameters followed by `T`
+impl BinaryFormat for PublicKey {
+    const KIND: u16 = 18;
+
+    fn write_payload(&self, writer: &mut BinaryWriter) {
+        self.params.write(writer);
+        self.matrix.write_payload(writer);
+    }
+
+    fn read_payload(reader: &mut BinaryReader) -> Result<PublicKey, BinaryError> {
+        let params = Parameters::read(reader)?;
+        let matrix = BitMatrix::read_payload(reader)?;
+        if matrix.nrows() != params.redundancy() || matrix.ncols() != params.dimension() {
+            return Err(BinaryError::Invalid(
+                "the public matrix does not match the parameters".to_string(),
+            ));
+        }
+        Ok(PublicKey { params, matrix })
+    }
+}
+
+/// The secret Goppa code
+#[derive(Clone, Debug, PartialEq)]
+pub struct SecretKey {
+    params: Parameters,
+    code: GoppaCode,
+}
+
+impl SecretKey {
+    pub fn parameters(&self) -> Parameters {
+        self.params
+    }
+
+    pub fn code(&self) -> &GoppaCode {
+        &self.code
+    }
+
+    /// Recover the error vector of weight `t` from its syndrome, or `None`
+    /// if the ciphertext does not have length `mt`
+    pub fn decrypt(&self, ciphertext: &BitVector) -> Option<BitVector> {
+        if ciphertext.len() != self.params.redundancy() {
+            return None;
+        }
+        // (s, 0) differs from a codeword exactly in the error positions
+        let mut received = ciphertext.clone();
+        received.extend_from(&BitVector::zero(self.params.dimension()));
+        self.code
+            .decode(&received)
+            .filter(|error| error.weight() == self.params.t)
+    }
+
+    /// Recover the error vector without timing leaks about the secret key or
+    /// the error. Returns the error and whether decryption succeeded; a
+    /// ciphertext without length `mt` fails with the zero error. Its length
+    /// is public, so that check may branch.
+    pub fn decrypt_constant_time(&self, ciphertext: &BitVector) -> (BitVector, bool) {
+        if ciphertext.len() != self.params.redundancy() {
+            return (BitVector::zero(self.params.n), false);
+        }
+        let mut received = ciphertext.clone();
+        received.extend_from(&BitVector::zero(self.params.dimension()));
+        self.code.decode_constant_time(&received)
+    }
+}
+
+/// The parameters followed by the Goppa code
+impl BinaryFormat for SecretKey {
+    const KIND: u16 = 19;
+
+    fn write_payload(&self, writer: &mut BinaryWriter) {
+        self.params.write(writer);
+        self.code.write_payload(writer);
+    }
+
+    fn read_payload(reader: &mut BinaryReader) -> Result<SecretKey, BinaryError> {
+        let params = Parameters::read(reader)?;
+        let code = GoppaCode::read_payload(reader)?;
+        if code.length() != params.n
+            || code.correction_capacity() != params.t
+            || code.field().modulus() != params.field_polynomial
+        {
+            return Err(BinaryError::Invalid(
+                "the Goppa code does not match the parameters".to_string(),
+            ));
+        }
+        Ok(SecretKey { params, code })
+    }
+}
+
+/// Generate a key pair, retrying until the public matrix has systematic form.
+///
+/// Only the reduction to systematic form avoids secret-dependent branches:
+/// the Goppa polynomial and the parity-check matrix are computed with the
+/// table-based `gf2m::Field`, so key generation is not constant time.
+pub fn keygen<R: Rng>(params: Parameters, rng: &mut R) -> (PublicKey, SecretKey) {
+    assert!(
+        params.redundancy() < params.n && params.n <= 1 << params.m,
+        "Invalid parameter set"
+    );
+    let field = params.field();
+    loop {
+        let code = GoppaCode::random(field.clone(), params.n, params.t, rng);
+        let mut h = code.parity_check_matrix();
+        if !h.reduce_systematic_constant_time() {
+            continue;
+        }
+        let public = PublicKey {
+            params,
+            matrix: h.columns(params.redundancy(), params.dimension()),
+        };
+        return (public, SecretKey { params, code });
+    }
+}
+
+/// Sample a random error of weight `t`: `(error, ciphertext)`
+pub fn encapsulate<R: Rng>(public: &PublicKey, rng: &mut R) -> (BitVector, BitVector) {
+    let n = public.params.n;
+    let error = BitVector::from_support(n, &seq::sample_indices(rng, n, public.params.t));
+    let ciphertext = public.encrypt(&error);
+    (error, ciphertext)
+}
+
+/// Recover the error vector from a ciphertext
+pub fn decapsulate(secret: &SecretKey, ciphertext: &BitVector) -> Option<BitVector> {
+    secret.decrypt(ciphertext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{SeedableRng, XorShiftRng};
+
+    fn rng() -> XorShiftRng {
+        XorShiftRng::from_seed([1, 2, 3, 4])
+    }
+
+    /// FNV-1a over the words of a sequence of vectors, to keep KATs short
+    fn digest<'a, I: IntoIterator<Item = &'a BitVector>>(vectors: I) -> u64 {
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        for v in vectors {
+            for word in v.words() {
+                for byte in 0..8 {
+                    hash ^= (word >> (8 * byte)) & 0xff;
+                    hash = hash.wrapping_mul(0x0100_0000_01b3);
+                }
+            }
+        }
+        hash
+    }
+
+    #[test]
+    fn parameter_sets() {
+        assert_eq!(MCELIECE_348864.redundancy(), 768);
+        assert_eq!(MCELIECE_348864.dimension(), 2720);
+        assert_eq!(MCELIECE_6960119.dimension(), 5413);
+        for params in &[
+            MCELIECE_348864,
+            MCELIECE_460896,
+            MCELIECE_6688128,
+            MCELIECE_6960119,
+            MCELIECE_8192128,
+            TOY,
+        ] {
+            assert!(params.n <= params.field().order());
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut rng = rng();
+        let (public, secret) = keygen(TOY, &mut rng);
+        assert_eq!(public.matrix().nrows(), 64);
+        assert_eq!(public.matrix().ncols(), 64);
+        for _ in 0..10 {
+            let (error, ciphertext) = encapsulate(&public, &mut rng);
+            assert_eq!(ciphertext.len(), 64);
+            assert_eq!(decapsulate(&secret, &ciphertext), Some(error.clone()));
+            assert_eq!(secret.decrypt_constant_time(&ciphertext), (error, true));
+        }
+    }
+
+    #[test]
+    fn public_key_matches_secret_code() {
+        let mut rng = rng();
+        let (public, secret) = keygen(TOY, &mut rng);
+        let (error, ciphertext) = encapsulate(&public, &mut rng);
+        assert_eq!(public.parity_check_matrix().mul_vector(&error), ciphertext);
+        // the public parity checks span the same code as the private ones
+        let h = secret.code().parity_check_matrix();
+        let g = public.parity_check_matrix();
+        for i in 0..TOY.dimension() {
+            let mut codeword = public.matrix().transpose().row(i).clone();
+            codeword.extend_from(&BitVector::from_support(TOY.dimension(), &[i]));
+            assert!(g.mul_vector(&codeword).is_zero());
+            assert!(h.mul_vector(&codeword).is_zero());
+        }
+    }
+
+    #[test]
+    fn reject_wrong_weight() {
+        let mut rng = rng();
+        let (public, secret) = keygen(TOY, &mut rng);
+        let error = BitVector::from_support(TOY.n, &[3, 70]);
+        assert_eq!(decapsulate(&secret, &public.encrypt(&error)), None);
+    }
+
+    #[test]
+    fn reject_wrong_length() {
+        let mut rng = rng();
+        let (public, secret) = keygen(TOY, &mut rng);
+        let (_, ciphertext) = encapsulate(&public, &mut rng);
+        let zero = BitVector::zero(TOY.n);
+        for wrong in &[
+            ciphertext.slice(0, ciphertext.len() - 1),
+            BitVector::zero(0),
+            BitVector::zero(TOY.n),
+        ] {
+            assert_eq!(decapsulate(&secret, wrong), None);
+            assert_eq!(secret.decrypt_constant_time(wrong), (zero.clone(), false));
+        }
+    }
+
+    #[test]
+    fn binary_roundtrip() {
+        let (public, secret) = keygen(TOY, &mut rng());
+        let public_bytes = public.to_binary();
+        // the header, the parameters and 64 rows of 8 bytes
+        assert_eq!(public_bytes.len(), 20 + 24 + 24 + 64 * 8);
+        assert_eq!(PublicKey::from_binary(&public_bytes).unwrap(), public);
+        assert_eq!(SecretKey::from_binary(&secret.to_binary()).unwrap(), secret);
+        assert!(SecretKey::from_binary(&public_bytes).is_err());
+    }
+
+    #[test]
+    fn known_answers() {
+        let mut rng = XorShiftRng::from_seed([0x4d63, 0x456c, 0x6965, 0x6365]);
+        let (public, secret) = keygen(TOY, &mut rng);
+        assert_eq!(digest(public.matrix().rows()), 0xe3f6_a170_8f0b_f824);
+        assert_eq!(
+            secret.code().goppa_polynomial().coefficients(),
+            &[238, 108, 147, 186, 173, 79, 168, 94, 1]
+        );
+        let expected: [(&[usize], u64); 3] = [
+            (&[5, 8, 41, 66, 94, 98, 101, 109], 0x35dc_45f0_baad_d3ac),
+            (&[0, 2, 25, 26, 36, 71, 78, 111], 0x638f_3030_1ee6_6ea0),
+            (&[9, 11, 26, 40, 43, 58, 93, 96], 0x06db_283a_90e9_25d6),
+        ];
+        for &(support, ciphertext_digest) in &expected {
+            let (error, ciphertext) = encapsulate(&public, &mut rng);
+            assert_eq!(error.support().collect::<Vec<_>>(), support);
+            assert_eq!(digest(Some(&ciphertext)), ciphertext_digest);
+            assert_eq!(decapsulate(&secret, &ciphertext), Some(error));
+        }
+    }
+}
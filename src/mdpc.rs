@@ -0,0 +1,663 @@
+//! Quasi-cyclic moderate-density parity-check (QC-MDPC) codes and their
+//! bit-flipping decoders, as used in BIKE.
+//!
+//! A QC-MDPC code with `n0` blocks of size `r` has parity-check matrix
+//! `H = [H_0 | ... | H_{n0-1}]`, where every `H_i` is a sparse circulant.
+//! The circulants are stored by the support of their first row, and the
+//! syndrome of `e = (e_0, ..., e_{n0-1})` is the polynomial
+//! `e_0 h_0 + ... + e_{n0-1} h_{n0-1} mod x^r - 1`.
+
+use bit_vector::BitVector;
+use matrix::{
+    BinaryError, BinaryFormat, BinaryReader, BinaryWriter, Circulant, QuasiCyclicMatrix,
+    SparseBinaryMatrix, Vector,
+};
+use rand::{seq, Rng};
+use simulation::wilson_interval;
+use zero_one::{One, Zero};
+
+/// A decoding threshold that is an affine function of the syndrome weight,
+/// `max(floor(slope * |s| + intercept), min)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Threshold {
+    pub slope: f64,
+    pub intercept: f64,
+    pub min: usize,
+}
+
+impl Threshold {
+    pub fn compute(&self, syndrome_weight: usize) -> usize {
+        let t = (self.slope * syndrome_weight as f64 + self.intercept).floor() as usize;
+        if t > self.min {
+            t
+        } else {
+            self.min
+        }
+    }
+}
+
+/// Parameters of a QC-MDPC code with two circulant blocks
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Parameters {
+    /// The block size
+    pub r: usize,
+    /// The row weight of `H`; every block has column weight `w / 2`
+    pub w: usize,
+    /// The weight of the errors to decode
+    pub t: usize,
+    /// The bit-flipping threshold for this parameter set
+    pub threshold: Threshold,
+}
+
+/// BIKE, NIST security level 1
+pub const BIKE_LEVEL_1: Parameters = Parameters {
+    r: 12_323,
+    w: 142,
+    t: 134,
+    threshold: Threshold {
+        slope: 0.006_972_2,
+        intercept: 13.530,
+        min: 36,
+    },
+};
+
+/// BIKE, NIST security level 3
+pub const BIKE_LEVEL_3: Parameters = Parameters {
+    r: 24_659,
+    w: 206,
+    t: 199,
+    threshold: Threshold {
+        slope: 0.005_265,
+        intercept: 15.2588,
+        min: 52,
+    },
+};
+
+/// BIKE, NIST security level 5
+pub const BIKE_LEVEL_5: Parameters = Parameters {
+    r: 40_973,
+    w: 274,
+    t: 264,
+    threshold: Threshold {
+        slope: 0.004_023_12,
+        intercept: 17.8785,
+        min: 69,
+    },
+};
+
+/// A toy parameter set for experiments; offers no security at all.
+pub const TOY: Parameters = Parameters {
+    r: 523,
+    w: 30,
+    t: 10,
+    threshold: Threshold {
+        slope: 0.03,
+        intercept: 5.0,
+        min: 8,
+    },
+};
+
+/// A QC-MDPC code, defined by the circulant blocks of its parity-check matrix
+#[derive(Clone, Debug, PartialEq)]
+pub struct QcMdpcCode {
+    block_size: usize,
+    supports: Vec<Vec<usize>>,
+}
+
+impl QcMdpcCode {
+    /// The code whose blocks of size `block_size` have first rows with the
+    /// given supports
+    pub fn from_supports(block_size: usize, supports: Vec<Vec<usize>>) -> QcMdpcCode {
+        assert!(!supports.is_empty(), "A code needs at least one block");
+        let supports = supports
+            .into_iter()
+            .map(|mut support| {
+                support.sort();
+                support.dedup();
+                assert!(
+                    support.iter().all(|&i| i < block_size),
+                    "Support should lie within the circulant"
+                );
+                support
+            })
+            .collect();
+        QcMdpcCode {
+            block_size,
+            supports,
+        }
+    }
+
+    /// Sample a code with two blocks of odd column weight `w / 2`
+    pub fn random<R: Rng>(params: &Parameters, rng: &mut R) -> QcMdpcCode {
+        let weight = params.w / 2;
+        assert!(weight % 2 == 1, "The blocks should have odd weight");
+        assert!(weight <= params.r, "Weight can not exceed the block size");
+        let supports = (0..2)
+            .map(|_| seq::sample_indices(rng, params.r, weight))
+            .collect();
+        QcMdpcCode::from_supports(params.r, supports)
+    }
+
+    /// The supports of the first rows of the blocks
+    pub fn supports(&self) -> &[Vec<usize>] {
+        &self.supports
+    }
+
+    /// The blocks as circulants over `T`
+    pub fn blocks<T: Zero + One>(&self) -> Vec<Circulant<T>> {
+        self.supports
+            .iter()
+            .map(|support| {
+                let mut row: Vec<T> = (0..self.block_size).map(|_| T::zero()).collect();
+                for &i in support {
+                    row[i] = T::one();
+                }
+                Circulant::from_row(Vector::from_vec(row))
+            })
+            .collect()
+    }
+
+    /// The size `r` of the circulant blocks
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// The code length `n = n0 * r`
+    pub fn length(&self) -> usize {
+        self.supports.len() * self.block_size
+    }
+
+    /// The maximum column weight of the parity-check matrix
+    pub fn column_weight(&self) -> usize {
+        self.supports.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    /// Sample a uniformly random error of the given weight
+    pub fn random_error<R: Rng>(&self, weight: usize, rng: &mut R) -> BitVector {
+        let n = self.length();
+        BitVector::from_support(n, &seq::sample_indices(rng, n, weight))
+    }
+
+    /// Compute the syndrome `e H^T` of a vector of length `n`
+    pub fn syndrome(&self, error: &BitVector) -> BitVector {
+        assert_eq!(
+            error.len(),
+            self.length(),
+            "Vector should match the code length"
+        );
+        let mut syndrome = BitVector::zero(self.block_size());
+        for i in error.support() {
+            self.flip(&mut syndrome, i);
+        }
+        syndrome
+    }
+
+    /// Add column `position` of `H` to the syndrome, which is the effect of
+    /// flipping that position in the error: `x^shift h(x)` for the block
+    /// `h` it lies in.
+    #[inline]
+    fn flip(&self, syndrome: &mut BitVector, position: usize) {
+        let r = self.block_size;
+        let shift = position % r;
+        for &k in &self.supports[position / r] {
+            let idx = shift + k;
+            syndrome.flip(if idx >= r { idx - r } else { idx });
+        }
+    }
+
+    /// The parity-check matrix `H` as an explicit sparse matrix
+    pub fn parity_check_matrix(&self) -> SparseBinaryMatrix {
+        let r = self.block_size();
+        let rows = (0..r)
+            .map(|i| {
+                let mut row = Vec::with_capacity(self.supports.len() * self.column_weight());
+                for (b, support) in self.supports.iter().enumerate() {
+                    row.extend(support.iter().map(|&k| b * r + (i + r - k) % r));
+                }
+                row
+            })
+            .collect();
+        SparseBinaryMatrix::from_rows(self.length(), rows)
+    }
+
+    /// The parity-check matrix `H` as a quasi-cyclic matrix over `T`.
+    ///
+    /// The blocks of `H` are the transposes of the circulants of `h_i`, as the
+    /// syndrome is `e H^T`.
+    pub fn to_quasi_cyclic<T: Zero + One + Clone>(&self) -> QuasiCyclicMatrix<T> {
+        QuasiCyclicMatrix::from_blocks(vec![self
+            .blocks()
+            .iter()
+            .map(Circulant::transpose)
+            .collect()])
+    }
+
+    /// Count, for every position, the unsatisfied parity checks it is involved in
+    pub fn counters(&self, syndrome: &BitVector) -> Vec<usize> {
+        let r = self.block_size();
+        let mut counters = vec![0; self.length()];
+        for (b, support) in self.supports.iter().enumerate() {
+            let counters = &mut counters[b * r..(b + 1) * r];
+            for i in syndrome.support() {
+                for &k in support {
+                    counters[if i >= k { i - k } else { i + r - k }] += 1;
+                }
+            }
+        }
+        counters
+    }
+}
+
+/// The block size, then the support of the first row of every block
+impl BinaryFormat for QcMdpcCode {
+    const KIND: u16 = 21;
+
+    fn write_payload(&self, writer: &mut BinaryWriter) {
+        writer.put_usize(self.block_size());
+        writer.put_usize(self.supports.len());
+        for support in &self.supports {
+            writer.put_usize(support.len());
+            for &i in support {
+                writer.put_u32(i as u32);
+            }
+        }
+    }
+
+    fn read_payload(reader: &mut BinaryReader) -> Result<QcMdpcCode, BinaryError> {
+        let size = reader.get_u64()? as usize;
+        let count = reader.get_len(1)?;
+        if count == 0 {
+            return Err(BinaryError::Invalid(
+                "a code needs at least one block".to_string(),
+            ));
+        }
+        let mut supports = Vec::with_capacity(count);
+        for _ in 0..count {
+            let weight = reader.get_len(1)?;
+            let mut support = Vec::with_capacity(weight);
+            for _ in 0..weight {
+                let i = reader.get_u32()? as usize;
+                if i >= size {
+                    return Err(BinaryError::Invalid(
+                        "support should lie within the circulant".to_string(),
+                    ));
+                }
+                support.push(i);
+            }
+            supports.push(support);
+        }
+        Ok(QcMdpcCode::from_supports(size, supports))
+    }
+}
+
+/// A decoder that recovers a low-weight error from its syndrome
+pub trait Decoder {
+    /// Find the error with the given syndrome, or `None` on a decoding failure
+    fn decode<R: Rng>(
+        &self,
+        code: &QcMdpcCode,
+        syndrome: &BitVector,
+        rng: &mut R,
+    ) -> Option<BitVector>;
+}
+
+/// The Black-Gray-Flip decoder from the BIKE specification
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BlackGrayFlip {
+    pub iterations: usize,
+    /// The margin below the threshold that marks a position as gray
+    pub tau: usize,
+    pub threshold: Threshold,
+}
+
+impl BlackGrayFlip {
+    /// The decoder with the settings of the BIKE specification
+    pub fn new(params: &Parameters) -> BlackGrayFlip {
+        BlackGrayFlip {
+            iterations: 5,
+            tau: 3,
+            threshold: params.threshold,
+        }
+    }
+
+    /// Flip every position whose counter reaches the threshold, and return
+    /// the positions that were flipped (black) and that came close (gray).
+    fn iteration(
+        &self,
+        code: &QcMdpcCode,
+        syndrome: &mut BitVector,
+        error: &mut BitVector,
+    ) -> (Vec<usize>, Vec<usize>) {
+        let threshold = self.threshold.compute(syndrome.weight());
+        let counters = code.counters(syndrome);
+        let mut black = Vec::new();
+        let mut gray = Vec::new();
+        for (i, &counter) in counters.iter().enumerate() {
+            if counter >= threshold {
+                black.push(i);
+            } else if counter + self.tau >= threshold {
+                gray.push(i);
+            }
+        }
+        for &i in &black {
+            error.flip(i);
+            code.flip(syndrome, i);
+        }
+        (black, gray)
+    }
+
+    /// Flip the positions in `mask` whose counter reaches the threshold
+    fn masked_iteration(
+        &self,
+        code: &QcMdpcCode,
+        syndrome: &mut BitVector,
+        error: &mut BitVector,
+        mask: &[usize],
+        threshold: usize,
+    ) {
+        let counters = code.counters(syndrome);
+        for &i in mask {
+            if counters[i] >= threshold {
+                error.flip(i);
+                code.flip(syndrome, i);
+            }
+        }
+    }
+}
+
+impl Decoder for BlackGrayFlip {
+    fn decode<R: Rng>(
+        &self,
+        code: &QcMdpcCode,
+        syndrome: &BitVector,
+        _rng: &mut R,
+    ) -> Option<BitVector> {
+        let mut syndrome = syndrome.clone();
+        let mut error = BitVector::zero(code.length());
+        let masked_threshold = code.column_weight().div_ceil(2) + 1;
+
+        for i in 0..self.iterations {
+            if syndrome.is_zero() {
+                break;
+            }
+            let (black, gray) = self.iteration(code, &mut syndrome, &mut error);
+            if i == 0 {
+                self.masked_iteration(code, &mut syndrome, &mut error, &black, masked_threshold);
+                self.masked_iteration(code, &mut syndrome, &mut error, &gray, masked_threshold);
+            }
+        }
+
+        if syndrome.is_zero() {
+            Some(error)
+        } else {
+            None
+        }
+    }
+}
+
+/// The step-by-step bit-flipping decoder, which considers one randomly
+/// chosen position at a time and updates the syndrome after every flip.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StepByStep {
+    /// The number of positions to consider before giving up
+    pub max_iterations: usize,
+    pub threshold: Threshold,
+}
+
+impl StepByStep {
+    pub fn new(params: &Parameters) -> StepByStep {
+        StepByStep {
+            max_iterations: 20 * 2 * params.r,
+            threshold: params.threshold,
+        }
+    }
+}
+
+impl Decoder for StepByStep {
+    fn decode<R: Rng>(
+        &self,
+        code: &QcMdpcCode,
+        syndrome: &BitVector,
+        rng: &mut R,
+    ) -> Option<BitVector> {
+        let r = code.block_size();
+        let mut syndrome = syndrome.clone();
+        let mut syndrome_weight = syndrome.weight();
+        let mut error = BitVector::zero(code.length());
+
+        for _ in 0..self.max_iterations {
+            if syndrome_weight == 0 {
+                return Some(error);
+            }
+            let position = rng.gen_range(0, code.length());
+            let support = &code.supports[position / r];
+            let shift = position % r;
+            let counter = support
+                .iter()
+                .filter(|&&k| syndrome.get((shift + k) % r))
+                .count();
+            if counter >= self.threshold.compute(syndrome_weight) {
+                error.flip(position);
+                code.flip(&mut syndrome, position);
+                // every unsatisfied check becomes satisfied and vice versa
+                syndrome_weight = syndrome_weight + support.len() - 2 * counter;
+            }
+        }
+
+        if syndrome_weight == 0 {
+            Some(error)
+        } else {
+            None
+        }
+    }
+}
+
+/// The outcome of a decoding-failure-rate simulation
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DfrEstimate {
+    pub trials: usize,
+    pub failures: usize,
+}
+
+impl DfrEstimate {
+    /// The observed failure rate
+    pub fn rate(&self) -> f64 {
+        if self.trials == 0 {
+            return 0.0;
+        }
+        self.failures as f64 / self.trials as f64
+    }
+
+    /// The 95% Wilson score interval for the failure rate
+    pub fn confidence_interval(&self) -> (f64, f64) {
+        wilson_interval(self.failures, self.trials)
+    }
+}
+
+/// Estimate the decoding failure rate of `decoder` by decoding
+/// `errors_per_key` random errors of weight `t` for each of `keys` random codes.
+pub fn estimate_dfr<D: Decoder, R: Rng>(
+    params: &Parameters,
+    decoder: &D,
+    keys: usize,
+    errors_per_key: usize,
+    rng: &mut R,
+) -> DfrEstimate {
+    let mut failures = 0;
+    for _ in 0..keys {
+        let code = QcMdpcCode::random(params, rng);
+        for _ in 0..errors_per_key {
+            let error = code.random_error(params.t, rng);
+            let syndrome = code.syndrome(&error);
+            if decoder.decode(&code, &syndrome, rng) != Some(error) {
+                failures += 1;
+            }
+        }
+    }
+    DfrEstimate {
+        trials: keys * errors_per_key,
+        failures,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bit_vector::Bit;
+    use rand::{SeedableRng, XorShiftRng};
+
+    fn rng() -> XorShiftRng {
+        XorShiftRng::from_seed([1, 2, 3, 4])
+    }
+
+    #[test]
+    fn supports_are_normalised() {
+        let code = QcMdpcCode::from_supports(7, vec![vec![3, 1, 3], vec![0]]);
+        assert_eq!(code.supports(), &[vec![1, 3], vec![0]]);
+        assert_eq!(code.column_weight(), 2);
+        assert_eq!(code.length(), 14);
+    }
+
+    #[test]
+    #[should_panic]
+    fn support_out_of_range() {
+        QcMdpcCode::from_supports(7, vec![vec![7]]);
+    }
+
+    #[test]
+    fn random_blocks_have_weight() {
+        let code = QcMdpcCode::random(&TOY, &mut rng());
+        assert!(code.supports().iter().all(|s| s.len() == TOY.w / 2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn random_blocks_of_even_weight() {
+        let params = Parameters { w: 32, ..TOY };
+        QcMdpcCode::random(&params, &mut rng());
+    }
+
+    #[test]
+    fn threshold() {
+        let t = BIKE_LEVEL_1.threshold;
+        assert_eq!(t.compute(0), 36);
+        assert_eq!(t.compute(4000), 41);
+    }
+
+    #[test]
+    fn syndrome_is_sum_of_products() {
+        let mut rng = rng();
+        let code = QcMdpcCode::random(&TOY, &mut rng);
+        let error = code.random_error(TOY.t, &mut rng);
+        let r = code.block_size();
+        // e_0 h_0 + e_1 h_1 as polynomials
+        let blocks = code.blocks::<Bit<bool>>();
+        let part = |offset: usize| -> Vector<Bit<bool>> {
+            (0..r).map(|i| Bit::from(error.get(offset + i))).collect()
+        };
+        let expected = &(&part(0) * &blocks[0]) + &(&part(r) * &blocks[1]);
+        let syndrome = code.syndrome(&error);
+        assert!((0..r).all(|i| Bit::from(syndrome.get(i)) == expected[i]));
+    }
+
+    #[test]
+    fn parity_check_matrix_gives_syndrome() {
+        let mut rng = rng();
+        let code = QcMdpcCode::random(&TOY, &mut rng);
+        let h = code.parity_check_matrix();
+        assert_eq!(h.nrows(), TOY.r);
+        assert_eq!(h.ncols(), 2 * TOY.r);
+        assert_eq!(h.nnz(), TOY.r * TOY.w);
+        let error = code.random_error(TOY.t, &mut rng);
+        assert_eq!(h.mul_vector(&error), code.syndrome(&error));
+    }
+
+    #[test]
+    fn quasi_cyclic_parity_check_matrix() {
+        let code = QcMdpcCode::from_supports(7, vec![vec![0, 1, 3], vec![2, 5, 6]]);
+        let h = code.to_quasi_cyclic::<i32>();
+        assert_eq!(h.block_cols(), 2);
+        assert_eq!(h.to_matrix(), code.parity_check_matrix().to_matrix());
+    }
+
+    #[test]
+    fn binary_roundtrip() {
+        let code = QcMdpcCode::random(&TOY, &mut rng());
+        let bytes = code.to_binary();
+        // the header, size and count, and per block a weight and 15 indices
+        assert_eq!(bytes.len(), 20 + 16 + 2 * (8 + 15 * 4));
+        assert_eq!(QcMdpcCode::from_binary(&bytes).unwrap(), code);
+    }
+
+    #[test]
+    fn counters_count_unsatisfied_checks() {
+        let mut rng = rng();
+        let code = QcMdpcCode::random(&TOY, &mut rng);
+        let error = code.random_error(1, &mut rng);
+        let position = error.support().next().unwrap();
+        let counters = code.counters(&code.syndrome(&error));
+        assert_eq!(counters[position], code.column_weight());
+        assert!(counters.iter().all(|&c| c <= code.column_weight()));
+    }
+
+    #[test]
+    fn black_gray_flip_decodes() {
+        let mut rng = rng();
+        let decoder = BlackGrayFlip::new(&TOY);
+        for _ in 0..10 {
+            let code = QcMdpcCode::random(&TOY, &mut rng);
+            let error = code.random_error(TOY.t, &mut rng);
+            let syndrome = code.syndrome(&error);
+            assert_eq!(decoder.decode(&code, &syndrome, &mut rng), Some(error));
+        }
+    }
+
+    #[test]
+    fn step_by_step_decodes() {
+        let mut rng = rng();
+        let decoder = StepByStep::new(&TOY);
+        for _ in 0..10 {
+            let code = QcMdpcCode::random(&TOY, &mut rng);
+            let error = code.random_error(TOY.t, &mut rng);
+            let syndrome = code.syndrome(&error);
+            assert_eq!(decoder.decode(&code, &syndrome, &mut rng), Some(error));
+        }
+    }
+
+    #[test]
+    fn decoders_fail_on_too_many_errors() {
+        let mut rng = rng();
+        let code = QcMdpcCode::random(&TOY, &mut rng);
+        let error = code.random_error(200, &mut rng);
+        let syndrome = code.syndrome(&error);
+        assert_ne!(
+            BlackGrayFlip::new(&TOY).decode(&code, &syndrome, &mut rng),
+            Some(error)
+        );
+    }
+
+    #[test]
+    fn estimate_dfr_counts_trials() {
+        let mut rng = rng();
+        let estimate = estimate_dfr(&TOY, &BlackGrayFlip::new(&TOY), 2, 5, &mut rng);
+        assert_eq!(estimate.trials, 10);
+        assert_eq!(estimate.failures, 0);
+        let (low, high) = estimate.confidence_interval();
+        assert_eq!(low, 0.0);
+        assert!(high > 0.0 && high < 0.5);
+    }
+
+    #[test]
+    fn dfr_rate() {
+        let estimate = DfrEstimate {
+            trials: 100,
+            failures: 10,
+        };
+        assert_eq!(estimate.rate(), 0.1);
+        let (low, high) = estimate.confidence_interval();
+        assert!(low < 0.1 && 0.1 < high);
+    }
+}
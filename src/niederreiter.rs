@@ -0,0 +1,215 @@
+//! The Niederreiter cryptosystem over Classic McEliece keys.
+//!
+//! Encryption encodes the message as an error vector of weight `t` and sends
+//! its syndrome; decryption decodes the syndrome and maps the error vector
+//! back to the message. The same keys give a KEM in which the error vector
+//! is random and the shared secret is `SHAKE256(1 || e || C)`.
+
+use bit_vector::BitVector;
+use constant_weight::ConstantWeight;
+use kem::{derive_secret, Kem, SharedSecret};
+use matrix::{BinaryError, BinaryFormat, BinaryReader, BinaryWriter};
+use mceliece::{self, Parameters, PublicKey, SecretKey};
+use rand::Rng;
+
+/// Niederreiter encryption and the KEM for one parameter set
+#[derive(Clone, Debug, PartialEq)]
+pub struct Niederreiter {
+    params: Parameters,
+    encoder: ConstantWeight,
+}
+
+impl Niederreiter {
+    pub fn new(params: Parameters) -> Niederreiter {
+        Niederreiter {
+            params,
+            encoder: ConstantWeight::new(params.n, params.t),
+        }
+    }
+
+    pub fn parameters(&self) -> Parameters {
+        self.params
+    }
+
+    /// The number of bits in a plaintext
+    pub fn message_length(&self) -> usize {
+        self.encoder.capacity()
+    }
+
+    pub fn encrypt(&self, public: &PublicKey, message: &BitVector) -> BitVector {
+        public.encrypt(&self.encoder.encode(message))
+    }
+
+    /// The message, or `None` if the ciphertext has the wrong length or
+    /// does not decrypt to an error of weight `t`
+    pub fn decrypt(&self, secret: &SecretKey, ciphertext: &BitVector) -> Option<BitVector> {
+        if ciphertext.len() != self.params.redundancy() {
+            return None;
+        }
+        secret
+            .decrypt(ciphertext)
+            .and_then(|error| self.encoder.decode(&error))
+    }
+}
+
+/// The secret key of the KEM, which holds the random string used for
+/// implicit rejection next to the Goppa code
+#[derive(Clone, Debug, PartialEq)]
+pub struct KemSecretKey {
+    key: SecretKey,
+    rejection: BitVector,
+}
+
+impl KemSecretKey {
+    pub fn key(&self) -> &SecretKey {
+        &self.key
+    }
+}
+
+/// The McEliece secret key followed by the rejection string
+impl BinaryFormat for KemSecretKey {
+    const KIND: u16 = 20;
+
+    fn write_payload(&self, writer: &mut BinaryWriter) {
+        self.key.write_payload(writer);
+        writer.put_bit_vector(&self.rejection);
+    }
+
+    fn read_payload(reader: &mut BinaryReader) -> Result<KemSecretKey, BinaryError> {
+        let key = SecretKey::read_payload(reader)?;
+        let rejection = reader.get_bit_vector()?;
+        if rejection.len() != key.parameters().n {
+            return Err(BinaryError::Invalid(
+                "the rejection string should have the code length".to_string(),
+            ));
+        }
+        Ok(KemSecretKey { key, rejection })
+    }
+}
+
+impl Kem for Niederreiter {
+    type PublicKey = PublicKey;
+    type SecretKey = KemSecretKey;
+    type Ciphertext = BitVector;
+
+    fn keygen<R: Rng>(&self, rng: &mut R) -> (PublicKey, KemSecretKey) {
+        let (public, key) = mceliece::keygen(self.params, rng);
+        let bits: Vec<bool> = (0..self.params.n).map(|_| rng.gen()).collect();
+        let rejection = BitVector::from_bools(&bits);
+        (public, KemSecretKey { key, rejection })
+    }
+
+    fn encaps<R: Rng>(&self, public: &PublicKey, rng: &mut R) -> (BitVector, SharedSecret) {
+        let (error, ciphertext) = mceliece::encapsulate(public, rng);
+        let secret = derive_secret(1, &error, &ciphertext);
+        (ciphertext, secret)
+    }
+
+    fn decaps(&self, secret: &KemSecretKey, ciphertext: &BitVector) -> SharedSecret {
+        // the length is public, so a ciphertext of the wrong length may be
+        // rejected early, but not by aborting
+        if ciphertext.len() != secret.key.parameters().redundancy() {
+            return derive_secret(0, &secret.rejection, ciphertext);
+        }
+        let (error, valid) = secret.key.decrypt_constant_time(ciphertext);
+        // hash the error if decryption succeeded and the rejection string
+        // otherwise, selecting between them without branching
+        let mut preimage = secret.rejection.clone();
+        preimage.masked_xor_assign(
+            &(&secret.rejection ^ &error),
+            0u64.wrapping_sub(valid as u64),
+        );
+        derive_secret(valid as u8, &preimage, ciphertext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mceliece::TOY;
+    use rand::{SeedableRng, XorShiftRng};
+
+    fn rng() -> XorShiftRng {
+        XorShiftRng::from_seed([1, 2, 3, 4])
+    }
+
+    #[test]
+    fn encrypt_decrypt() {
+        let mut rng = rng();
+        let scheme = Niederreiter::new(TOY);
+        // log2 C(128, 8) is about 40.3
+        assert_eq!(scheme.message_length(), 40);
+        let (public, secret) = mceliece::keygen(TOY, &mut rng);
+        for _ in 0..5 {
+            let bits: Vec<bool> = (0..40).map(|_| rng.gen()).collect();
+            let message = BitVector::from_bools(&bits);
+            let ciphertext = scheme.encrypt(&public, &message);
+            assert_eq!(ciphertext.len(), TOY.redundancy());
+            assert_eq!(scheme.decrypt(&secret, &ciphertext), Some(message));
+        }
+    }
+
+    #[test]
+    fn kem_agrees() {
+        let mut rng = rng();
+        let kem = Niederreiter::new(TOY);
+        let (public, secret) = kem.keygen(&mut rng);
+        for _ in 0..5 {
+            let (ciphertext, shared) = kem.encaps(&public, &mut rng);
+            assert_eq!(kem.decaps(&secret, &ciphertext), shared);
+        }
+    }
+
+    #[test]
+    fn kem_implicit_rejection() {
+        let mut rng = rng();
+        let kem = Niederreiter::new(TOY);
+        let (public, secret) = kem.keygen(&mut rng);
+        let (mut ciphertext, shared) = kem.encaps(&public, &mut rng);
+        ciphertext.flip(0);
+        let rejected = kem.decaps(&secret, &ciphertext);
+        assert_ne!(rejected, shared);
+        assert_eq!(rejected, derive_secret(0, &secret.rejection, &ciphertext));
+        // rejection is deterministic
+        assert_eq!(kem.decaps(&secret, &ciphertext), rejected);
+    }
+
+    #[test]
+    fn decrypt_rejects_wrong_length() {
+        let mut rng = rng();
+        let scheme = Niederreiter::new(TOY);
+        let (public, secret) = mceliece::keygen(TOY, &mut rng);
+        let ciphertext = scheme.encrypt(&public, &BitVector::zero(40));
+        let truncated = ciphertext.slice(0, ciphertext.len() - 1);
+        assert_eq!(scheme.decrypt(&secret, &truncated), None);
+        assert_eq!(scheme.decrypt(&secret, &BitVector::zero(0)), None);
+    }
+
+    #[test]
+    fn kem_rejects_wrong_length() {
+        let mut rng = rng();
+        let kem = Niederreiter::new(TOY);
+        let (public, secret) = kem.keygen(&mut rng);
+        let (ciphertext, shared) = kem.encaps(&public, &mut rng);
+        let truncated = ciphertext.slice(0, ciphertext.len() - 1);
+        let rejected = kem.decaps(&secret, &truncated);
+        assert_ne!(rejected, shared);
+        assert_eq!(rejected, derive_secret(0, &secret.rejection, &truncated));
+        let empty = BitVector::zero(0);
+        assert_eq!(
+            kem.decaps(&secret, &empty),
+            derive_secret(0, &secret.rejection, &empty)
+        );
+    }
+
+    #[test]
+    fn secret_key_roundtrip() {
+        let mut rng = rng();
+        let kem = Niederreiter::new(TOY);
+        let (public, secret) = kem.keygen(&mut rng);
+        let loaded = KemSecretKey::from_binary(&secret.to_binary()).unwrap();
+        assert_eq!(loaded, secret);
+        let (ciphertext, shared) = kem.encaps(&public, &mut rng);
+        assert_eq!(kem.decaps(&loaded, &ciphertext), shared);
+    }
+}
@@ -0,0 +1,79 @@
+//! `Serialize` and `Deserialize` for the `serde` feature.
+//!
+//! Codes and keys are stored as a byte string holding their binary format
+//! (see `storage`), so they are validated and checksummed on the way in.
+//! Plain parameter sets are written field by field.
+
+use goppa::GoppaCode;
+use linear_code::LinearCode;
+use matrix::BinaryFormat;
+use mceliece::{PublicKey, SecretKey};
+use mdpc::QcMdpcCode;
+use niederreiter::KemSecretKey;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_bytes::ByteBuf;
+
+macro_rules! serialize_as_binary {
+    ($($t:ty),*) => {
+        $(
+            impl Serialize for $t {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_bytes(&self.to_binary())
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $t {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$t, D::Error> {
+                    let bytes = ByteBuf::deserialize(deserializer)?;
+                    <$t>::from_binary(&bytes).map_err(D::Error::custom)
+                }
+            }
+        )*
+    };
+}
+
+serialize_as_binary!(
+    LinearCode,
+    GoppaCode,
+    PublicKey,
+    SecretKey,
+    KemSecretKey,
+    QcMdpcCode
+);
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_cbor;
+    extern crate serde_json;
+    use super::*;
+    use mceliece::{self, Parameters, TOY};
+    use rand::{SeedableRng, XorShiftRng};
+
+    #[test]
+    fn keys_cbor() {
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        let (public, secret) = mceliece::keygen(TOY, &mut rng);
+        let bytes = serde_cbor::to_vec(&public).unwrap();
+        // a byte string header on top of the binary format
+        assert_eq!(bytes.len(), public.to_binary().len() + 3);
+        assert_eq!(serde_cbor::from_slice::<PublicKey>(&bytes).unwrap(), public);
+        let bytes = serde_cbor::to_vec(&secret).unwrap();
+        assert_eq!(serde_cbor::from_slice::<SecretKey>(&bytes).unwrap(), secret);
+        assert!(serde_cbor::from_slice::<PublicKey>(&bytes).is_err());
+    }
+
+    #[test]
+    fn parameters_json() {
+        let json = serde_json::to_string(&TOY).unwrap();
+        assert_eq!(json, r#"{"m":8,"n":128,"t":8,"field_polynomial":283}"#);
+        assert_eq!(serde_json::from_str::<Parameters>(&json).unwrap(), TOY);
+    }
+
+    #[test]
+    fn code_json() {
+        let code = QcMdpcCode::random(&::mdpc::TOY, &mut XorShiftRng::from_seed([1, 2, 3, 4]));
+        let json = serde_json::to_string(&code).unwrap();
+        assert_eq!(serde_json::from_str::<QcMdpcCode>(&json).unwrap(), code);
+    }
+}
@@ -0,0 +1,397 @@
+//! Monte Carlo estimates of frame and bit error rates.
+//!
+//! Every frame encodes a random message, sends the codeword through a
+//! channel and decodes the received word from its syndrome. A frame is in
+//! error when the decoder fails or returns another codeword; its bit errors
+//! are the positions where the result differs from the codeword sent, so
+//! the bit error rate is over all `n` code bits.
+//!
+//! Frames are simulated in batches on several threads. Each batch has its
+//! own generator, seeded from its index and a single number drawn from the
+//! generator passed in, and the batches are counted in order until the
+//! target is reached. The same seed therefore repeats a simulation exactly,
+//! whatever the number of threads.
+
+use bit_vector::BitVector;
+use channel::ErrorChannel;
+use linear_code::{Decoder, LinearCode};
+use rand::Rng;
+use rng::from_seed;
+use std::io::{self, Write};
+use std::thread;
+
+/// The 95% Wilson score interval for a rate of `errors` in `trials`
+pub fn wilson_interval(errors: usize, trials: usize) -> (f64, f64) {
+    if trials == 0 {
+        return (0.0, 1.0);
+    }
+    let z = 1.96;
+    let n = trials as f64;
+    let p = errors as f64 / n;
+    let denominator = 1.0 + z * z / n;
+    let centre = (p + z * z / (2.0 * n)) / denominator;
+    let margin = z * (p * (1.0 - p) / n + z * z / (4.0 * n * n)).sqrt() / denominator;
+    ((centre - margin).max(0.0), (centre + margin).min(1.0))
+}
+
+/// The errors counted in a simulation
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ErrorRates {
+    pub frames: usize,
+    pub frame_errors: usize,
+    pub bits: usize,
+    pub bit_errors: usize,
+}
+
+impl ErrorRates {
+    /// The frame error rate
+    pub fn fer(&self) -> f64 {
+        rate(self.frame_errors, self.frames)
+    }
+
+    /// The bit error rate
+    pub fn ber(&self) -> f64 {
+        rate(self.bit_errors, self.bits)
+    }
+
+    /// The 95% confidence interval for the frame error rate
+    pub fn fer_interval(&self) -> (f64, f64) {
+        wilson_interval(self.frame_errors, self.frames)
+    }
+
+    /// The 95% confidence interval for the bit error rate. It takes the bits
+    /// to be independent, which errors within a frame are not, so it is too
+    /// narrow when the decoder fails with many bit errors at once.
+    pub fn ber_interval(&self) -> (f64, f64) {
+        wilson_interval(self.bit_errors, self.bits)
+    }
+
+    fn add(&mut self, other: &ErrorRates) {
+        self.frames += other.frames;
+        self.frame_errors += other.frame_errors;
+        self.bits += other.bits;
+        self.bit_errors += other.bit_errors;
+    }
+}
+
+fn rate(errors: usize, trials: usize) -> f64 {
+    if trials == 0 {
+        return 0.0;
+    }
+    errors as f64 / trials as f64
+}
+
+/// When to stop simulating, and how to spread the work
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Simulation {
+    /// Stop once this many frame errors are counted
+    pub target_errors: usize,
+    /// Stop after this many frames regardless
+    pub max_frames: usize,
+    /// The number of frames a thread simulates at a time
+    pub batch: usize,
+    pub threads: usize,
+}
+
+impl Default for Simulation {
+    fn default() -> Simulation {
+        Simulation {
+            target_errors: 100,
+            max_frames: 1_000_000,
+            batch: 100,
+            threads: 1,
+        }
+    }
+}
+
+impl Simulation {
+    /// Simulate frames until the target number of frame errors or the
+    /// maximum number of frames is reached
+    pub fn run<D, C, R>(
+        &self,
+        code: &LinearCode,
+        decoder: &D,
+        channel: &C,
+        rng: &mut R,
+    ) -> ErrorRates
+    where
+        D: Decoder + Sync,
+        C: ErrorChannel + Sync,
+        R: Rng,
+    {
+        assert!(
+            self.threads > 0 && self.batch > 0,
+            "A simulation needs threads and frames per batch"
+        );
+        let base: u64 = rng.gen();
+        let mut total = ErrorRates::default();
+        let mut next = 0;
+        while !self.done(&total) {
+            // batch `index` always holds the same frames, so the threads
+            // only decide how many batches are simulated at once
+            let batches: Vec<(usize, u64)> = (next..next + self.threads)
+                .map(|index| {
+                    let start = index.saturating_mul(self.batch);
+                    let frames = self.batch.min(self.max_frames.saturating_sub(start));
+                    (frames, batch_seed(base, index as u64))
+                })
+                .filter(|&(frames, _)| frames > 0)
+                .collect();
+            next += self.threads;
+            let results: Vec<ErrorRates> = thread::scope(|scope| {
+                let workers: Vec<_> = batches
+                    .iter()
+                    .map(|&(frames, seed)| {
+                        scope.spawn(move || {
+                            simulate_frames(code, decoder, channel, frames, &mut from_seed(seed))
+                        })
+                    })
+                    .collect();
+                workers
+                    .into_iter()
+                    .map(|worker| worker.join().expect("A simulation thread panicked"))
+                    .collect()
+            });
+            // the batches past the one that reaches the target are dropped
+            for result in &results {
+                if self.done(&total) {
+                    break;
+                }
+                total.add(result);
+            }
+        }
+        total
+    }
+
+    fn done(&self, total: &ErrorRates) -> bool {
+        total.frame_errors >= self.target_errors || total.frames >= self.max_frames
+    }
+
+    /// Run a simulation for each parameter, on the channel `channel` makes
+    /// from it: crossover probabilities, signal-to-noise ratios or error
+    /// weights, for instance
+    pub fn sweep<D, C, F, R>(
+        &self,
+        code: &LinearCode,
+        decoder: &D,
+        parameters: &[f64],
+        channel: F,
+        rng: &mut R,
+    ) -> Vec<(f64, ErrorRates)>
+    where
+        D: Decoder + Sync,
+        C: ErrorChannel + Sync,
+        F: Fn(f64) -> C,
+        R: Rng,
+    {
+        parameters
+            .iter()
+            .map(|&parameter| {
+                let rates = self.run(code, decoder, &channel(parameter), rng);
+                (parameter, rates)
+            })
+            .collect()
+    }
+}
+
+/// The seed of batch `index` of a run drawing `base`, mixed as in
+/// splitmix64 so that neighbouring batches get unrelated generators
+fn batch_seed(base: u64, index: u64) -> u64 {
+    let mut z = base.wrapping_add(index.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn simulate_frames<D, C, R>(
+    code: &LinearCode,
+    decoder: &D,
+    channel: &C,
+    frames: usize,
+    rng: &mut R,
+) -> ErrorRates
+where
+    D: Decoder,
+    C: ErrorChannel,
+    R: Rng,
+{
+    let mut rates = ErrorRates {
+        frames,
+        bits: frames * code.length(),
+        ..ErrorRates::default()
+    };
+    for _ in 0..frames {
+        let message: Vec<bool> = (0..code.dimension()).map(|_| rng.gen()).collect();
+        let codeword = code.encode(&BitVector::from_bools(&message));
+        let received = channel.transmit(&codeword, rng);
+        let errors = match decoder.decode(code, &code.syndrome(&received), rng) {
+            Some(error) => (&(&received ^ &error) ^ &codeword).weight(),
+            None => {
+                // a failure leaves the errors of the channel
+                rates.frame_errors += 1;
+                rates.bit_errors += (&received ^ &codeword).weight();
+                continue;
+            }
+        };
+        if errors > 0 {
+            rates.frame_errors += 1;
+            rates.bit_errors += errors;
+        }
+    }
+    rates
+}
+
+/// Write the results of a sweep as CSV, with a header naming the parameter
+pub fn write_csv<W: Write>(
+    writer: &mut W,
+    parameter: &str,
+    results: &[(f64, ErrorRates)],
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        "{},frames,frame_errors,fer,fer_low,fer_high,bits,bit_errors,ber,ber_low,ber_high",
+        parameter
+    )?;
+    for &(value, ref rates) in results {
+        let (fer_low, fer_high) = rates.fer_interval();
+        let (ber_low, ber_high) = rates.ber_interval();
+        writeln!(
+            writer,
+            "{},{},{},{:e},{:e},{:e},{},{},{:e},{:e},{:e}",
+            value,
+            rates.frames,
+            rates.frame_errors,
+            rates.fer(),
+            fer_low,
+            fer_high,
+            rates.bits,
+            rates.bit_errors,
+            rates.ber(),
+            ber_low,
+            ber_high
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use channel::{BinarySymmetric, FixedWeight};
+    use linear_code::Exhaustive;
+    use rand::{SeedableRng, XorShiftRng};
+
+    fn rng() -> XorShiftRng {
+        XorShiftRng::from_seed([1, 2, 3, 4])
+    }
+
+    #[test]
+    fn intervals() {
+        assert_eq!(wilson_interval(0, 0), (0.0, 1.0));
+        let (low, high) = wilson_interval(0, 100);
+        assert_eq!(low, 0.0);
+        assert!(high > 0.0 && high < 0.05);
+        let (low, high) = wilson_interval(10, 100);
+        assert!(low < 0.1 && 0.1 < high);
+
+        let rates = ErrorRates {
+            frames: 100,
+            frame_errors: 10,
+            bits: 1000,
+            bit_errors: 25,
+        };
+        assert_eq!(rates.fer(), 0.1);
+        assert_eq!(rates.ber(), 0.025);
+        assert_eq!(ErrorRates::default().fer(), 0.0);
+    }
+
+    #[test]
+    fn stops_at_target_errors() {
+        let mut rng = rng();
+        let code = LinearCode::random(20, 10, &mut rng);
+        let simulation = Simulation {
+            target_errors: 30,
+            max_frames: 100_000,
+            batch: 10,
+            threads: 3,
+        };
+        let decoder = Exhaustive { max_weight: 2 };
+        let channel = BinarySymmetric { crossover: 0.2 };
+        let rates = simulation.run(&code, &decoder, &channel, &mut rng);
+        assert!(rates.frame_errors >= 30);
+        assert!(rates.frame_errors < 30 + 10);
+        assert_eq!(rates.frames % 10, 0);
+        assert_eq!(rates.bits, 20 * rates.frames);
+        assert!(rates.bit_errors >= rates.frame_errors);
+
+        let mut again = super::tests::rng();
+        LinearCode::random(20, 10, &mut again);
+        assert_eq!(simulation.run(&code, &decoder, &channel, &mut again), rates);
+
+        // the thread count changes the speed, not the result
+        for &threads in &[1, 2, 7] {
+            let mut again = super::tests::rng();
+            LinearCode::random(20, 10, &mut again);
+            let simulation = Simulation {
+                threads,
+                ..simulation
+            };
+            assert_eq!(simulation.run(&code, &decoder, &channel, &mut again), rates);
+        }
+    }
+
+    #[test]
+    fn stops_at_max_frames() {
+        let mut rng = rng();
+        let code = LinearCode::random(20, 10, &mut rng);
+        let simulation = Simulation {
+            target_errors: 1,
+            max_frames: 250,
+            batch: 100,
+            threads: 2,
+        };
+        let rates = simulation.run(
+            &code,
+            &Exhaustive { max_weight: 1 },
+            &BinarySymmetric { crossover: 0.0 },
+            &mut rng,
+        );
+        assert_eq!(rates.frames, 250);
+        assert_eq!(rates.frame_errors, 0);
+        assert_eq!(rates.bit_errors, 0);
+    }
+
+    #[test]
+    fn sweep_to_csv() {
+        let mut rng = rng();
+        let code = LinearCode::random(7, 4, &mut rng);
+        let simulation = Simulation {
+            target_errors: 20,
+            max_frames: 1000,
+            ..Simulation::default()
+        };
+        let results = simulation.sweep(
+            &code,
+            &Exhaustive { max_weight: 1 },
+            &[0.0, 3.0],
+            |weight| FixedWeight {
+                weight: weight as usize,
+            },
+            &mut rng,
+        );
+        assert_eq!(results[0].1.frames, 1000);
+        assert_eq!(results[0].1.frame_errors, 0);
+        // three errors are never corrected by decoding up to weight one
+        assert_eq!(results[1].1.frame_errors, results[1].1.frames);
+
+        let mut csv = Vec::new();
+        write_csv(&mut csv, "weight", &results).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("weight,frames,frame_errors,fer,"));
+        assert!(lines[1].starts_with("0,1000,0,0e0,0e0,"));
+        assert!(lines[2].starts_with("3,100,100,1e0,"));
+    }
+}
@@ -0,0 +1,102 @@
+//! Saving and loading matrices, codes and keys in the binary format of the
+//! `matrix` crate.
+//!
+//! Files are memory mapped when loading, so multi-megabyte public keys are
+//! read straight from the page cache. The kinds of object are:
+//!
+//! | kind | type                         |
+//! |------|------------------------------|
+//! | 1    | `matrix::BitMatrix`          |
+//! | 16   | `linear_code::LinearCode`    |
+//! | 17   | `goppa::GoppaCode`           |
+//! | 18   | `mceliece::PublicKey`        |
+//! | 19   | `mceliece::SecretKey`        |
+//! | 20   | `niederreiter::KemSecretKey` |
+//! | 21   | `mdpc::QcMdpcCode`           |
+
+use matrix::{BinaryError, BinaryFormat};
+use memmap::Mmap;
+use std::fs::File;
+use std::io::{self, Write};
+use std::ops::Deref;
+use std::path::Path;
+
+/// The contents of a file, memory mapped if it is not empty
+pub struct MappedFile {
+    map: Option<Mmap>,
+}
+
+impl Deref for MappedFile {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self.map {
+            Some(ref map) => map,
+            None => &[],
+        }
+    }
+}
+
+/// Map a file into memory for reading
+pub fn map<P: AsRef<Path>>(path: P) -> io::Result<MappedFile> {
+    let file = File::open(path)?;
+    // mapping an empty file fails on some platforms
+    if file.metadata()?.len() == 0 {
+        return Ok(MappedFile { map: None });
+    }
+    // the file should not be modified while it is mapped
+    let map = unsafe { Mmap::map(&file)? };
+    Ok(MappedFile { map: Some(map) })
+}
+
+/// Load an object from a file in the binary format
+pub fn load<T: BinaryFormat, P: AsRef<Path>>(path: P) -> Result<T, BinaryError> {
+    T::from_binary(&map(path)?)
+}
+
+/// Save an object to a file in the binary format
+pub fn save<T: BinaryFormat, P: AsRef<Path>>(value: &T, path: P) -> io::Result<()> {
+    File::create(path)?.write_all(&value.to_binary())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use matrix::BitMatrix;
+    use mceliece::{self, PublicKey, SecretKey, TOY};
+    use rand::{SeedableRng, XorShiftRng};
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn save_and_load_keys() {
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        let (public, secret) = mceliece::keygen(TOY, &mut rng);
+        let dir = env::temp_dir().join(format!("decoder-storage-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        save(&public, dir.join("public.bin")).unwrap();
+        save(&secret, dir.join("secret.bin")).unwrap();
+        assert_eq!(
+            load::<PublicKey, _>(dir.join("public.bin")).unwrap(),
+            public
+        );
+        assert_eq!(
+            load::<SecretKey, _>(dir.join("secret.bin")).unwrap(),
+            secret
+        );
+        match load::<BitMatrix, _>(dir.join("public.bin")) {
+            Err(BinaryError::WrongKind { .. }) => (),
+            other => panic!("{:?}", other),
+        }
+        fs::write(dir.join("empty.bin"), b"").unwrap();
+        match load::<BitMatrix, _>(dir.join("empty.bin")) {
+            Err(BinaryError::NotBinary) => (),
+            other => panic!("{:?}", other),
+        }
+        match load::<BitMatrix, _>(dir.join("missing.bin")) {
+            Err(BinaryError::Io(_)) => (),
+            other => panic!("{:?}", other),
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,222 @@
+//! Weight distributions of codes and the MacWilliams identity.
+//!
+//! The weight distribution `A_0, ..., A_n` of a code counts its codewords of
+//! every weight. It is found by enumerating all `2^k` codewords in Gray-code
+//! order, so that each one is the previous one plus a single row of the
+//! generator matrix. The distribution `B` of the dual code follows from the
+//! MacWilliams identity `B_j = 2^-k sum_i A_i K_j(i)`, with `K_j` the
+//! Krawtchouk polynomials, so only the smaller of the two is enumerated.
+
+use bit_vector::BitVector;
+use linear_code::LinearCode;
+use matrix::{BitMatrix, Matrix, Vector};
+use std::convert::TryFrom;
+
+impl LinearCode {
+    /// The number of codewords of every weight `0..=n`.
+    ///
+    /// This enumerates `2^min(k, n - k)` codewords, so it is only feasible
+    /// for codes of small dimension or redundancy.
+    pub fn weight_distribution(&self) -> Vec<u64> {
+        if self.dimension() <= self.redundancy() {
+            enumerate(self.generator_matrix())
+        } else {
+            let dual = self.dual();
+            macwilliams(&enumerate(dual.generator_matrix()), dual.dimension())
+        }
+    }
+}
+
+/// The weights of all combinations of the rows of `generator`
+fn enumerate(generator: &BitMatrix) -> Vec<u64> {
+    let (k, n) = (generator.nrows(), generator.ncols());
+    assert!(k < 64, "Too many codewords to enumerate");
+    let mut distribution = vec![0; n + 1];
+    distribution[0] = 1;
+    let mut word = BitVector::zero(n);
+    for i in 1..1u64 << k {
+        // the Gray codes of i - 1 and i differ in the lowest set bit of i
+        word ^= generator.row(i.trailing_zeros() as usize);
+        distribution[word.weight()] += 1;
+    }
+    distribution
+}
+
+fn binomial(n: usize, k: usize) -> i128 {
+    if k > n {
+        return 0;
+    }
+    (0..k.min(n - k)).fold(1, |c, i| c * (n - i) as i128 / (i + 1) as i128)
+}
+
+/// The Krawtchouk polynomial `K_j(x) = sum_s (-1)^s C(x, s) C(n - x, j - s)`:
+/// the sum of `(-1)^(u.v)` over the words `v` of weight `j`, for any word `u`
+/// of weight `x` and length `n`
+pub fn krawtchouk(n: usize, j: usize, x: usize) -> i128 {
+    (0..=j.min(x))
+        .map(|s| {
+            let term = binomial(x, s) * binomial(n - x, j - s);
+            if s % 2 == 0 {
+                term
+            } else {
+                -term
+            }
+        })
+        .sum()
+}
+
+/// The matrix with `K_j(i)` in row `i` and column `j`, for `i, j` in `0..=n`
+pub fn krawtchouk_matrix(n: usize) -> Matrix<i128> {
+    Matrix::from_vec(
+        (0..=n)
+            .map(|j| (0..=n).map(|i| krawtchouk(n, j, i)).collect())
+            .collect(),
+    )
+}
+
+/// The weight distribution of the dual of a code with dimension
+/// `dimension` and weight distribution `distribution`
+pub fn macwilliams(distribution: &[u64], dimension: usize) -> Vec<u64> {
+    let n = distribution.len() - 1;
+    // the sums are at most 2^dimension C(n, j), and the binomials are
+    // computed through n C(n, j) < 2^(n + 7)
+    assert!(
+        n + dimension.max(7) < 127,
+        "The transform of codes this long overflows"
+    );
+    let a: Vector<i128> = distribution.iter().map(|&a| i128::from(a)).collect();
+    (&a * &krawtchouk_matrix(n))
+        .into_iter()
+        .map(|b| {
+            assert!(
+                b >= 0 && b % (1 << dimension) == 0,
+                "Not the weight distribution of a code of this dimension"
+            );
+            u64::try_from(b >> dimension).expect("Too many codewords of one weight")
+        })
+        .collect()
+}
+
+/// The probability that the binary symmetric channel with the given
+/// crossover probability turns a codeword into another one, which no
+/// decoder can detect
+pub fn undetected_error_probability(distribution: &[u64], crossover: f64) -> f64 {
+    let n = distribution.len() - 1;
+    distribution
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, &a)| a as f64 * crossover.powi(i as i32) * (1.0 - crossover).powi((n - i) as i32))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linear_code::tests::{golay, hamming};
+    use rand::{SeedableRng, XorShiftRng};
+
+    fn rng() -> XorShiftRng {
+        XorShiftRng::from_seed([1, 2, 3, 4])
+    }
+
+    #[test]
+    fn krawtchouk_polynomials() {
+        assert_eq!(krawtchouk(7, 0, 3), 1);
+        for x in 0..=7 {
+            assert_eq!(krawtchouk(7, 1, x), 7 - 2 * x as i128);
+        }
+        assert_eq!(krawtchouk(4, 2, 1), 0);
+        assert_eq!(krawtchouk(4, 4, 1), -1);
+        // K K = 2^n I
+        let k = krawtchouk_matrix(6);
+        assert_eq!(
+            &k * &k,
+            Matrix::from_vec(
+                (0..7)
+                    .map(|j| (0..7).map(|i| if i == j { 64 } else { 0 }).collect())
+                    .collect()
+            )
+        );
+    }
+
+    #[test]
+    fn hamming_and_simplex() {
+        let code = hamming();
+        let distribution = code.weight_distribution();
+        assert_eq!(distribution, [1, 0, 0, 7, 7, 0, 0, 1]);
+        assert_eq!(enumerate(code.generator_matrix()), distribution);
+        assert_eq!(macwilliams(&distribution, 4), [1, 0, 0, 0, 7, 0, 0, 0]);
+
+        let p: f64 = 0.01;
+        let expected =
+            7.0 * p.powi(3) * (1.0 - p).powi(4) + 7.0 * p.powi(4) * (1.0 - p).powi(3) + p.powi(7);
+        assert!((undetected_error_probability(&distribution, p) - expected).abs() < 1e-15);
+    }
+
+    #[test]
+    fn golay_code() {
+        let code = golay();
+        let mut expected = vec![0; 24];
+        for &(weight, count) in &[
+            (0, 1),
+            (7, 253),
+            (8, 506),
+            (11, 1288),
+            (12, 1288),
+            (15, 506),
+            (16, 253),
+            (23, 1),
+        ] {
+            expected[weight] = count;
+        }
+        // enumerated through the dual
+        assert_eq!(code.weight_distribution(), expected);
+        assert_eq!(enumerate(code.generator_matrix()), expected);
+    }
+
+    #[test]
+    fn identity_is_an_involution() {
+        let mut rng = rng();
+        for &(n, k) in &[(12, 3), (15, 9), (20, 10), (9, 0), (9, 9)] {
+            let code = LinearCode::random(n, k, &mut rng);
+            let a = enumerate(code.generator_matrix());
+            assert_eq!(a.iter().sum::<u64>(), 1 << k);
+            assert_eq!(code.weight_distribution(), a);
+            let b = macwilliams(&a, k);
+            assert_eq!(b.iter().sum::<u64>(), 1 << (n - k));
+            assert_eq!(macwilliams(&b, n - k), a);
+        }
+    }
+
+    #[test]
+    fn long_code_of_small_redundancy() {
+        // the even-weight code of length 64, enumerated through its dual
+        let code = LinearCode::from_parity_check(BitMatrix::from_rows(
+            64,
+            vec![BitVector::from_bools(&[true; 64])],
+        ));
+        let distribution = code.weight_distribution();
+        for (weight, &count) in distribution.iter().enumerate() {
+            let expected = if weight % 2 == 0 {
+                binomial(64, weight)
+            } else {
+                0
+            };
+            assert_eq!(i128::from(count), expected);
+        }
+
+        let mut rng = rng();
+        let code = LinearCode::random(70, 64, &mut rng);
+        let distribution = code.weight_distribution();
+        assert_eq!(distribution[0], 1);
+        let total: i128 = distribution.iter().map(|&a| i128::from(a)).sum();
+        assert_eq!(total, 1 << 64);
+    }
+
+    #[test]
+    #[should_panic]
+    fn not_a_distribution() {
+        macwilliams(&[1, 0, 1, 0], 2);
+    }
+}
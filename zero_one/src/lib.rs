@@ -68,7 +68,10 @@ impl_zero!(u32, 0u32);
 impl_zero!(i32, 0i32);
 impl_zero!(u64, 0u64);
 impl_zero!(i64, 0i64);
+impl_zero!(i128, 0i128);
 impl_zero!(bool, false);
+impl_zero!(f32, 0.0f32);
+impl_zero!(f64, 0.0f64);
 
 macro_rules! impl_one {
     ($t: ty, $v: expr) => {
@@ -89,7 +92,10 @@ impl_one!(u32, 1u32);
 impl_one!(i32, 1i32);
 impl_one!(u64, 1u64);
 impl_one!(i64, 1i64);
+impl_one!(i128, 1i128);
 impl_one!(bool, true);
+impl_one!(f32, 1.0f32);
+impl_one!(f64, 1.0f64);
 
 #[cfg(test)]
 mod tests {
@@ -101,7 +107,8 @@ mod tests {
         assert_eq!(i32::zero(), 0i32);
         assert_eq!(u64::zero(), 0u64);
         assert_eq!(i64::zero(), 0i64);
-        assert_eq!(bool::zero(), false);
+        assert!(!bool::zero());
+        assert_eq!(f64::zero(), 0.0);
     }
 
     #[test]
@@ -110,6 +117,7 @@ mod tests {
         assert_eq!(i32::one(), 1i32);
         assert_eq!(u64::one(), 1u64);
         assert_eq!(i64::one(), 1i64);
-        assert_eq!(bool::one(), true);
+        assert!(bool::one());
+        assert_eq!(f32::one(), 1.0);
     }
 }